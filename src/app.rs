@@ -0,0 +1,342 @@
+//! Event loop shared by the CLI and embedders.
+//!
+//! `run` spawns the configured sources and feeds their events, scheduled runs and
+//! idle-watchdog firings into a `Runtime` until the sources end or a shutdown signal
+//! arrives. Reloaded configs (see `Runtime::config_update_sender`) are applied between
+//! events. Signals are injected as a channel so callers (and tests) decide where
+//! they come from; the CLI forwards every Ctrl+C into it.
+//!
+//! Events emitted by workflows (`emit_event`) are handled before the next source event,
+//! and the ones left are handled before `run` returns (unless the exit is forced).
+//! Source events lose any hop counter they carry: only emitted events may have one.
+//!
+//! Sources that keep counters (see `EventSource::metrics`) get a summary logged every
+//! `METRICS_LOG_INTERVAL`.
+//!
+//! Shutdown is two-staged:
+//! - First signal: stop the sources and drain the events already queued.
+//! - Second signal during the drain: stop immediately, dropping what is left.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use serde_json::Value;
+use tokio::sync::mpsc::{self, UnboundedReceiver};
+use tokio::time::{Instant, interval_at};
+use tracing::{debug, error, info, warn};
+
+use crate::executor::{IdleWatchdog, Runtime};
+use crate::sources::{self, EventSource, SourceMetrics};
+
+/// Capacity of the channel between sources and the runtime.
+const EVENT_CHANNEL_CAPACITY: usize = 256;
+
+/// How often source metrics are summarized in the log.
+const METRICS_LOG_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Why `run` returned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunExit {
+    /// Every source ended and all their events were handled.
+    SourcesEnded,
+    /// A shutdown signal arrived and the queued events were drained.
+    Drained,
+    /// A second signal arrived during the drain; remaining events were dropped.
+    Forced,
+}
+
+/// Run the event loop until the sources end or a shutdown signal is received.
+///
+/// Each message on `signals` counts as one shutdown request (e.g. one Ctrl+C).
+/// A closed `signals` channel is treated as "no more signals", not as a request.
+pub async fn run(
+    runtime: &mut Runtime,
+    sources: &[Box<dyn EventSource>],
+    mut signals: UnboundedReceiver<()>,
+) -> Result<RunExit> {
+    let (tx, mut rx) = mpsc::channel::<Value>(EVENT_CHANNEL_CAPACITY);
+    let (_handles, shutdown) = sources::spawn_all_sources(sources, tx);
+    let metrics = sources::source_metrics(sources);
+    let mut metrics_ticker =
+        interval_at(Instant::now() + METRICS_LOG_INTERVAL, METRICS_LOG_INTERVAL);
+
+    // Workflows scheduled by `schedule_workflow` actions come back through this channel
+    let mut scheduled = runtime
+        .take_schedule_receiver()
+        .ok_or_else(|| anyhow::anyhow!("Schedule receiver was already taken"))?;
+    // Events emitted by `emit_event` actions, handled before the next source event
+    let mut emitted = runtime
+        .take_emitted_event_receiver()
+        .ok_or_else(|| anyhow::anyhow!("Emitted event receiver was already taken"))?;
+    let mut config_updates = runtime
+        .take_config_update_receiver()
+        .ok_or_else(|| anyhow::anyhow!("Config update receiver was already taken"))?;
+
+    // Optional dead-man's switch, reset whenever an event is processed
+    let mut watchdog = runtime
+        .config()
+        .idle_watchdog
+        .as_ref()
+        .map(IdleWatchdog::new);
+
+    let mut signals_open = true;
+    loop {
+        tokio::select! {
+            biased;
+            signal = signals.recv(), if signals_open => {
+                if signal.is_none() {
+                    signals_open = false;
+                    continue;
+                }
+                info!(
+                    target: "notabot::runtime",
+                    queued = rx.len(),
+                    "Shutdown requested; draining queued events (signal again to force quit)"
+                );
+                break;
+            }
+            Some(event) = emitted.recv() => {
+                handle_event(runtime, &event).await;
+            }
+            maybe_event = rx.recv() => {
+                let Some(mut event) = maybe_event else {
+                    drain_emitted(runtime, &mut emitted).await;
+                    info!(target: "notabot::runtime", "All sources ended");
+                    return Ok(RunExit::SourcesEnded);
+                };
+                runtime.strip_event_hops(&mut event);
+                handle_event(runtime, &event).await;
+                if let Some(wd) = watchdog.as_mut() {
+                    wd.reset();
+                }
+            }
+            workflow = async { watchdog.as_mut().unwrap().expired().await }, if watchdog.is_some() => {
+                if let Err(err) = runtime.run_workflow_by_name(&workflow, Default::default()).await {
+                    error!(error = %err, %workflow, "Idle watchdog workflow failed");
+                }
+            }
+            Some(run) = scheduled.recv() => {
+                if let Err(err) = runtime.run_workflow_by_name(&run.workflow, run.vars).await {
+                    error!(error = %err, workflow = %run.workflow, "Scheduled workflow failed");
+                }
+            }
+            Some(config) = config_updates.recv() => {
+                runtime.replace_config(config);
+            }
+            _ = metrics_ticker.tick(), if !metrics.is_empty() => {
+                log_metrics(&metrics);
+            }
+        }
+    }
+
+    // Drain: stop producing, then handle whatever is already queued.
+    shutdown.cancel();
+    rx.close();
+    loop {
+        tokio::select! {
+            biased;
+            Some(()) = signals.recv(), if signals_open => {
+                warn!(
+                    target: "notabot::runtime",
+                    dropped = rx.len(),
+                    "Second shutdown signal; forcing exit without draining"
+                );
+                return Ok(RunExit::Forced);
+            }
+            Some(event) = emitted.recv() => {
+                handle_event(runtime, &event).await;
+                tokio::task::yield_now().await;
+            }
+            maybe_event = rx.recv() => {
+                let Some(mut event) = maybe_event else { break };
+                runtime.strip_event_hops(&mut event);
+                handle_event(runtime, &event).await;
+                // Let a pending signal be observed between events.
+                tokio::task::yield_now().await;
+            }
+        }
+    }
+    drain_emitted(runtime, &mut emitted).await;
+    info!(target: "notabot::runtime", "Drain complete");
+    Ok(RunExit::Drained)
+}
+
+fn log_metrics(metrics: &[(&'static str, Arc<SourceMetrics>)]) {
+    for (source, m) in metrics {
+        let m = m.snapshot();
+        info!(
+            target: "notabot::sources",
+            %source,
+            events_sent = m.events_sent,
+            parse_errors = m.parse_errors,
+            read_errors = m.read_errors,
+            skipped = m.skipped,
+            files_deleted = m.files_deleted,
+            delete_errors = m.delete_errors,
+            "Source metrics"
+        );
+    }
+}
+
+/// Handle the events emitted so far, including those emitted meanwhile. This ends:
+/// chains are cut off by `Config::max_event_hops`.
+async fn drain_emitted(runtime: &mut Runtime, emitted: &mut UnboundedReceiver<Value>) {
+    while let Ok(event) = emitted.try_recv() {
+        handle_event(runtime, &event).await;
+    }
+}
+
+async fn handle_event(runtime: &mut Runtime, event: &Value) {
+    if let Err(err) = runtime.run_event(event).await {
+        error!(error = %err, event = %event, "Failed to handle event");
+    } else {
+        debug!(target: "notabot::runtime", "Event handled");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ActionDef, Config, EventBinding};
+    use serde_json::json;
+    use std::collections::HashMap;
+    use std::path::Path;
+    use std::time::Duration;
+    use tokio::sync::mpsc::{Sender, unbounded_channel};
+    use tokio::task::JoinHandle;
+    use tokio_util::sync::CancellationToken;
+
+    /// Emits a fixed list of events, then stays alive until shut down.
+    struct FixedSource(Vec<Value>);
+
+    impl EventSource for FixedSource {
+        fn name(&self) -> &'static str {
+            "fixed"
+        }
+
+        fn start(&self, sender: Sender<Value>, shutdown: CancellationToken) -> JoinHandle<()> {
+            let events = self.0.clone();
+            tokio::spawn(async move {
+                for e in events {
+                    let _ = sender.send(e).await;
+                }
+                shutdown.cancelled().await;
+            })
+        }
+    }
+
+    /// Emits a fixed list of events, then ends.
+    struct FiniteSource(Vec<Value>);
+
+    impl EventSource for FiniteSource {
+        fn name(&self) -> &'static str {
+            "finite"
+        }
+
+        fn start(&self, sender: Sender<Value>, _shutdown: CancellationToken) -> JoinHandle<()> {
+            let events = self.0.clone();
+            tokio::spawn(async move {
+                for e in events {
+                    let _ = sender.send(e).await;
+                }
+            })
+        }
+    }
+
+    /// Five events, each taking ~40ms, each leaving `<dir>/<n>.json` behind.
+    fn slow_setup(dir: &Path) -> (Runtime, Vec<Box<dyn EventSource>>) {
+        let mut cfg = Config::default();
+        cfg.workflows.insert(
+            "slow".into(),
+            vec![
+                ActionDef::SleepMs { ms: 40 },
+                ActionDef::DumpState {
+                    path: format!("{}/{{{{n}}}}.json", dir.display()),
+                },
+            ],
+        );
+        cfg.events.insert(
+            "e".into(),
+            EventBinding {
+                workflow: "slow".into(),
+                vars_map: HashMap::from([("n".into(), "n".into())]),
+                schema: None,
+            },
+        );
+        let events = (0..5).map(|n| json!({"type": "e", "n": n})).collect();
+        let sources: Vec<Box<dyn EventSource>> = vec![Box::new(FixedSource(events))];
+        (Runtime::new(cfg, false), sources)
+    }
+
+    fn handled(dir: &Path) -> usize {
+        std::fs::read_dir(dir).unwrap().count()
+    }
+
+    async fn wait_for_first_event(dir: &Path) {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while handled(dir) == 0 {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("first event should be handled");
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn single_signal_drains_queued_events() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut rt, sources) = slow_setup(dir.path());
+        let (sig_tx, sig_rx) = unbounded_channel();
+        let task = tokio::spawn(async move { run(&mut rt, &sources, sig_rx).await });
+
+        wait_for_first_event(dir.path()).await;
+        sig_tx.send(()).unwrap();
+        assert_eq!(task.await.unwrap().unwrap(), RunExit::Drained);
+        assert_eq!(handled(dir.path()), 5);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn second_signal_short_circuits_drain() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut rt, sources) = slow_setup(dir.path());
+        let (sig_tx, sig_rx) = unbounded_channel();
+        let task = tokio::spawn(async move { run(&mut rt, &sources, sig_rx).await });
+
+        wait_for_first_event(dir.path()).await;
+        sig_tx.send(()).unwrap();
+        sig_tx.send(()).unwrap();
+        assert_eq!(task.await.unwrap().unwrap(), RunExit::Forced);
+        assert!(handled(dir.path()) < 5);
+    }
+
+    #[tokio::test]
+    async fn emitted_events_chain_until_the_hop_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let cfg: Config = serde_json::from_value(json!({
+            "workflows": {
+                "start": [ { "type": "emit_event", "event": { "type": "tick", "from": "{{$type}}" } } ],
+                "tick": [
+                    { "type": "dump_state", "path": format!("{}/{{{{$_hops}}}}-{{{{$from}}}}.json", dir.path().display()) },
+                    { "type": "emit_event", "event": { "type": "tick", "from": "tick" } }
+                ]
+            },
+            "events": { "start": { "workflow": "start" }, "tick": { "workflow": "tick" } },
+            "max_event_hops": 3
+        }))
+        .unwrap();
+        let mut rt = Runtime::new(cfg, false);
+        let sources: Vec<Box<dyn EventSource>> =
+            vec![Box::new(FiniteSource(vec![json!({ "type": "start" })]))];
+        let (_sig_tx, sig_rx) = unbounded_channel();
+
+        let exit = run(&mut rt, &sources, sig_rx).await.unwrap();
+        assert_eq!(exit, RunExit::SourcesEnded);
+        let mut files: Vec<String> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name().into_string().unwrap())
+            .collect();
+        files.sort();
+        assert_eq!(files, ["1-start.json", "2-tick.json", "3-tick.json"]);
+    }
+}
@@ -0,0 +1,188 @@
+//! Detect out-of-band edits to the config file of a running process.
+//!
+//! Without `--watch-config`, Notabot does not reload its config. When
+//! `config_drift_check_ms` is set, the CLI periodically compares the file on disk with
+//! the version it loaded and warns (once per distinct edit) that the running config is
+//! stale. Nothing is reloaded.
+
+use anyhow::{Context, Result};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+/// Modification time and content hash of the config file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Fingerprint {
+    modified: Option<SystemTime>,
+    hash: u64,
+}
+
+impl Fingerprint {
+    fn read(path: &Path) -> Result<Self> {
+        let modified = modified_time(path);
+        let bytes = std::fs::read(path)
+            .with_context(|| format!("Failed to read config file {}", path.display()))?;
+        let mut hasher = DefaultHasher::new();
+        bytes.hash(&mut hasher);
+        Ok(Self {
+            modified,
+            hash: hasher.finish(),
+        })
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Compares the on-disk config file against the version that was loaded.
+#[derive(Debug)]
+pub struct ConfigDriftWatcher {
+    path: PathBuf,
+    loaded: Fingerprint,
+    last_seen: Fingerprint,
+}
+
+impl ConfigDriftWatcher {
+    /// Take the baseline from the file's current contents (call right after loading it).
+    pub fn new(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let loaded = Fingerprint::read(&path)?;
+        Ok(Self {
+            path,
+            loaded,
+            last_seen: loaded,
+        })
+    }
+
+    /// Check the file once. Returns true (and logs a warning) when the content
+    /// differs from the loaded config in a way not reported before. Touching the
+    /// file without changing its content is not drift.
+    pub fn check(&mut self) -> Result<bool> {
+        // An unchanged mtime means an unchanged file: skip reading it
+        let modified = modified_time(&self.path);
+        if modified.is_some() && modified == self.last_seen.modified {
+            return Ok(false);
+        }
+        let current = Fingerprint::read(&self.path)?;
+        let previous = std::mem::replace(&mut self.last_seen, current);
+        if current.hash == previous.hash {
+            return Ok(false);
+        }
+        // The content changed, so it differed from the loaded config before this edit
+        if current.hash == self.loaded.hash {
+            info!(
+                target: "notabot",
+                path = %self.path.display(),
+                "Config file matches the running config again"
+            );
+            return Ok(false);
+        }
+        warn!(
+            target: "notabot",
+            path = %self.path.display(),
+            "Config file changed on disk; the running config is stale (restart to apply)"
+        );
+        Ok(true)
+    }
+
+    /// Check every `interval` in a background task, logging read errors.
+    pub fn spawn(mut self, interval: Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                if let Err(err) = self.check() {
+                    warn!(target: "notabot", error = %format!("{err:#}"), "Config drift check failed");
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    /// Log sink for `tracing_subscriber` shared with the test.
+    #[derive(Clone, Default)]
+    struct LogBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl std::io::Write for LogBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Set the file's mtime `secs` seconds later without touching its content.
+    fn touch(path: &Path, secs: u64) {
+        let file = std::fs::File::options().write(true).open(path).unwrap();
+        let modified = file.metadata().unwrap().modified().unwrap();
+        file.set_modified(modified + Duration::from_secs(secs))
+            .unwrap();
+    }
+
+    #[test]
+    fn touching_the_file_is_silent() {
+        let logs = LogBuffer::default();
+        let writer = logs.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || writer.clone())
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        std::fs::write(&path, r#"{"workflows": {}}"#).unwrap();
+        let mut watcher = ConfigDriftWatcher::new(&path).unwrap();
+
+        touch(&path, 10);
+        assert!(!watcher.check().unwrap());
+        let logged = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        assert!(logged.is_empty(), "unexpected logs: {logged}");
+
+        // After a real edit, touching the file again does not re-report it
+        std::fs::write(&path, r#"{"workflows": {"new": []}}"#).unwrap();
+        touch(&path, 20);
+        assert!(watcher.check().unwrap());
+        touch(&path, 30);
+        assert!(
+            !watcher.check().unwrap(),
+            "touch after an edit is not new drift"
+        );
+        let logged = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        assert_eq!(logged.matches("changed on disk").count(), 1, "{logged}");
+        assert!(
+            !logged.contains("matches the running config again"),
+            "{logged}"
+        );
+    }
+
+    #[test]
+    fn modifying_the_file_reports_drift_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        std::fs::write(&path, r#"{"workflows": {}}"#).unwrap();
+
+        let mut watcher = ConfigDriftWatcher::new(&path).unwrap();
+        assert!(!watcher.check().unwrap());
+
+        std::fs::write(&path, r#"{"workflows": {"new": []}}"#).unwrap();
+        assert!(watcher.check().unwrap());
+        assert!(!watcher.check().unwrap(), "same edit is reported once");
+
+        // Reverting to the loaded content is not drift.
+        std::fs::write(&path, r#"{"workflows": {}}"#).unwrap();
+        assert!(!watcher.check().unwrap());
+    }
+}
@@ -0,0 +1,122 @@
+//! Millisecond duration fields that also accept human-readable strings.
+//!
+//! Timing fields (`sleep_ms.ms`, `poll_ms`, `post_delay_ms`, timeouts, ...) take either a
+//! raw integer number of milliseconds or a string such as `"500ms"`, `"1.5s"`, `"2m"` or
+//! `"1h 30m"` (parsed with `humantime`). Values are always serialized back as integers.
+//!
+//! Use with `#[serde(deserialize_with = "duration::ms")]` (or `duration::opt_ms` for
+//! `Option<u64>`), plus `#[schemars(with = "DurationMs")]` so the schema allows both forms.
+
+use schemars::JsonSchema;
+use serde::de::{self, Deserializer, Visitor};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Schema-only description of a duration field: milliseconds or a human string.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum DurationMs {
+    /// Raw milliseconds.
+    Millis(u64),
+    /// Human-readable duration (e.g., "500ms", "1.5s", "2m").
+    Human(String),
+}
+
+/// Parse a human-readable duration string into whole milliseconds.
+pub fn parse_ms(s: &str) -> Result<u64, String> {
+    let duration =
+        humantime::parse_duration(s.trim()).map_err(|e| format!("invalid duration '{s}': {e}"))?;
+    u64::try_from(duration.as_millis()).map_err(|_| format!("duration '{s}' is too large"))
+}
+
+struct MsVisitor;
+
+impl<'de> Visitor<'de> for MsVisitor {
+    type Value = u64;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("milliseconds as an integer or a duration string like \"1.5s\"")
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<u64, E> {
+        Ok(v)
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<u64, E> {
+        u64::try_from(v).map_err(|_| E::custom(format!("duration must not be negative: {v}")))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<u64, E> {
+        parse_ms(v).map_err(E::custom)
+    }
+}
+
+/// Deserialize a `u64` millisecond field from an integer or a human string.
+pub fn ms<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    deserializer.deserialize_any(MsVisitor)
+}
+
+/// Like `ms`, for `Option<u64>` fields (`null` maps to `None`).
+pub fn opt_ms<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u64>, D::Error> {
+    #[derive(Deserialize)]
+    struct Wrapper(#[serde(deserialize_with = "ms")] u64);
+
+    Option::<Wrapper>::deserialize(deserializer).map(|w| w.map(|Wrapper(v)| v))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[derive(Debug, Deserialize)]
+    struct Timing {
+        #[serde(deserialize_with = "ms")]
+        ms: u64,
+        #[serde(default, deserialize_with = "opt_ms")]
+        poll: Option<u64>,
+    }
+
+    fn timing(v: serde_json::Value) -> Result<Timing, serde_json::Error> {
+        serde_json::from_value(v)
+    }
+
+    #[test]
+    fn integers_and_strings_parse_to_the_same_value() {
+        assert_eq!(timing(json!({"ms": 1500})).unwrap().ms, 1500);
+        assert_eq!(timing(json!({"ms": "1.5s"})).unwrap().ms, 1500);
+        assert_eq!(timing(json!({"ms": "500ms"})).unwrap().ms, 500);
+        assert_eq!(timing(json!({"ms": "1m"})).unwrap().ms, 60_000);
+    }
+
+    #[test]
+    fn action_timing_fields_accept_both_forms() {
+        use crate::config::ActionDef;
+        let a: ActionDef = serde_json::from_value(json!({"type": "sleep_ms", "ms": 1500})).unwrap();
+        let b: ActionDef =
+            serde_json::from_value(json!({"type": "sleep_ms", "ms": "1.5s"})).unwrap();
+        assert_eq!(a, b);
+        assert_eq!(
+            serde_json::to_value(&b).unwrap(),
+            json!({"type": "sleep_ms", "ms": 1500})
+        );
+    }
+
+    #[test]
+    fn optional_fields() {
+        assert_eq!(timing(json!({"ms": 1})).unwrap().poll, None);
+        assert_eq!(timing(json!({"ms": 1, "poll": null})).unwrap().poll, None);
+        assert_eq!(
+            timing(json!({"ms": 1, "poll": "2s"})).unwrap().poll,
+            Some(2000)
+        );
+    }
+
+    #[test]
+    fn invalid_values_error() {
+        let err = timing(json!({"ms": "soon"})).unwrap_err();
+        assert!(err.to_string().contains("invalid duration 'soon'"));
+        assert!(timing(json!({"ms": -5})).is_err());
+        assert!(timing(json!({"ms": true})).is_err());
+    }
+}
@@ -0,0 +1,153 @@
+//! Environment variable expansion in config string values.
+//!
+//! At load time every string value of the config may reference the environment:
+//! - `${NAME}`: the value of `NAME`; an unset variable is a load error.
+//! - `${NAME:-default}`: the value of `NAME`, or `default` when it is unset or empty.
+//! - `$${...}`: a literal `${...}` (no expansion).
+//!
+//! Only values are expanded (not map keys such as workflow or event names). A `$` that
+//! is not followed by `{` is kept as is.
+
+use anyhow::{Result, bail};
+use serde_json::Value;
+
+use super::models::Config;
+
+/// Expand `${VAR}` references in every string value of `cfg` using the process environment.
+pub fn expand_env_vars(cfg: Config) -> Result<Config> {
+    let mut value = serde_json::to_value(&cfg)?;
+    if !contains_reference(&value) {
+        return Ok(cfg);
+    }
+    expand_value(&mut value, &mut String::new(), &|name| {
+        std::env::var(name).ok()
+    })?;
+    Ok(serde_json::from_value(value)?)
+}
+
+fn contains_reference(value: &Value) -> bool {
+    match value {
+        Value::String(s) => s.contains("${"),
+        Value::Array(items) => items.iter().any(contains_reference),
+        Value::Object(map) => map.values().any(contains_reference),
+        _ => false,
+    }
+}
+
+/// Expand strings in place; `path` tracks the location for error messages.
+fn expand_value(
+    value: &mut Value,
+    path: &mut String,
+    lookup: &dyn Fn(&str) -> Option<String>,
+) -> Result<()> {
+    match value {
+        Value::String(s) if s.contains("${") => {
+            *s = expand_str(s, lookup).map_err(|e| e.context(format!("at '{path}'")))?;
+        }
+        Value::Array(items) => {
+            for (i, item) in items.iter_mut().enumerate() {
+                let len = path.len();
+                path.push_str(&format!("[{i}]"));
+                expand_value(item, path, lookup)?;
+                path.truncate(len);
+            }
+        }
+        Value::Object(map) => {
+            for (key, item) in map.iter_mut() {
+                let len = path.len();
+                if !path.is_empty() {
+                    path.push('.');
+                }
+                path.push_str(key);
+                expand_value(item, path, lookup)?;
+                path.truncate(len);
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Expand the `${...}` references of a single string with `lookup`.
+pub fn expand_str(input: &str, lookup: &dyn Fn(&str) -> Option<String>) -> Result<String> {
+    let mut out = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(pos) = rest.find("${") {
+        // `$${` escapes a literal `${`
+        if rest[..pos].ends_with('$') {
+            out.push_str(&rest[..pos - 1]);
+            out.push_str("${");
+            rest = &rest[pos + 2..];
+            continue;
+        }
+        out.push_str(&rest[..pos]);
+        let after = &rest[pos + 2..];
+        let Some(end) = after.find('}') else {
+            bail!("Unterminated environment variable reference in '{input}'");
+        };
+        let (name, default) = match after[..end].split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (&after[..end], None),
+        };
+        if !is_valid_name(name) {
+            bail!("Invalid environment variable name '{name}' in '{input}'");
+        }
+        match (lookup(name), default) {
+            (Some(v), Some(default)) if v.is_empty() => out.push_str(default),
+            (Some(v), _) => out.push_str(&v),
+            (None, Some(default)) => out.push_str(default),
+            (None, None) => bail!(
+                "Environment variable '{name}' is not set (use '${{{name}:-default}}' for a fallback)"
+            ),
+        }
+        rest = &after[end + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lookup(name: &str) -> Option<String> {
+        match name {
+            "HOME_DIR" => Some("/home/ada".into()),
+            "EMPTY" => Some(String::new()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn expand_str_cases() {
+        let cases = [
+            ("${HOME_DIR}/events", "/home/ada/events"),
+            ("no refs, $5 and {braces}", "no refs, $5 and {braces}"),
+            ("${MISSING:-fallback}", "fallback"),
+            ("${EMPTY:-fallback}", "fallback"),
+            ("${EMPTY}", ""),
+            ("${HOME_DIR:-unused}", "/home/ada"),
+            ("$${HOME_DIR}", "${HOME_DIR}"),
+            ("{{name}} in ${HOME_DIR}", "{{name}} in /home/ada"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(expand_str(input, &lookup).unwrap(), expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn expand_str_errors() {
+        let err = expand_str("${MISSING}", &lookup).unwrap_err();
+        assert!(err.to_string().contains("'MISSING' is not set"), "{err}");
+        assert!(expand_str("${HOME_DIR", &lookup).is_err());
+        assert!(expand_str("${1BAD}", &lookup).is_err());
+    }
+}
@@ -0,0 +1,236 @@
+//! Typed key names shared by keyboard actions.
+//!
+//! A `Key` deserializes from a string: either one of the portable named keys below
+//! (case-insensitive, with a few aliases such as `ctrl` or `enter`) or a single
+//! character (`"a"`, `"7"`, `"é"`). Anything else is rejected when the config is
+//! loaded, with the list of valid names in the error.
+
+use crossterm::event::KeyCode;
+use schemars::{JsonSchema, Schema, SchemaGenerator, json_schema};
+use serde::de::{self, Deserializer};
+use serde::{Deserialize, Serialize, Serializer};
+use std::borrow::Cow;
+use std::fmt;
+
+macro_rules! named_keys {
+    ($($variant:ident => $name:literal $(| $alias:literal)*),+ $(,)?) => {
+        /// A keyboard key: a portable named key or a single character.
+        #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+        pub enum Key {
+            $(
+                #[doc = concat!("`", $name, "`")]
+                $variant,
+            )+
+            /// A single character key (e.g., `"a"`).
+            Char(char),
+        }
+
+        impl Key {
+            /// Canonical names of all named keys.
+            pub const NAMES: &'static [&'static str] = &[$($name),+];
+
+            fn from_name(name: &str) -> Option<Self> {
+                match name {
+                    $($name $(| $alias)* => Some(Key::$variant),)+
+                    _ => None,
+                }
+            }
+
+            /// Canonical name (named keys) or the character itself.
+            pub fn name(&self) -> Cow<'static, str> {
+                match self {
+                    $(Key::$variant => Cow::Borrowed($name),)+
+                    Key::Char(c) => Cow::Owned(c.to_string()),
+                }
+            }
+        }
+    };
+}
+
+named_keys! {
+    Alt => "alt" | "option",
+    Backspace => "backspace",
+    CapsLock => "caps_lock" | "capslock",
+    Control => "control" | "ctrl",
+    LControl => "left_control" | "lctrl",
+    RControl => "right_control" | "rctrl",
+    Shift => "shift",
+    LShift => "left_shift" | "lshift",
+    RShift => "right_shift" | "rshift",
+    Meta => "meta" | "win" | "super" | "command" | "cmd",
+    Delete => "delete" | "del",
+    Enter => "enter" | "return",
+    Escape => "escape" | "esc",
+    Space => "space",
+    Tab => "tab",
+    Up => "up" | "up_arrow",
+    Down => "down" | "down_arrow",
+    Left => "left" | "left_arrow",
+    Right => "right" | "right_arrow",
+    Home => "home",
+    End => "end",
+    PageUp => "page_up" | "pageup" | "pgup",
+    PageDown => "page_down" | "pagedown" | "pgdn",
+    F1 => "f1",
+    F2 => "f2",
+    F3 => "f3",
+    F4 => "f4",
+    F5 => "f5",
+    F6 => "f6",
+    F7 => "f7",
+    F8 => "f8",
+    F9 => "f9",
+    F10 => "f10",
+    F11 => "f11",
+    F12 => "f12",
+    VolumeUp => "volume_up",
+    VolumeDown => "volume_down",
+    VolumeMute => "volume_mute",
+    MediaPlayPause => "media_play_pause",
+    MediaNextTrack => "media_next",
+    MediaPrevTrack => "media_prev",
+}
+
+impl Key {
+    /// Parse a key name (case-insensitive) or a single character.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let mut chars = s.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            return Ok(Key::Char(c));
+        }
+        Key::from_name(&s.trim().to_ascii_lowercase()).ok_or_else(|| {
+            format!(
+                "unknown key '{s}': expected a single character or one of: {}",
+                Key::NAMES.join(", ")
+            )
+        })
+    }
+}
+
+impl Key {
+    /// Console key code for this key, or `None` for keys a terminal does not report on
+    /// their own (modifiers, media and volume keys). Used to read `Config::arm_key`.
+    pub fn console_code(self) -> Option<KeyCode> {
+        let code = match self {
+            Key::Char(c) => KeyCode::Char(c),
+            Key::Space => KeyCode::Char(' '),
+            Key::Enter => KeyCode::Enter,
+            Key::Escape => KeyCode::Esc,
+            Key::Tab => KeyCode::Tab,
+            Key::Backspace => KeyCode::Backspace,
+            Key::Delete => KeyCode::Delete,
+            Key::Up => KeyCode::Up,
+            Key::Down => KeyCode::Down,
+            Key::Left => KeyCode::Left,
+            Key::Right => KeyCode::Right,
+            Key::Home => KeyCode::Home,
+            Key::End => KeyCode::End,
+            Key::PageUp => KeyCode::PageUp,
+            Key::PageDown => KeyCode::PageDown,
+            Key::F1 => KeyCode::F(1),
+            Key::F2 => KeyCode::F(2),
+            Key::F3 => KeyCode::F(3),
+            Key::F4 => KeyCode::F(4),
+            Key::F5 => KeyCode::F(5),
+            Key::F6 => KeyCode::F(6),
+            Key::F7 => KeyCode::F(7),
+            Key::F8 => KeyCode::F(8),
+            Key::F9 => KeyCode::F(9),
+            Key::F10 => KeyCode::F(10),
+            Key::F11 => KeyCode::F(11),
+            Key::F12 => KeyCode::F(12),
+            _ => return None,
+        };
+        Some(code)
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name())
+    }
+}
+
+impl std::str::FromStr for Key {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Key::parse(s)
+    }
+}
+
+impl Serialize for Key {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for Key {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Key::parse(&s).map_err(de::Error::custom)
+    }
+}
+
+impl JsonSchema for Key {
+    fn schema_name() -> Cow<'static, str> {
+        "Key".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "description": "A named key or a single character.",
+            "anyOf": [
+                { "type": "string", "enum": Key::NAMES },
+                { "type": "string", "minLength": 1, "maxLength": 1 }
+            ]
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(s: &str) -> Result<Key, serde_json::Error> {
+        serde_json::from_value(serde_json::Value::String(s.into()))
+    }
+
+    #[test]
+    fn parses_names_aliases_and_chars() {
+        assert_eq!(key("enter").unwrap(), Key::Enter);
+        assert_eq!(key("Return").unwrap(), Key::Enter);
+        assert_eq!(key("CTRL").unwrap(), Key::Control);
+        assert_eq!(key("a").unwrap(), Key::Char('a'));
+        assert_eq!(key("é").unwrap(), Key::Char('é'));
+        assert_eq!(
+            serde_json::to_string(&Key::PageDown).unwrap(),
+            "\"page_down\""
+        );
+    }
+
+    #[test]
+    fn unknown_key_fails_with_helpful_message() {
+        let err = key("entr").unwrap_err().to_string();
+        assert!(err.contains("unknown key 'entr'"), "{err}");
+        assert!(err.contains("enter"), "{err}");
+        assert!(key("").is_err());
+    }
+
+    #[test]
+    fn console_codes() {
+        assert_eq!(Key::F12.console_code(), Some(KeyCode::F(12)));
+        assert_eq!(Key::Char('a').console_code(), Some(KeyCode::Char('a')));
+        assert_eq!(Key::Space.console_code(), Some(KeyCode::Char(' ')));
+        assert_eq!(Key::Control.console_code(), None);
+        assert_eq!(Key::VolumeUp.console_code(), None);
+    }
+
+    #[test]
+    fn every_name_round_trips() {
+        for name in Key::NAMES {
+            let k = Key::parse(name).unwrap();
+            assert_eq!(k.name(), *name);
+        }
+    }
+}
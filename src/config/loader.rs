@@ -1,58 +1,139 @@
-use anyhow::{Context, Result, bail};
+use anyhow::{Context, Result, anyhow, bail};
 use schemars::{Schema, schema_for};
-use serde_json;
+use serde_json::{self, Value};
 use std::fs::File;
 use std::io::{Read, Write};
 use std::path::Path;
-use tracing::{debug, trace};
+use tracing::{debug, trace, warn};
 
-use super::models::{ActionDef, Config};
+use super::env::expand_env_vars;
+use super::models::{ActionDef, Config, SourceConfig};
+use crate::executor::runtime::DEFAULT_REGEX_GROUP;
+use crate::utils::interpolation::resolve_global_references;
+use crate::utils::screen::Rgb;
+use crate::utils::{http, window};
 
 /// Load configuration from a string slice.
 pub fn load_from_str(s: &str) -> Result<Config> {
-    let cfg: Config =
+    let mut cfg: Config =
         serde_json::from_str(s).context("Failed to parse JSON config string into Config")?;
+    prepare_config(&mut cfg)?;
+    validate_config(&cfg)?;
+    Ok(cfg)
+}
+
+/// Load configuration from a YAML string slice (same `Config` shape as JSON).
+pub fn load_from_str_yaml(s: &str) -> Result<Config> {
+    let mut cfg: Config =
+        serde_norway::from_str(s).context("Failed to parse YAML config string into Config")?;
+    prepare_config(&mut cfg)?;
     validate_config(&cfg)?;
     Ok(cfg)
 }
 
 /// Load configuration from any reader (e.g., a file).
 pub fn load_from_reader<R: Read>(reader: R) -> Result<Config> {
-    let cfg: Config =
+    let mut cfg: Config =
         serde_json::from_reader(reader).context("Failed to parse JSON config from reader")?;
+    prepare_config(&mut cfg)?;
     validate_config(&cfg)?;
     Ok(cfg)
 }
 
+/// Whether `path` names a YAML file (`.yaml` / `.yml`, any case). Everything else is JSON.
+fn is_yaml_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("yaml") || ext.eq_ignore_ascii_case("yml"))
+}
+
 /// Load configuration from a file path synchronously.
+/// `.yaml` / `.yml` files are parsed as YAML, anything else as JSON.
 pub fn load_from_path<P: AsRef<Path>>(path: P) -> Result<Config> {
     let path_ref = path.as_ref();
     let file = File::open(path_ref)
         .with_context(|| format!("Failed to open config file {}", path_ref.display()))?;
-    let cfg = load_from_reader(file)?;
+    let cfg = if is_yaml_path(path_ref) {
+        let mut cfg: Config = serde_norway::from_reader(file)
+            .with_context(|| format!("Failed to parse YAML config from {}", path_ref.display()))?;
+        prepare_config(&mut cfg)?;
+        validate_config(&cfg)?;
+        cfg
+    } else {
+        load_from_reader(file)?
+    };
     debug!("Loaded config from {}", path_ref.display());
     Ok(cfg)
 }
 
 /// Load configuration from a file path asynchronously (Tokio).
+/// `.yaml` / `.yml` files are parsed as YAML, anything else as JSON.
 pub async fn load_from_path_async<P: AsRef<Path>>(path: P) -> Result<Config> {
     use tokio::fs;
     let path_ref = path.as_ref();
     let bytes = fs::read(path_ref)
         .await
         .with_context(|| format!("Failed to read config file {}", path_ref.display()))?;
-    let cfg: Config = serde_json::from_slice(&bytes)
-        .with_context(|| format!("Failed to parse JSON config from {}", path_ref.display()))?;
+    let mut cfg: Config = if is_yaml_path(path_ref) {
+        serde_norway::from_slice(&bytes)
+            .with_context(|| format!("Failed to parse YAML config from {}", path_ref.display()))?
+    } else {
+        serde_json::from_slice(&bytes)
+            .with_context(|| format!("Failed to parse JSON config from {}", path_ref.display()))?
+    };
+    prepare_config(&mut cfg)?;
     validate_config(&cfg)?;
     debug!("Loaded config from {}", path_ref.display());
     Ok(cfg)
 }
 
+/// Built-in configuration used when the config file does not exist: reads events from
+/// stdin and logs `{ "type": "echo", "message": "..." }` events. No input is simulated.
+pub const EMBEDDED_DEFAULT_CONFIG: &str = include_str!("../../config/embedded.json");
+
+/// Parse the built-in configuration (see `EMBEDDED_DEFAULT_CONFIG`).
+pub fn embedded_default() -> Result<Config> {
+    load_from_str(EMBEDDED_DEFAULT_CONFIG).context("Embedded default config is invalid")
+}
+
+/// Like `load_from_path_async`, but falls back to the built-in configuration (with a
+/// warning) when `path` does not exist. A file that exists but fails to load is an error.
+pub async fn load_from_path_or_default_async<P: AsRef<Path>>(path: P) -> Result<Config> {
+    let path_ref = path.as_ref();
+    let exists = tokio::fs::try_exists(path_ref)
+        .await
+        .with_context(|| format!("Failed to check config file {}", path_ref.display()))?;
+    if !exists {
+        warn!(
+            path = %path_ref.display(),
+            "Config file not found; using the built-in default configuration"
+        );
+        return embedded_default();
+    }
+    load_from_path_async(path_ref).await
+}
+
+/// Load-time passes applied to every freshly parsed config, before validation.
+/// - Expands `${VAR}` environment references in string values (see `env`).
+/// - Resolves `{{@...}}` references between globals (see `resolve_global_references`).
+fn prepare_config(cfg: &mut Config) -> Result<()> {
+    *cfg = expand_env_vars(std::mem::take(cfg))?;
+    cfg.globals = resolve_global_references(&cfg.globals);
+    Ok(())
+}
+
 /// Generate the JSON Schema for the Config model (for external validation or tooling).
 pub fn generate_schema() -> Schema {
     schema_for!(Config)
 }
 
+/// Generate TypeScript declarations for the Config model (derived from `generate_schema`).
+/// The top-level type is `Config`; every schema definition gets its own declaration.
+pub fn generate_typescript() -> String {
+    let schema = generate_schema();
+    super::typescript::schema_to_typescript(schema.as_value(), "Config")
+}
+
 /// Write the JSON Schema for the Config model to any writer (pretty-printed).
 pub fn write_schema_to_writer<W: Write>(mut writer: W) -> Result<()> {
     let schema = generate_schema();
@@ -63,20 +144,51 @@ pub fn write_schema_to_writer<W: Write>(mut writer: W) -> Result<()> {
     Ok(())
 }
 
-/// Placeholder for schema-based validation.
-/// Currently a no-op. You can integrate a JSON Schema validator here if desired.
-/// Returns Ok(()) if validation passes or is skipped.
-pub fn validate_with_schema_placeholder(_config: &Config) -> Result<()> {
-    // Example idea:
-    // - Use `jsonschema` crate to compile `generate_schema()` and validate a serde_json::Value of the config.
-    // - Or derive `serde_valid::Validate` on models and call `validate()` here.
-    trace!("Schema validation placeholder (no-op)");
+/// Every violation of `validator` by `instance`, as `<json pointer>: <message>` lines.
+pub(crate) fn schema_violations(
+    validator: &jsonschema::Validator,
+    instance: &Value,
+) -> Vec<String> {
+    validator
+        .iter_errors(instance)
+        .map(|e| {
+            let path = e.instance_path().to_string();
+            let path = if path.is_empty() {
+                "/".to_string()
+            } else {
+                path
+            };
+            format!("{path}: {e}")
+        })
+        .collect()
+}
+
+/// Validate the config against its own JSON Schema (`generate_schema`).
+/// Catches structural mistakes that loose deserialization lets through; the error
+/// lists every violation with its JSON pointer.
+pub fn validate_against_schema(config: &Config) -> Result<()> {
+    let schema = generate_schema();
+    let validator = jsonschema::validator_for(schema.as_value())
+        .map_err(|e| anyhow!("Failed to compile the config schema: {}", e))?;
+    let value = serde_json::to_value(config).context("Failed to serialize config")?;
+    let violations = schema_violations(&validator, &value);
+    if !violations.is_empty() {
+        bail!(
+            "Config does not match its schema:\n  {}",
+            violations.join("\n  ")
+        );
+    }
+    trace!("Config matches its schema");
     Ok(())
 }
 
 /// Perform basic sanity checks and internal reference validation.
-/// - Ensure events reference existing workflows.
-/// - Ensure `Ref` actions reference existing named actions.
+/// - Ensure events (the default workflow and the idle watchdog) reference existing workflows.
+/// - Ensure `Ref` actions reference existing named actions, and actions that invoke
+///   workflows by a literal name (`schedule_workflow`, `call_workflow`) reference
+///   existing workflows.
+/// - Reject cycles of `ref`s and `call_workflow`s.
+/// - Check source settings that cannot wait until the source starts (see `validate_source`).
 pub fn validate_config(cfg: &Config) -> Result<()> {
     // Ensure events reference existing workflows
     for (event_type, binding) in &cfg.events {
@@ -87,6 +199,64 @@ pub fn validate_config(cfg: &Config) -> Result<()> {
                 binding.workflow
             );
         }
+        if let Some(schema) = &binding.schema {
+            jsonschema::validator_for(schema)
+                .map_err(|e| anyhow!("Event '{}' has an invalid schema: {}", event_type, e))?;
+        }
+    }
+
+    if let Some(workflow) = &cfg.default_workflow
+        && !cfg.workflows.contains_key(workflow)
+    {
+        bail!("default_workflow refers to missing workflow '{}'", workflow);
+    }
+
+    for name in cfg.action_meta.keys() {
+        if !cfg.actions.contains_key(name) {
+            bail!("action_meta refers to missing action '{}'", name);
+        }
+    }
+
+    for name in cfg.workflow_meta.keys() {
+        if !cfg.workflows.contains_key(name) {
+            bail!("workflow_meta refers to missing workflow '{}'", name);
+        }
+    }
+
+    if let Some(watchdog) = &cfg.idle_watchdog {
+        if watchdog.timeout_ms == 0 {
+            bail!("idle_watchdog.timeout_ms must be greater than 0");
+        }
+        if !cfg.workflows.contains_key(&watchdog.workflow) {
+            bail!(
+                "idle_watchdog refers to missing workflow '{}'",
+                watchdog.workflow
+            );
+        }
+    }
+
+    if cfg.config_drift_check_ms == Some(0) {
+        bail!("config_drift_check_ms must be greater than 0");
+    }
+
+    for (idx, source) in cfg.sources.iter().enumerate() {
+        validate_source(source).with_context(|| format!("Invalid source at index {}", idx))?;
+    }
+
+    if cfg.require_arm {
+        if let Some(key) = cfg.arm_key
+            && key.console_code().is_none()
+        {
+            bail!("arm_key '{}' cannot be read from the console", key);
+        }
+        // The arm key is read from the console, which a stdin source also consumes
+        if cfg
+            .sources
+            .iter()
+            .any(|source| matches!(source, SourceConfig::Stdin { .. }))
+        {
+            bail!("require_arm cannot be combined with a stdin source (both read the console)");
+        }
     }
 
     // Collect all names for fast lookup
@@ -95,67 +265,390 @@ pub fn validate_config(cfg: &Config) -> Result<()> {
         .keys()
         .cloned()
         .collect::<std::collections::BTreeSet<_>>();
+    let workflow_names = cfg
+        .workflows
+        .keys()
+        .cloned()
+        .collect::<std::collections::BTreeSet<_>>();
 
     // Validate refs within named actions
     for (name, action) in &cfg.actions {
-        validate_action_refs(action, &named_action_names)
+        validate_action_refs(action, &named_action_names, &workflow_names)
             .with_context(|| format!("Invalid reference in named action '{}'", name))?;
     }
 
     // Validate refs within workflows
     for (wf_name, steps) in &cfg.workflows {
         for (idx, step) in steps.iter().enumerate() {
-            validate_action_refs(step, &named_action_names).with_context(|| {
-                format!(
-                    "Invalid reference in workflow '{}' at step {}",
-                    wf_name, idx
-                )
+            validate_action_refs(step, &named_action_names, &workflow_names).with_context(
+                || format!("Invalid reference in workflow '{wf_name}' at step {idx}"),
+            )?;
+        }
+    }
+
+    // Refs resolve; now make sure they cannot recurse forever
+    validate_ref_cycles(cfg)?;
+
+    // Per-action checks: no input inside `parallel`, literal regexes compile, the
+    // action is supported by this build
+    for (name, action) in &cfg.actions {
+        validate_action(action, cfg)
+            .with_context(|| format!("Invalid action in named action '{}'", name))?;
+    }
+    for (wf_name, steps) in &cfg.workflows {
+        for (idx, step) in steps.iter().enumerate() {
+            validate_action(step, cfg).with_context(|| {
+                format!("Invalid action in workflow '{}' at step {}", wf_name, idx)
             })?;
         }
     }
 
-    // Optional schema step (currently a no-op)
-    validate_with_schema_placeholder(cfg)?;
+    // Safety net: the config must also match its generated schema
+    validate_against_schema(cfg)?;
+
+    Ok(())
+}
+
+/// Settings of an event source that would otherwise only fail (or panic) when it starts.
+fn validate_source(source: &SourceConfig) -> Result<()> {
+    if let SourceConfig::Http { path, .. } = source
+        && !path.starts_with('/')
+    {
+        bail!("http source path '{}' must start with '/'", path);
+    }
+    if let SourceConfig::WebSocket { url, .. } = source
+        && !url.starts_with("ws://")
+    {
+        bail!(
+            "websocket source url '{}' must start with 'ws://' (wss:// and other schemes are not supported)",
+            url
+        );
+    }
+    Ok(())
+}
+
+/// Checks on an action tree that go beyond references (see `validate_config`).
+fn validate_action(action: &ActionDef, cfg: &Config) -> Result<()> {
+    validate_parallel_steps(action, cfg)?;
+    validate_regex_patterns(action)?;
+    validate_pixel_colors(action)?;
+    validate_http_methods(action)?;
+    validate_action_support(action)
+}
 
+/// Reject actions that cannot work in this build (platform or cargo feature missing),
+/// so they fail at load time instead of on every run.
+fn validate_action_support(action: &ActionDef) -> Result<()> {
+    if let ActionDef::MouseMoveWindowRel { .. } = action
+        && !window::BOUNDS_SUPPORTED
+    {
+        bail!(
+            "mouse_move_window_rel is not supported on this platform (enable the `x11` feature on Linux/X11)"
+        );
+    }
+    if let ActionDef::HttpRequest { .. } | ActionDef::HttpPollUntil { .. } = action
+        && !cfg!(feature = "http-client")
+    {
+        bail!("{} requires the `http-client` feature", action.type_name());
+    }
+    for child in action.children() {
+        validate_action_support(child)?;
+    }
     Ok(())
 }
 
+/// Check literal (non-templated) `http_request` methods.
+fn validate_http_methods(action: &ActionDef) -> Result<()> {
+    if let ActionDef::HttpRequest { method, .. } = action
+        && !method.contains("{{")
+    {
+        http::parse_method(method).context("Invalid http_request `method`")?;
+    }
+    for child in action.children() {
+        validate_http_methods(child)?;
+    }
+    Ok(())
+}
+
+/// Compile `regex_extract` patterns without tokens, so mistakes fail at load time.
+fn validate_regex_patterns(action: &ActionDef) -> Result<()> {
+    if let ActionDef::RegexExtract { pattern, group, .. } = action
+        && !pattern.contains("{{")
+    {
+        let re = regex::Regex::new(pattern)
+            .with_context(|| format!("Invalid regex_extract pattern '{}'", pattern))?;
+        let group = group.unwrap_or(DEFAULT_REGEX_GROUP);
+        if group >= re.captures_len() {
+            bail!(
+                "regex_extract pattern '{}' has no capture group {}",
+                pattern,
+                group
+            );
+        }
+    }
+    for child in action.children() {
+        validate_regex_patterns(child)?;
+    }
+    Ok(())
+}
+
+/// Check literal (non-templated) `pixel_color_check` colors.
+fn validate_pixel_colors(action: &ActionDef) -> Result<()> {
+    if let ActionDef::PixelColorCheck { expected, .. } = action
+        && !expected.contains("{{")
+    {
+        Rgb::parse_hex(expected).context("Invalid pixel_color_check `expected` color")?;
+    }
+    for child in action.children() {
+        validate_pixel_colors(child)?;
+    }
+    Ok(())
+}
+
+/// A node of the reference graph walked by `validate_ref_cycles`.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum RefTarget<'a> {
+    Action(&'a str),
+    Workflow(&'a str),
+}
+
+impl std::fmt::Display for RefTarget<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RefTarget::Action(name) => write!(f, "action '{name}'"),
+            RefTarget::Workflow(name) => write!(f, "workflow '{name}'"),
+        }
+    }
+}
+
+/// Reject cycles of `ref`s and `call_workflow`s (e.g. `a -> b -> a`), which would
+/// otherwise only fail at runtime once the maximum nesting depth is reached.
+fn validate_ref_cycles(cfg: &Config) -> Result<()> {
+    let mut checked = std::collections::BTreeSet::new();
+    let roots = cfg
+        .actions
+        .keys()
+        .map(|name| RefTarget::Action(name))
+        .chain(cfg.workflows.keys().map(|name| RefTarget::Workflow(name)));
+    for root in roots {
+        find_ref_cycle(root, cfg, &mut Vec::new(), &mut checked)?;
+    }
+    Ok(())
+}
+
+/// Depth-first walk of the reference graph from `node`; `path` holds the references
+/// being followed.
+fn find_ref_cycle<'a>(
+    node: RefTarget<'a>,
+    cfg: &'a Config,
+    path: &mut Vec<RefTarget<'a>>,
+    checked: &mut std::collections::BTreeSet<RefTarget<'a>>,
+) -> Result<()> {
+    if let Some(start) = path.iter().position(|n| *n == node) {
+        let cycle: Vec<String> = path[start..]
+            .iter()
+            .chain(std::iter::once(&node))
+            .map(ToString::to_string)
+            .collect();
+        bail!("Reference cycle: {}", cycle.join(" -> "));
+    }
+    if checked.contains(&node) {
+        return Ok(());
+    }
+    let roots: Vec<&ActionDef> = match node {
+        RefTarget::Action(name) => cfg.actions.get(name).into_iter().collect(),
+        RefTarget::Workflow(name) => cfg.workflows.get(name).into_iter().flatten().collect(),
+    };
+    path.push(node);
+    for target in roots.into_iter().flat_map(referenced_targets) {
+        find_ref_cycle(target, cfg, path, checked)?;
+    }
+    path.pop();
+    checked.insert(node);
+    Ok(())
+}
+
+/// Named actions and workflows referenced anywhere in `action`.
+fn referenced_targets(action: &ActionDef) -> Vec<RefTarget<'_>> {
+    let mut targets = Vec::new();
+    match action {
+        ActionDef::Ref { name, .. } => targets.push(RefTarget::Action(name)),
+        ActionDef::CallWorkflow { name, .. } => targets.push(RefTarget::Workflow(name)),
+        _ => {}
+    }
+    for child in action.children() {
+        targets.extend(referenced_targets(child));
+    }
+    targets
+}
+
+/// Reject input actions anywhere inside the steps of a `parallel` action.
+fn validate_parallel_steps(action: &ActionDef, cfg: &Config) -> Result<()> {
+    if let ActionDef::Parallel { steps } = action {
+        for (i, step) in steps.iter().enumerate() {
+            if let Some(input) = find_input_action(step, cfg, &mut Vec::new()) {
+                bail!(
+                    "parallel step {} contains input action '{}' (input actions must run sequentially)",
+                    i,
+                    input
+                );
+            }
+        }
+    }
+    for child in action.children() {
+        validate_parallel_steps(child, cfg)?;
+    }
+    Ok(())
+}
+
+/// The type of the first input action in `action`, following `ref`s and `call_workflow`s.
+fn find_input_action<'a>(
+    action: &'a ActionDef,
+    cfg: &'a Config,
+    visiting: &mut Vec<RefTarget<'a>>,
+) -> Option<&'static str> {
+    if action.is_input() {
+        return Some(action.type_name());
+    }
+    let (node, targets): (RefTarget<'a>, Vec<&'a ActionDef>) = match action {
+        ActionDef::Ref { name, .. } => (
+            RefTarget::Action(name),
+            cfg.actions.get(name).into_iter().collect(),
+        ),
+        ActionDef::CallWorkflow { name, .. } => (
+            RefTarget::Workflow(name),
+            cfg.workflows.get(name).into_iter().flatten().collect(),
+        ),
+        _ => {
+            return action
+                .children()
+                .into_iter()
+                .find_map(|child| find_input_action(child, cfg, visiting));
+        }
+    };
+    // Cycles are rejected by `validate_ref_cycles`; this guard keeps the walk finite
+    if visiting.contains(&node) {
+        return None;
+    }
+    visiting.push(node);
+    let found = targets
+        .into_iter()
+        .find_map(|target| find_input_action(target, cfg, visiting));
+    visiting.pop();
+    found
+}
+
 fn validate_action_refs(
     action: &ActionDef,
     named_action_names: &std::collections::BTreeSet<String>,
+    workflow_names: &std::collections::BTreeSet<String>,
 ) -> Result<()> {
     match action {
-        ActionDef::Ref { name } => {
+        ActionDef::Ref { name, .. } => {
             if !named_action_names.contains(name) {
                 bail!("Referenced action '{}' was not found in `actions`", name);
             }
         }
+        // Templated names are only known at run time
+        ActionDef::ScheduleWorkflow { name, .. } => {
+            if !name.contains("{{") && !workflow_names.contains(name) {
+                bail!("Scheduled workflow '{}' was not found in `workflows`", name);
+            }
+        }
+        ActionDef::CallWorkflow { name, .. } => {
+            if !workflow_names.contains(name) {
+                bail!("Called workflow '{}' was not found in `workflows`", name);
+            }
+        }
         ActionDef::Sequence { steps } => {
             for (i, step) in steps.iter().enumerate() {
-                validate_action_refs(step, named_action_names)
+                validate_action_refs(step, named_action_names, workflow_names)
                     .with_context(|| format!("Invalid reference in sequence at index {}", i))?;
             }
         }
+        ActionDef::Parallel { steps } => {
+            for (i, step) in steps.iter().enumerate() {
+                validate_action_refs(step, named_action_names, workflow_names)
+                    .with_context(|| format!("Invalid reference in parallel at index {}", i))?;
+            }
+        }
+        ActionDef::Repeat { body, .. } => {
+            validate_action_refs(body, named_action_names, workflow_names)
+                .context("Invalid reference in repeat body")?;
+        }
+        ActionDef::While { body, .. } => {
+            validate_action_refs(body, named_action_names, workflow_names)
+                .context("Invalid reference in while body")?;
+        }
+        ActionDef::Retry { body, .. } => {
+            validate_action_refs(body, named_action_names, workflow_names)
+                .context("Invalid reference in retry body")?;
+        }
+        ActionDef::Try { body, catch } => {
+            validate_action_refs(body, named_action_names, workflow_names)
+                .context("Invalid reference in try body")?;
+            validate_action_refs(catch, named_action_names, workflow_names)
+                .context("Invalid reference in try `catch` branch")?;
+        }
         ActionDef::Conditional { then, else_, .. } => {
-            validate_action_refs(then, named_action_names)
+            validate_action_refs(then, named_action_names, workflow_names)
                 .context("Invalid reference in conditional `then` branch")?;
             if let Some(else_action) = else_ {
-                validate_action_refs(else_action, named_action_names)
+                validate_action_refs(else_action, named_action_names, workflow_names)
                     .context("Invalid reference in conditional `else` branch")?;
             }
         }
+        ActionDef::PixelColorCheck { then, else_, .. } => {
+            validate_action_refs(then, named_action_names, workflow_names)
+                .context("Invalid reference in pixel_color_check `then` branch")?;
+            if let Some(else_action) = else_ {
+                validate_action_refs(else_action, named_action_names, workflow_names)
+                    .context("Invalid reference in pixel_color_check `else` branch")?;
+            }
+        }
+        ActionDef::OcrCheck { then, else_, .. } => {
+            if let Some(then_action) = then {
+                validate_action_refs(then_action, named_action_names, workflow_names)
+                    .context("Invalid reference in ocr_check `then` branch")?;
+            }
+            if let Some(else_action) = else_ {
+                validate_action_refs(else_action, named_action_names, workflow_names)
+                    .context("Invalid reference in ocr_check `else` branch")?;
+            }
+        }
         // Leaf actions: nothing to validate
         ActionDef::MouseMove { .. }
+        | ActionDef::MouseMoveWindowRel { .. }
         | ActionDef::MouseClick { .. }
         | ActionDef::MouseScroll { .. }
+        | ActionDef::MouseDrag { .. }
+        | ActionDef::ClickGridCell { .. }
+        | ActionDef::GetCursor { .. }
+        | ActionDef::GetScreenSize { .. }
         | ActionDef::KeySeq { .. }
+        | ActionDef::KeyDown { .. }
+        | ActionDef::KeyUp { .. }
         | ActionDef::TypeText { .. }
         | ActionDef::SleepMs { .. }
         | ActionDef::SleepRandMs { .. }
+        | ActionDef::Abort { .. }
+        | ActionDef::EmitEvent { .. }
         | ActionDef::FocusWindow { .. }
+        | ActionDef::WaitForWindow { .. }
         | ActionDef::SetVar { .. }
+        | ActionDef::SetVarFromJson { .. }
+        | ActionDef::RegexExtract { .. }
+        | ActionDef::Math { .. }
+        | ActionDef::ClearVars { .. }
+        | ActionDef::ClipboardSet { .. }
+        | ActionDef::ClipboardGet { .. }
+        | ActionDef::RunCommand { .. }
+        | ActionDef::TcpSend { .. }
+        | ActionDef::HttpPollUntil { .. }
+        | ActionDef::HttpRequest { .. }
         | ActionDef::Log { .. }
-        | ActionDef::OcrCheck { .. }
+        | ActionDef::Comment { .. }
+        | ActionDef::DumpState { .. }
         | ActionDef::CaptureScreen { .. } => {}
     }
     Ok(())
@@ -8,17 +8,27 @@
 //!
 //! let cfg = load_from_path("config/default.json")?;
 
+pub mod drift;
+pub mod duration;
+pub mod env;
+pub mod keys;
 pub mod loader;
 pub mod models;
+pub mod typescript;
+pub mod watch;
+
+pub use keys::Key;
 
 // Re-export core data models
 pub use models::{
-    ActionDef, Config, EventBinding, EventMap, GlobalsMap, LogLevel, MouseButton, NamedActions,
-    Rect, SourceConfig, VarsMap, Workflows,
+    ActionDef, ActionMeta, CompareOp, Config, Coord, EventBinding, EventMap, FileDedup, GlobalsMap,
+    IdleWatchdogConfig, InputSettings, LogLevel, MathOp, MouseButton, NamedActions, Rect,
+    SourceConfig, TcpFraming, TlsConfig, TransformSpec, VarsMap, WorkflowMeta, Workflows,
 };
 
 // Re-export loader utilities
 pub use loader::{
-    generate_schema, load_from_path, load_from_path_async, load_from_reader, load_from_str,
-    validate_config, write_schema_to_writer,
+    EMBEDDED_DEFAULT_CONFIG, embedded_default, generate_schema, generate_typescript,
+    load_from_path, load_from_path_async, load_from_path_or_default_async, load_from_reader,
+    load_from_str, load_from_str_yaml, validate_config, write_schema_to_writer,
 };
@@ -2,6 +2,9 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, HashMap};
 
+use super::duration::{self, DurationMs};
+use super::keys::Key;
+
 /// Root configuration for Notabot.
 ///
 /// This structure is intended to be deserialized from a JSON configuration file.
@@ -11,9 +14,9 @@ use std::collections::{BTreeMap, HashMap};
 /// - `workflows` (named sequences of actions)
 /// - `events` bindings (event type -> workflow + variable mapping)
 /// - global variables available across workflows (`globals`)
-#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema, Default)]
 pub struct Config {
-    /// Event input sources (file, directory, tcp, stdin).
+    /// Event input sources (file, directory, tcp, udp, http, websocket, stdin).
     #[serde(default)]
     pub sources: Vec<SourceConfig>,
 
@@ -22,19 +25,147 @@ pub struct Config {
     #[serde(default)]
     pub actions: NamedActions,
 
+    /// Optional metadata per named action (e.g., `debounce_ms`).
+    #[serde(default)]
+    pub action_meta: BTreeMap<String, ActionMeta>,
+
     /// Named workflows, each a sequence of action definitions.
     /// Events typically refer to a workflow by name.
     #[serde(default)]
     pub workflows: Workflows,
 
+    /// Optional metadata per workflow name (e.g., tags used by `--only-tags`).
+    #[serde(default)]
+    pub workflow_meta: BTreeMap<String, WorkflowMeta>,
+
     /// Event bindings mapping an incoming event's `type` to a workflow and variable mapping.
+    /// Keys may use `*` wildcards (e.g. `order.*`). An exact key wins over wildcards;
+    /// among matching wildcards, the one with the longest text before its first `*` wins.
     #[serde(default)]
     pub events: EventMap,
 
+    /// Workflow run for events whose `type` has no binding (default: none, meaning such
+    /// events are an error). It gets no mapped variables; read fields with `{{$path}}`.
+    #[serde(default)]
+    pub default_workflow: Option<String>,
+
     /// Global variables accessible via interpolation (e.g., `{{@app_name}}`).
     /// Values can be any JSON value (string/number/bool/object/array).
     #[serde(default)]
     pub globals: GlobalsMap,
+
+    /// Dry-run "slow-motion" factor applied to sleeps (default: 0, meaning instant).
+    /// When nonzero, dry-run sleeps actually wait `ms * dry_run_time_scale` so the
+    /// log pacing matches a real run (e.g., `1.0` for real time, `0.5` for double speed).
+    #[serde(default)]
+    pub dry_run_time_scale: f32,
+
+    /// Allow events to pick a workflow directly with a `_workflow` field (default: false).
+    /// When enabled, such events bypass `events` bindings and every top-level event
+    /// field is exposed as a workflow variable. Only enable for trusted producers.
+    #[serde(default)]
+    pub allow_inline_workflow: bool,
+
+    /// Per-event-type rate limits: event `type` -> maximum events per second.
+    /// Excess events are dropped (logged at debug). Enforced with a token bucket that
+    /// allows bursts up to the limit; a limit of 0 drops every event of that type.
+    #[serde(default)]
+    pub rate_limits: BTreeMap<String, u32>,
+
+    /// Delay applied after every input action (mouse/keyboard) unless the action sets
+    /// its own `post_delay_ms` (default: none). Honored in dry-run via the time scale.
+    #[serde(default, deserialize_with = "duration::opt_ms")]
+    #[schemars(with = "Option<DurationMs>")]
+    pub default_post_delay_ms: Option<u64>,
+
+    /// Maximum number of `schedule_workflow` timers that may be pending at once (default: 64).
+    #[serde(default)]
+    pub max_pending_schedules: Option<usize>,
+
+    /// Longest chain of events emitted by `emit_event` from one original event
+    /// (default: 8). Guards against workflows that trigger each other forever.
+    #[serde(default)]
+    pub max_event_hops: Option<u32>,
+
+    /// Envelope key for batched events (default: "batch"; empty disables batching).
+    /// An event without a `type` whose envelope key holds an array, e.g.
+    /// `{ "batch": [ {event}, {event} ] }`, is unpacked and each element is handled
+    /// as an independent event, in order.
+    #[serde(default)]
+    pub batch_key: Option<String>,
+
+    /// Keep processing the remaining batch elements after one fails (default: false,
+    /// meaning the batch stops at the first error). Failures are logged either way.
+    #[serde(default)]
+    pub batch_continue_on_error: bool,
+
+    /// Dead-man's switch: run a recovery workflow when no event arrives for a while.
+    #[serde(default)]
+    pub idle_watchdog: Option<IdleWatchdogConfig>,
+
+    /// Periodically compare the config file on disk with the loaded one and warn when
+    /// it was edited (the running config is not reloaded). Off by default; must be
+    /// greater than 0 when set.
+    #[serde(default, deserialize_with = "duration::opt_ms")]
+    #[schemars(with = "Option<DurationMs>")]
+    pub config_drift_check_ms: Option<u64>,
+
+    /// Safe mode: start with input actions disabled (skipped and logged) until
+    /// `arm_key` is pressed in the Notabot console (default: false).
+    #[serde(default)]
+    pub require_arm: bool,
+
+    /// Key that arms input when `require_arm` is set (default: "f12"). Must be a key
+    /// a terminal can report: a character, a function key, arrows, enter, ...
+    #[serde(default)]
+    pub arm_key: Option<Key>,
+
+    /// Tuning of the input backend (Enigo). Unset fields keep Enigo's defaults.
+    #[serde(default)]
+    pub input_settings: InputSettings,
+}
+
+/// Enigo connection settings (see `Config::input_settings`). Each field only has an
+/// effect on the platform named in its description; unset fields keep Enigo's default.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct InputSettings {
+    /// X11 only: delay between simulated events, in milliseconds (Enigo default: 12).
+    #[serde(default)]
+    pub linux_delay: Option<u32>,
+    /// X11 only: display to connect to (default: `$DISPLAY`).
+    #[serde(default)]
+    pub x11_display: Option<String>,
+    /// Wayland only: display to connect to (default: `$WAYLAND_DISPLAY`).
+    #[serde(default)]
+    pub wayland_display: Option<String>,
+    /// All platforms: release keys still held (e.g., after a failed `key_down`/`key_up`
+    /// pair) when the input connection is dropped (Enigo default: true).
+    #[serde(default)]
+    pub release_keys_when_dropped: Option<bool>,
+    /// macOS only: prompt for the accessibility permission when it is missing
+    /// (Enigo default: true).
+    #[serde(default)]
+    pub open_prompt_to_get_permissions: Option<bool>,
+    /// macOS only: ignore physically held modifiers, so a held Shift does not capitalize
+    /// typed text (Enigo default: true).
+    #[serde(default)]
+    pub independent_of_keyboard_state: Option<bool>,
+    /// Windows only: apply the user's mouse speed and acceleration to relative moves
+    /// (Enigo default: false).
+    #[serde(default)]
+    pub windows_subject_to_mouse_speed_and_acceleration_level: Option<bool>,
+}
+
+/// Configuration of the idle watchdog (see `Config::idle_watchdog`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct IdleWatchdogConfig {
+    /// Silence (no event processed) after which the workflow runs, in milliseconds.
+    /// The timer is re-armed after every event and after every watchdog run.
+    #[serde(deserialize_with = "duration::ms")]
+    #[schemars(with = "DurationMs")]
+    pub timeout_ms: u64,
+    /// Workflow to run when the timeout elapses (with no variables).
+    pub workflow: String,
 }
 
 /// A convenient alias for named action map.
@@ -43,6 +174,31 @@ pub type NamedActions = BTreeMap<String, ActionDef>;
 /// Workflows are named lists of `ActionDef`s.
 pub type Workflows = BTreeMap<String, Vec<ActionDef>>;
 
+/// Metadata attached to a workflow by name in `Config::workflow_meta`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct WorkflowMeta {
+    /// Free-form labels (e.g., "smoke"). With `--only-tags`, only bindings to
+    /// workflows carrying one of the requested tags are active.
+    #[serde(default)]
+    pub tags: Vec<String>,
+
+    /// Abort the workflow if its steps have not finished within this long. The timeout
+    /// takes effect at the next await point (sleeps, post-delays, polling).
+    #[serde(default, deserialize_with = "duration::opt_ms")]
+    #[schemars(with = "Option<DurationMs>")]
+    pub timeout_ms: Option<u64>,
+}
+
+/// Metadata attached to a named action in `Config::action_meta`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ActionMeta {
+    /// Minimum interval between two runs of this action through `ref`, across the whole
+    /// runtime. A `ref` within the window is skipped (and logged).
+    #[serde(default, deserialize_with = "duration::opt_ms")]
+    #[schemars(with = "Option<DurationMs>")]
+    pub debounce_ms: Option<u64>,
+}
+
 /// Map of incoming event type -> binding.
 pub type EventMap = BTreeMap<String, EventBinding>;
 
@@ -56,7 +212,7 @@ pub type VarsMap = HashMap<String, String>;
 
 /// Event binding definition: connects an incoming event `type` to a workflow and
 /// optionally maps JSON fields from the event into workflow variables.
-#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct EventBinding {
     /// Workflow name to execute for this event type.
     pub workflow: String,
@@ -65,6 +221,11 @@ pub struct EventBinding {
     /// Example: `{ "message": "text" }` maps `{{message}}` to event's `text` field.
     #[serde(default)]
     pub vars_map: VarsMap,
+
+    /// JSON Schema the event must match before the workflow runs (optional).
+    /// Events that do not match are rejected with the list of violations.
+    #[serde(default)]
+    pub schema: Option<serde_json::Value>,
 }
 
 /// Event source configuration.
@@ -72,8 +233,11 @@ pub struct EventBinding {
 /// - "file": watch/read a single file repeatedly
 /// - "directory": watch a directory for new files
 /// - "tcp": listen on a TCP socket for JSON messages
+/// - "udp": receive one JSON value per UDP datagram
+/// - "http": accept POSTed JSON events (webhooks)
+/// - "websocket": connect to a WebSocket server and read JSON text frames
 /// - "stdin": read newline-delimited JSON from standard input
-#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum SourceConfig {
     /// Poll a single file for JSON events.
@@ -81,11 +245,18 @@ pub enum SourceConfig {
         /// Absolute or relative path to the file.
         path: String,
         /// Poll interval in milliseconds (default: 100).
-        #[serde(default)]
+        #[serde(default, deserialize_with = "duration::opt_ms")]
+        #[schemars(with = "Option<DurationMs>")]
         poll_ms: Option<u64>,
         /// Delete the file after a successful read/parse (default: false).
         #[serde(default)]
         delete_on_success: Option<bool>,
+        /// How an unchanged file is recognized when not deleting (default: `signature`).
+        #[serde(default)]
+        dedup: FileDedup,
+        /// Optional field mapping applied to each event before it is forwarded.
+        #[serde(default)]
+        transform: Option<TransformSpec>,
     },
 
     /// Watch a directory for new files that contain JSON events.
@@ -93,11 +264,37 @@ pub enum SourceConfig {
         /// Directory to watch.
         path: String,
         /// Optional file name pattern (e.g., "event_*" or "*.json").
+        /// Treated as one more `include` pattern.
         #[serde(default)]
         pattern: Option<String>,
+        /// Optional include patterns; a file must match at least one (if any are given).
+        #[serde(default)]
+        include: Option<Vec<String>>,
+        /// Optional exclude patterns; a file matching any of them is skipped
+        /// (e.g., `["*.tmp", "sentinel.json"]`).
+        #[serde(default)]
+        exclude: Option<Vec<String>>,
         /// Whether to watch subdirectories (default: false).
         #[serde(default)]
         recursive: Option<bool>,
+        /// Poll interval in milliseconds (default: 400, minimum: 10). At most one file
+        /// is dispatched per interval.
+        #[serde(default, deserialize_with = "duration::opt_ms")]
+        #[schemars(with = "Option<DurationMs>")]
+        poll_ms: Option<u64>,
+        /// Only process a file once its size and modification time have been unchanged
+        /// for this long (guards against half-written files; default: no check).
+        #[serde(default, deserialize_with = "duration::opt_ms")]
+        #[schemars(with = "Option<DurationMs>")]
+        require_stable_ms: Option<u64>,
+        /// Pick up new files from filesystem notifications instead of scanning the
+        /// directory every poll (default: false). Falls back to polling if watching
+        /// is not supported.
+        #[serde(default)]
+        watch: Option<bool>,
+        /// Optional field mapping applied to each event before it is forwarded.
+        #[serde(default)]
+        transform: Option<TransformSpec>,
     },
 
     /// Listen on a TCP address (e.g., "127.0.0.1:5000") for JSON events.
@@ -107,12 +304,96 @@ pub enum SourceConfig {
         /// Whether to send an ACK ("OK"/"ERROR") after processing (default: true).
         #[serde(default)]
         ack: Option<bool>,
+        /// Split a line holding a JSON array into one event per element (default: false).
+        /// The line is acknowledged once, after all elements were queued.
+        #[serde(default)]
+        batch: Option<bool>,
+        /// How messages are delimited (default: `lines`).
+        #[serde(default)]
+        framing: TcpFraming,
+        /// Shared secret: when set, clients must send `AUTH <token>` as their first
+        /// message and are disconnected on mismatch.
+        #[serde(default)]
+        token: Option<String>,
+        /// Serve TLS instead of plain TCP (requires the `tls` cargo feature).
+        #[serde(default)]
+        tls: Option<TlsConfig>,
+        /// Optional field mapping applied to each event before it is forwarded.
+        #[serde(default)]
+        transform: Option<TransformSpec>,
+    },
+
+    /// Receive JSON events as UDP datagrams (one value per datagram, no ACKs).
+    Udp {
+        /// Bind address and port (e.g., "127.0.0.1:5001").
+        bind: String,
+        /// Optional field mapping applied to each event before it is forwarded.
+        #[serde(default)]
+        transform: Option<TransformSpec>,
+    },
+
+    /// Accept JSON events POSTed to an HTTP endpoint (requires the `http` cargo feature).
+    Http {
+        /// Bind address and port (e.g., "127.0.0.1:8080").
+        bind: String,
+        /// Route accepting the POSTs (e.g., "/events").
+        path: String,
+        /// Split a body holding a JSON array into one event per element (default: false).
+        #[serde(default)]
+        batch: Option<bool>,
+        /// Optional field mapping applied to each event before it is forwarded.
+        #[serde(default)]
+        transform: Option<TransformSpec>,
+    },
+
+    /// Connect to a WebSocket server and read one JSON event per text frame
+    /// (requires the `websocket` cargo feature).
+    #[serde(rename = "websocket")]
+    WebSocket {
+        /// Server URL (e.g., "ws://127.0.0.1:9000/events"). Only `ws://` is supported.
+        url: String,
+        /// Reconnect with exponential backoff when the connection fails or drops
+        /// (default: true).
+        #[serde(default)]
+        reconnect: Option<bool>,
+        /// Optional field mapping applied to each event before it is forwarded.
+        #[serde(default)]
+        transform: Option<TransformSpec>,
     },
 
     /// Read JSON events from standard input (newline-delimited).
-    Stdin,
+    Stdin {
+        /// Optional field mapping applied to each event before it is forwarded.
+        #[serde(default)]
+        transform: Option<TransformSpec>,
+    },
 }
 
+impl SourceConfig {
+    /// The transform configured for this source, if any.
+    pub fn transform(&self) -> Option<&TransformSpec> {
+        match self {
+            SourceConfig::File { transform, .. }
+            | SourceConfig::Directory { transform, .. }
+            | SourceConfig::Tcp { transform, .. }
+            | SourceConfig::Udp { transform, .. }
+            | SourceConfig::Http { transform, .. }
+            | SourceConfig::WebSocket { transform, .. }
+            | SourceConfig::Stdin { transform } => transform.as_ref(),
+        }
+    }
+}
+
+/// Per-source event transform: target field path -> source field path (dotted).
+///
+/// Each mapped field is moved (renamed) from its source path to its target path;
+/// fields not mentioned are kept as-is. Intermediate objects are created as needed.
+/// Targets are written in sorted order, so when one target is nested in another
+/// (`a` and `a.b`), the longer one is written last.
+/// Example:
+///   { "transform": { "type": "t", "order.side": "side" } }
+pub type TransformSpec = BTreeMap<String, String>;
+
 /// Action definition.
 ///
 /// This is the heart of the runtime. Actions can be:
@@ -123,21 +404,107 @@ pub enum SourceConfig {
 /// By default, all string fields support interpolation with:
 /// - workflow variables: `{{var_name}}`
 /// - globals: `{{@global_key}}`
-#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum ActionDef {
     /// A sequence of actions executed in order.
     Sequence { steps: Vec<ActionDef> },
 
+    /// Run all `steps` concurrently and wait for them; the first failure stops the
+    /// others and fails the action. Each step starts from a copy of the variables, and
+    /// values set by the steps are merged back in step order.
+    ///
+    /// Input actions (mouse/keyboard, also through `ref`) are rejected: they share one
+    /// input device and must stay ordered. Waits (sleeps, commands, HTTP polling)
+    /// overlap; blocking actions such as `focus_window` still run one at a time.
+    Parallel { steps: Vec<ActionDef> },
+
+    /// Run `body` `count` times. The zero-based iteration is available to the body as
+    /// `{{repeat_index}}` (restored to its outer value afterwards, so loops can nest).
+    Repeat { count: u32, body: Box<ActionDef> },
+
+    /// Run `body` as long as `when` equals `equals`, both re-interpolated before every
+    /// pass (e.g., with a `set_var` in the body updating the loop variable).
+    While {
+        when: String,
+        equals: String,
+        body: Box<ActionDef>,
+        /// Fail instead of running the body more than this many times (default: 1000).
+        #[serde(default)]
+        max_iterations: Option<u32>,
+    },
+
+    /// Run `body`; if it fails, run `catch` instead of failing the workflow. The error
+    /// message is available to `catch` as `{{error}}`. A failing `catch` fails the action.
+    Try {
+        body: Box<ActionDef>,
+        catch: Box<ActionDef>,
+    },
+
+    /// Run `body` until it succeeds, at most `attempts` times in total, waiting
+    /// `delay_ms` after a failure (multiplied by `backoff` after every retry). The
+    /// one-based attempt number is available to the body as `{{attempt}}`. Fails with
+    /// the last error when every attempt failed.
+    Retry {
+        /// Total number of attempts (0 is treated as 1).
+        attempts: u32,
+        /// Wait before the first retry (milliseconds or a string like "500ms").
+        #[serde(deserialize_with = "duration::ms")]
+        #[schemars(with = "DurationMs")]
+        delay_ms: u64,
+        /// Factor applied to the delay after each retry (default: 1, a fixed delay).
+        #[serde(default)]
+        backoff: Option<f64>,
+        body: Box<ActionDef>,
+    },
+
     /// Reference a named action from the `actions` map.
+    ///
+    /// The referenced action runs in its own variable scope: it sees the caller's
+    /// variables, but anything it sets is discarded on return unless listed in `export`
+    /// (or `share_vars` is true). `args` are set as variables for the duration of the
+    /// call, so one named action can be reused with different inputs.
     Ref {
         /// The name of the action to reference.
         name: String,
+        /// Variables passed to the referenced action (values interpolated in the
+        /// caller's scope). They shadow caller variables of the same name, and the
+        /// caller's values are restored on return, even with `share_vars`.
+        #[serde(default)]
+        args: BTreeMap<String, String>,
+        /// Variables set inside the reference that are copied back to the caller.
+        #[serde(default)]
+        export: Vec<String>,
+        /// Opt out of scoping: run in the caller's scope so every change persists (default: false).
+        #[serde(default)]
+        share_vars: bool,
     },
 
     // --- Input: Mouse ---
     /// Move the mouse cursor to an absolute screen position.
-    MouseMove { x: i32, y: i32 },
+    MouseMove {
+        x: i32,
+        y: i32,
+        /// Wait this long after the action completes (overrides `default_post_delay_ms`).
+        #[serde(default, deserialize_with = "duration::opt_ms")]
+        #[schemars(with = "Option<DurationMs>")]
+        post_delay_ms: Option<u64>,
+    },
+
+    /// Move the mouse cursor relative to the top-left corner of a window
+    /// whose title contains `title_contains`. Fails if no such window is found.
+    MouseMoveWindowRel {
+        /// Window title substring (interpolated).
+        title_contains: String,
+        /// Horizontal offset from the window's left edge.
+        x: i32,
+        /// Vertical offset from the window's top edge.
+        y: i32,
+        /// Wait this long after the action completes (overrides `default_post_delay_ms`).
+        #[serde(default, deserialize_with = "duration::opt_ms")]
+        #[schemars(with = "Option<DurationMs>")]
+        post_delay_ms: Option<u64>,
+    },
 
     /// Click a mouse button one or more times.
     MouseClick {
@@ -145,6 +512,10 @@ pub enum ActionDef {
         /// Number of clicks (default: 1).
         #[serde(default)]
         count: Option<u8>,
+        /// Wait this long after the action completes (overrides `default_post_delay_ms`).
+        #[serde(default, deserialize_with = "duration::opt_ms")]
+        #[schemars(with = "Option<DurationMs>")]
+        post_delay_ms: Option<u64>,
     },
 
     /// Scroll the mouse wheel (pixels/lines; interpretation depends on executor).
@@ -156,38 +527,294 @@ pub enum ActionDef {
         /// Vertical scroll delta.
         #[serde(default)]
         delta_y: i32,
+        /// Split the deltas into this many smaller scrolls (default: 1, a single scroll;
+        /// at most the larger delta).
+        #[serde(default)]
+        steps: Option<u32>,
+        /// Pause between two scroll steps (default: none).
+        #[serde(default, deserialize_with = "duration::opt_ms")]
+        #[schemars(with = "Option<DurationMs>")]
+        step_delay_ms: Option<u64>,
+        /// Wait this long after the action completes (overrides `default_post_delay_ms`).
+        #[serde(default, deserialize_with = "duration::opt_ms")]
+        #[schemars(with = "Option<DurationMs>")]
+        post_delay_ms: Option<u64>,
+    },
+
+    /// Press a button at `from`, move to `to` while holding it, then release.
+    MouseDrag {
+        /// Start position `[x, y]` in screen coordinates.
+        from: (Coord, Coord),
+        /// End position `[x, y]` in screen coordinates.
+        to: (Coord, Coord),
+        /// Button held during the drag (default: left).
+        #[serde(default = "default_mouse_button")]
+        button: MouseButton,
+        /// Wait this long after the action completes (overrides `default_post_delay_ms`).
+        #[serde(default, deserialize_with = "duration::opt_ms")]
+        #[schemars(with = "Option<DurationMs>")]
+        post_delay_ms: Option<u64>,
+    },
+
+    /// Click the center of a cell in a uniform grid (calendars, game boards, ...).
+    /// Cell (0, 0) is the top-left one; its top-left corner is at (`origin_x`, `origin_y`).
+    ClickGridCell {
+        /// Screen X of the grid's top-left corner.
+        origin_x: i32,
+        /// Screen Y of the grid's top-left corner.
+        origin_y: i32,
+        /// Cell width in pixels.
+        cell_w: u32,
+        /// Cell height in pixels.
+        cell_h: u32,
+        /// Zero-based column index.
+        col: u32,
+        /// Zero-based row index.
+        row: u32,
+        /// Button to click (default: left).
+        #[serde(default = "default_mouse_button")]
+        button: MouseButton,
+        /// Wait this long after the action completes (overrides `default_post_delay_ms`).
+        #[serde(default, deserialize_with = "duration::opt_ms")]
+        #[schemars(with = "Option<DurationMs>")]
+        post_delay_ms: Option<u64>,
+    },
+
+    /// Store the current cursor position into two variables, e.g. to move back to it
+    /// later with `mouse_drag` or interpolated coordinates. Dry-run reports (0, 0).
+    GetCursor {
+        /// Variable receiving the X coordinate.
+        x_var: String,
+        /// Variable receiving the Y coordinate.
+        y_var: String,
+    },
+
+    /// Store the display size in pixels into two variables, e.g. to compute coordinates
+    /// relative to the screen. Dry-run reports 1920x1080.
+    GetScreenSize {
+        /// Variable receiving the width.
+        width_var: String,
+        /// Variable receiving the height.
+        height_var: String,
+        /// Display index; only the main display (0, the default) is supported.
+        #[serde(default)]
+        monitor: Option<usize>,
     },
 
     // --- Input: Keyboard ---
     /// Send a raw key sequence using Enigo's syntax
     /// e.g., "{WIN}rnotepad{ENTER}"
-    KeySeq { text: String },
+    KeySeq {
+        text: String,
+        /// Wait this long after the action completes (overrides `default_post_delay_ms`).
+        #[serde(default, deserialize_with = "duration::opt_ms")]
+        #[schemars(with = "Option<DurationMs>")]
+        post_delay_ms: Option<u64>,
+    },
+
+    /// Press and hold a key (e.g., "shift") until a matching `key_up`; keys still held
+    /// when the workflow ends (even by error or timeout) are released.
+    KeyDown {
+        key: Key,
+        /// Wait this long after the action completes (overrides `default_post_delay_ms`).
+        #[serde(default, deserialize_with = "duration::opt_ms")]
+        #[schemars(with = "Option<DurationMs>")]
+        post_delay_ms: Option<u64>,
+    },
+
+    /// Release a key pressed with `key_down`.
+    KeyUp {
+        key: Key,
+        /// Wait this long after the action completes (overrides `default_post_delay_ms`).
+        #[serde(default, deserialize_with = "duration::opt_ms")]
+        #[schemars(with = "Option<DurationMs>")]
+        post_delay_ms: Option<u64>,
+    },
 
     /// Type literal text (handles unicode).
-    TypeText { text: String },
+    TypeText {
+        text: String,
+        /// Type one character at a time, waiting this long between characters (for
+        /// fields that drop fast input). Unset types the whole text at once.
+        #[serde(default, deserialize_with = "duration::opt_ms")]
+        #[schemars(with = "Option<DurationMs>")]
+        per_char_ms: Option<u64>,
+        /// Wait this long after the action completes (overrides `default_post_delay_ms`).
+        #[serde(default, deserialize_with = "duration::opt_ms")]
+        #[schemars(with = "Option<DurationMs>")]
+        post_delay_ms: Option<u64>,
+    },
 
     // --- Timing & Control ---
     /// Sleep for a fixed duration in milliseconds.
-    SleepMs { ms: u64 },
+    SleepMs {
+        /// Duration (milliseconds or a string like "2s").
+        #[serde(deserialize_with = "duration::ms")]
+        #[schemars(with = "DurationMs")]
+        ms: u64,
+    },
 
     /// Sleep for a random duration in milliseconds within [min, max].
-    SleepRandMs { min: u64, max: u64 },
+    SleepRandMs {
+        /// Lower bound (milliseconds or a duration string).
+        #[serde(deserialize_with = "duration::ms")]
+        #[schemars(with = "DurationMs")]
+        min: u64,
+        /// Upper bound (milliseconds or a duration string).
+        #[serde(deserialize_with = "duration::ms")]
+        #[schemars(with = "DurationMs")]
+        max: u64,
+    },
+
+    /// Stop the current workflow here without an error: the remaining steps (including
+    /// those of enclosing loops and sequences) are skipped and the run counts as
+    /// successful. A `try` does not run its `catch`. Inside `parallel`, the other steps
+    /// still finish.
+    Abort {
+        /// Logged at info level (interpolated).
+        #[serde(default)]
+        message: Option<String>,
+    },
 
     // --- Window Management ---
     /// Attempt to focus a window whose title contains the given substring.
     FocusWindow { title_contains: String },
 
+    /// Wait until a window whose title contains `title_contains` exists (e.g., right
+    /// after launching an app), checking every `poll_ms`. Fails after `timeout_ms`.
+    /// Dry-run assumes the window is already there.
+    WaitForWindow {
+        /// Window title substring (interpolated).
+        title_contains: String,
+        /// Give up after this long.
+        #[serde(deserialize_with = "duration::ms")]
+        #[schemars(with = "DurationMs")]
+        timeout_ms: u64,
+        /// Delay between checks (default: 250 ms).
+        #[serde(default, deserialize_with = "duration::opt_ms")]
+        #[schemars(with = "Option<DurationMs>")]
+        poll_ms: Option<u64>,
+    },
+
+    /// Schedule a future run of another workflow after `delay_ms`, without blocking.
+    /// Values in `vars` are interpolated when the schedule is created.
+    ScheduleWorkflow {
+        /// Workflow name to run.
+        name: String,
+        /// Delay before the run, in milliseconds.
+        #[serde(deserialize_with = "duration::ms")]
+        #[schemars(with = "DurationMs")]
+        delay_ms: u64,
+        /// Variables passed to the scheduled run.
+        #[serde(default)]
+        vars: BTreeMap<String, String>,
+    },
+
+    /// Run another workflow's steps inline and wait for them to finish.
+    ///
+    /// The steps run in a fresh variable scope holding only `vars` (or a copy of the
+    /// caller's variables overridden by `vars` with `inherit_vars`); nothing they set
+    /// is copied back. An `abort` inside ends only the called workflow.
+    CallWorkflow {
+        /// Workflow name to run.
+        name: String,
+        /// Variables passed to the workflow (values interpolated in the caller's scope).
+        #[serde(default)]
+        vars: BTreeMap<String, String>,
+        /// Start from a copy of the caller's variables instead of an empty scope (default: false).
+        #[serde(default)]
+        inherit_vars: bool,
+    },
+
+    /// Feed a new event back into the event loop, queued like one from a source, so
+    /// one workflow can trigger another through its binding. String values are
+    /// interpolated (including `{{$path}}` fields of the current event).
+    ///
+    /// The emitted event carries a `_hops` count one higher than the current event's;
+    /// once it would exceed `max_event_hops`, the action fails instead of emitting.
+    EmitEvent {
+        /// The event to emit, normally with a `type` (e.g., `{ "type": "order.done" }`).
+        event: serde_json::Map<String, serde_json::Value>,
+    },
+
     // --- Logic & State ---
     /// Set (or override) a workflow-scoped variable.
     SetVar { name: String, value: String },
 
+    /// Set a variable from a field of the current event, read with a dotted JSON path
+    /// (like `vars_map`). Non-string values are stored as compact JSON; a missing path
+    /// stores an empty string and logs a warning.
+    SetVarFromJson {
+        /// Variable name (interpolated).
+        name: String,
+        /// Dotted path into the event (interpolated), e.g. `"order.side"`.
+        path: String,
+    },
+
+    /// Search `input` with a regular expression (Rust `regex` syntax) and store capture
+    /// `group` of the first match in `into_var`, or an empty string when nothing matches.
+    RegexExtract {
+        /// Text to search (interpolated).
+        input: String,
+        /// Pattern (interpolated). Patterns without tokens are compiled at load time.
+        pattern: String,
+        /// Variable receiving the capture.
+        into_var: String,
+        /// Capture group to store (default: 1; 0 is the whole match).
+        #[serde(default)]
+        group: Option<usize>,
+    },
+
+    /// Compute `lhs op rhs` on numbers and store the result in `into_var`. Both sides
+    /// are interpolated and must parse as numbers; whole results are written without a
+    /// fractional part (`"3"`, not `"3.0"`). Dividing by zero fails the action.
+    Math {
+        /// Variable receiving the result.
+        into_var: String,
+        /// Left operand (interpolated, e.g. `"{{count}}"`).
+        lhs: String,
+        op: MathOp,
+        /// Right operand (interpolated).
+        rhs: String,
+    },
+
+    /// Remove every workflow variable except those named in `keep`.
+    /// Reserved variables (names starting with `_`) are always preserved.
+    ClearVars {
+        /// Variables to keep.
+        #[serde(default)]
+        keep: Vec<String>,
+    },
+
     /// Conditionally execute `then` or `else` based on string equality:
     /// if interpolate(when) == interpolate(equals) => then, else otherwise.
+    ///
+    /// Either side can instead read the current event directly with `when_path` /
+    /// `equals_path` (dotted JSON path). A path takes precedence over the string on
+    /// the same side; a path missing from the event compares as an empty string.
+    ///
+    /// Types: variables are always strings. A side given as a path, or as exactly one
+    /// `{{@global}}` / `{{$field}}` token (no filters, no surrounding text), keeps its
+    /// JSON type. When such a side is a number, the comparison without `op` is numeric
+    /// (`{{@port}}` holding `8080` equals `"8080.0"`); other values compare by their
+    /// text (strings as-is, booleans as `true`/`false`, objects as compact JSON).
     Conditional {
         /// Left-hand side string (interpolated).
+        #[serde(default)]
         when: String,
+        /// Left-hand side event path; overrides `when` when set.
+        #[serde(default)]
+        when_path: Option<String>,
         /// Right-hand side string (interpolated).
+        #[serde(default)]
         equals: String,
+        /// Right-hand side event path; overrides `equals` when set.
+        #[serde(default)]
+        equals_path: Option<String>,
+        /// Comparison operator (`lhs op rhs`). Without it the sides are compared as
+        /// plain strings (or as numbers for typed numeric sides, see above).
+        #[serde(default)]
+        op: Option<CompareOp>,
         /// Action to run if the condition holds.
         then: Box<ActionDef>,
         /// Optional action to run otherwise.
@@ -196,30 +823,289 @@ pub enum ActionDef {
         else_: Option<Box<ActionDef>>,
     },
 
+    /// Replace the clipboard contents with text (interpolated; needs the `clipboard` feature).
+    /// Useful to paste long text instead of typing it.
+    ClipboardSet { text: String },
+
+    /// Read the clipboard text into a workflow variable (needs the `clipboard` feature).
+    ClipboardGet { into_var: String },
+
+    /// Run a program and wait for it to exit (no shell: `program` is executed directly).
+    RunCommand {
+        /// Program to run (interpolated; looked up on `PATH`).
+        program: String,
+        /// Arguments (each interpolated).
+        #[serde(default)]
+        args: Vec<String>,
+        /// Store the trimmed standard output in this workflow variable.
+        #[serde(default)]
+        capture_output_var: Option<String>,
+        /// Do not fail the workflow when the program exits with a non-zero code.
+        #[serde(default)]
+        ignore_exit_code: bool,
+        /// Kill the program and fail if it is still running after this long
+        /// (default: 60000 ms).
+        #[serde(default, deserialize_with = "duration::opt_ms")]
+        #[schemars(with = "Option<DurationMs>")]
+        timeout_ms: Option<u64>,
+    },
+
+    // --- Networking ---
+    /// Open a short-lived TCP connection, send one line, and optionally wait for an `OK` reply.
+    /// Useful to coordinate with a peer (e.g., another Notabot's TCP source with `ack` enabled).
+    TcpSend {
+        /// Peer address (e.g., "127.0.0.1:6000"; interpolated).
+        addr: String,
+        /// Line to send (interpolated). A trailing newline is appended.
+        line: String,
+        /// Wait for a response line and fail unless it is `OK` (default: false).
+        #[serde(default)]
+        expect_ack: bool,
+    },
+
+    /// Poll a JSON HTTP endpoint (GET) until the field at `path` equals `equals`.
+    /// Fails once `timeout_ms` elapses without a match. Request errors and
+    /// non-JSON responses count as "not yet" and are retried. Needs the `http-client`
+    /// feature (on by default).
+    HttpPollUntil {
+        /// Endpoint URL (interpolated).
+        url: String,
+        /// Dotted path of the field in the JSON response (e.g., "job.status").
+        path: String,
+        /// Expected value, compared as a string (interpolated).
+        equals: String,
+        /// Delay between polls (default: 1000 ms).
+        #[serde(default, deserialize_with = "duration::opt_ms")]
+        #[schemars(with = "Option<DurationMs>")]
+        interval_ms: Option<u64>,
+        /// Give up after this long (default: 30000 ms).
+        #[serde(default, deserialize_with = "duration::opt_ms")]
+        #[schemars(with = "Option<DurationMs>")]
+        timeout_ms: Option<u64>,
+    },
+
+    /// Send an HTTP request, e.g. to notify a backend. The response body can be stored
+    /// in `into_var`. Non-2xx responses fail the action unless `allow_error_status` is
+    /// set. Only logged in dry-run (nothing is sent and `into_var` is left unchanged).
+    /// Needs the `http-client` feature; without it the action fails.
+    HttpRequest {
+        /// HTTP method: GET, HEAD, POST, PUT, PATCH, DELETE or OPTIONS (case-insensitive,
+        /// interpolated).
+        method: String,
+        /// Endpoint URL (interpolated).
+        url: String,
+        /// Request body (interpolated).
+        #[serde(default)]
+        body: Option<String>,
+        /// Extra request headers (values interpolated).
+        #[serde(default)]
+        headers: BTreeMap<String, String>,
+        /// Variable receiving the response body.
+        #[serde(default)]
+        into_var: Option<String>,
+        /// Accept non-2xx responses instead of failing (default: false).
+        #[serde(default)]
+        allow_error_status: bool,
+    },
+
     // --- Logging ---
     /// Log a message with a chosen level.
     Log { level: LogLevel, message: String },
 
+    /// Do nothing: annotate a workflow step (JSON has no comments). The interpolated
+    /// text is logged at trace level. Also accepted as `noop`.
+    #[serde(alias = "noop")]
+    Comment {
+        #[serde(default)]
+        text: String,
+    },
+
+    /// Write the current workflow variables and globals as pretty JSON to a file
+    /// (parent directories are created). Useful to inspect state in the field.
+    DumpState {
+        /// Output file path (interpolated).
+        path: String,
+    },
+
     // --- Extensions (placeholders) ---
-    /// Check for OCR text presence in a region.
+    /// Check for OCR text presence in an image or a screen region (needs the `ocr`
+    /// feature).
+    /// Sets the `ocr_matched` variable to "true" or "false", then runs `then` when the
+    /// text was found and `else` otherwise (both optional).
     OcrCheck {
-        /// Screen region to scan. If omitted, implementation may use full screen.
+        /// Screen region to capture and scan when no `image` is given (default: the whole
+        /// main screen).
         #[serde(default)]
         region: Option<Rect>,
-        /// The text that must appear.
+        /// The text that must appear (case-insensitive).
         must_contain: String,
+        /// Image file to recognize instead of capturing the screen (e.g., the output of an
+        /// earlier `capture_screen`). Supports interpolation.
+        #[serde(default)]
+        image: Option<String>,
+        /// Action to run if the text was found.
+        #[serde(default)]
+        then: Option<Box<ActionDef>>,
+        /// Action to run if the text was not found.
+        #[serde(rename = "else")]
+        #[serde(default)]
+        else_: Option<Box<ActionDef>>,
+    },
+
+    /// Read the screen pixel at (`x`, `y`) and run `then` when its color is within
+    /// `tolerance` of `expected` on every channel, `else` otherwise. Needs Windows or the
+    /// `x11` feature on Linux. Dry-run takes the `then` branch.
+    PixelColorCheck {
+        x: i32,
+        y: i32,
+        /// Expected color as `#RRGGBB` (interpolated).
+        expected: String,
+        /// Maximum difference allowed per channel (default: 0, an exact match).
+        #[serde(default)]
+        tolerance: Option<u8>,
+        /// Action to run if the color matched.
+        then: Box<ActionDef>,
+        /// Action to run if the color did not match.
+        #[serde(rename = "else")]
+        #[serde(default)]
+        else_: Option<Box<ActionDef>>,
     },
 
     /// Capture a screenshot to a file.
     CaptureScreen {
-        /// Output file path (e.g., "screenshot.png").
+        /// Output file path; the capture is saved as a PNG (e.g., "screenshot.png").
         path: String,
         /// Optional region to capture.
         #[serde(default)]
         region: Option<Rect>,
+        /// Also copy the captured image to the clipboard (default: false).
+        /// Requires the `clipboard-image` cargo feature.
+        #[serde(default)]
+        to_clipboard: bool,
+        /// Store the final (interpolated) output path in this workflow variable after
+        /// a successful capture (also set in dry-run, where nothing is captured).
+        #[serde(default)]
+        save_path_to: Option<String>,
     },
 }
 
+impl ActionDef {
+    /// The action's `type` tag as written in the config (e.g., `"mouse_move"`).
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            ActionDef::Sequence { .. } => "sequence",
+            ActionDef::Parallel { .. } => "parallel",
+            ActionDef::Repeat { .. } => "repeat",
+            ActionDef::While { .. } => "while",
+            ActionDef::Try { .. } => "try",
+            ActionDef::Retry { .. } => "retry",
+            ActionDef::Ref { .. } => "ref",
+            ActionDef::MouseMove { .. } => "mouse_move",
+            ActionDef::MouseMoveWindowRel { .. } => "mouse_move_window_rel",
+            ActionDef::MouseClick { .. } => "mouse_click",
+            ActionDef::MouseScroll { .. } => "mouse_scroll",
+            ActionDef::MouseDrag { .. } => "mouse_drag",
+            ActionDef::ClickGridCell { .. } => "click_grid_cell",
+            ActionDef::GetCursor { .. } => "get_cursor",
+            ActionDef::GetScreenSize { .. } => "get_screen_size",
+            ActionDef::KeySeq { .. } => "key_seq",
+            ActionDef::KeyDown { .. } => "key_down",
+            ActionDef::KeyUp { .. } => "key_up",
+            ActionDef::TypeText { .. } => "type_text",
+            ActionDef::SleepMs { .. } => "sleep_ms",
+            ActionDef::SleepRandMs { .. } => "sleep_rand_ms",
+            ActionDef::Abort { .. } => "abort",
+            ActionDef::FocusWindow { .. } => "focus_window",
+            ActionDef::WaitForWindow { .. } => "wait_for_window",
+            ActionDef::ScheduleWorkflow { .. } => "schedule_workflow",
+            ActionDef::CallWorkflow { .. } => "call_workflow",
+            ActionDef::EmitEvent { .. } => "emit_event",
+            ActionDef::SetVar { .. } => "set_var",
+            ActionDef::SetVarFromJson { .. } => "set_var_from_json",
+            ActionDef::RegexExtract { .. } => "regex_extract",
+            ActionDef::Math { .. } => "math",
+            ActionDef::ClearVars { .. } => "clear_vars",
+            ActionDef::Conditional { .. } => "conditional",
+            ActionDef::ClipboardSet { .. } => "clipboard_set",
+            ActionDef::ClipboardGet { .. } => "clipboard_get",
+            ActionDef::RunCommand { .. } => "run_command",
+            ActionDef::TcpSend { .. } => "tcp_send",
+            ActionDef::HttpPollUntil { .. } => "http_poll_until",
+            ActionDef::HttpRequest { .. } => "http_request",
+            ActionDef::Log { .. } => "log",
+            ActionDef::Comment { .. } => "comment",
+            ActionDef::DumpState { .. } => "dump_state",
+            ActionDef::OcrCheck { .. } => "ocr_check",
+            ActionDef::PixelColorCheck { .. } => "pixel_color_check",
+            ActionDef::CaptureScreen { .. } => "capture_screen",
+        }
+    }
+
+    /// Whether this action simulates input (mouse/keyboard) through Enigo.
+    pub fn is_input(&self) -> bool {
+        matches!(
+            self,
+            ActionDef::MouseMove { .. }
+                | ActionDef::MouseMoveWindowRel { .. }
+                | ActionDef::MouseClick { .. }
+                | ActionDef::MouseScroll { .. }
+                | ActionDef::MouseDrag { .. }
+                | ActionDef::ClickGridCell { .. }
+                | ActionDef::KeySeq { .. }
+                | ActionDef::KeyDown { .. }
+                | ActionDef::KeyUp { .. }
+                | ActionDef::TypeText { .. }
+        )
+    }
+
+    /// Actions nested directly inside this one (`ref` targets are not followed).
+    pub fn children(&self) -> Vec<&ActionDef> {
+        match self {
+            ActionDef::Sequence { steps } | ActionDef::Parallel { steps } => steps.iter().collect(),
+            ActionDef::Repeat { body, .. }
+            | ActionDef::While { body, .. }
+            | ActionDef::Retry { body, .. } => vec![body],
+            ActionDef::Try { body, catch } => vec![body, catch],
+            ActionDef::Conditional { then, else_, .. }
+            | ActionDef::PixelColorCheck { then, else_, .. } => {
+                std::iter::once(&**then).chain(else_.as_deref()).collect()
+            }
+            ActionDef::OcrCheck { then, else_, .. } => then
+                .as_deref()
+                .into_iter()
+                .chain(else_.as_deref())
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// The per-action post-delay of an input action, if one is set.
+    pub fn post_delay_ms(&self) -> Option<u64> {
+        match self {
+            ActionDef::MouseMove { post_delay_ms, .. }
+            | ActionDef::MouseMoveWindowRel { post_delay_ms, .. }
+            | ActionDef::MouseClick { post_delay_ms, .. }
+            | ActionDef::MouseScroll { post_delay_ms, .. }
+            | ActionDef::MouseDrag { post_delay_ms, .. }
+            | ActionDef::ClickGridCell { post_delay_ms, .. }
+            | ActionDef::KeySeq { post_delay_ms, .. }
+            | ActionDef::KeyDown { post_delay_ms, .. }
+            | ActionDef::KeyUp { post_delay_ms, .. }
+            | ActionDef::TypeText { post_delay_ms, .. } => *post_delay_ms,
+            _ => None,
+        }
+    }
+}
+
+/// An integer coordinate, given literally (`120`) or as a template that must render
+/// to an integer (`"{{x}}"`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum Coord {
+    Value(i32),
+    Template(String),
+}
+
 /// A rectangle region on screen.
 #[derive(Debug, Copy, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 pub struct Rect {
@@ -229,6 +1115,39 @@ pub struct Rect {
     pub height: i32,
 }
 
+/// Server certificate of a `tcp` source with TLS.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct TlsConfig {
+    /// PEM file with the certificate chain (leaf first).
+    pub cert_path: String,
+    /// PEM file with the private key (PKCS#8, PKCS#1 or SEC1).
+    pub key_path: String,
+}
+
+/// Change detection of a `file` source that keeps its file.
+#[derive(Debug, Copy, Clone, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FileDedup {
+    /// Compare length and modification time (seconds). Cheap, but misses
+    /// same-length edits within one second.
+    #[default]
+    Signature,
+    /// Compare a hash of the trimmed contents (the file is read every poll).
+    Hash,
+}
+
+/// Message framing of a `tcp` source.
+#[derive(Debug, Copy, Clone, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TcpFraming {
+    /// Newline-delimited JSON (one value per line).
+    #[default]
+    Lines,
+    /// A 4-byte big-endian length, then that many bytes of UTF-8 JSON.
+    /// Binary-safe: payloads may contain newlines.
+    LengthPrefixed,
+}
+
 /// Mouse button enumeration.
 #[derive(Debug, Copy, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
@@ -238,6 +1157,42 @@ pub enum MouseButton {
     Right,
 }
 
+fn default_mouse_button() -> MouseButton {
+    MouseButton::Left
+}
+
+/// Comparison operator of a `conditional`.
+///
+/// When both sides parse as numbers they are compared numerically (`"2" lt "10"`).
+/// Otherwise `eq`/`ne` compare the strings and the ordering operators fail.
+#[derive(Debug, Copy, Clone, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CompareOp {
+    #[default]
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+/// Arithmetic operator of a `math` action.
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub enum MathOp {
+    #[serde(rename = "+")]
+    Add,
+    #[serde(rename = "-")]
+    Sub,
+    #[serde(rename = "*")]
+    Mul,
+    #[serde(rename = "/")]
+    Div,
+    /// Remainder, with the sign of `lhs` (`-7 % 3` is `-1`).
+    #[serde(rename = "%")]
+    Rem,
+}
+
 /// Logging level enumeration.
 #[derive(Debug, Copy, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
@@ -0,0 +1,247 @@
+//! Render the config JSON Schema as TypeScript declarations.
+//!
+//! A small generator covering the constructs `schemars` emits for our models:
+//! `$ref`/`$defs`, objects (`properties`/`required`/`additionalProperties`), arrays and
+//! tuples (`prefixItems`), `oneOf`/`anyOf` unions, `enum`/`const` literals and `type`
+//! (including lists such as `["string", "null"]`). Definitions that are plain objects
+//! become `interface`s; all others become `type` aliases. Descriptions are kept as
+//! JSDoc comments.
+
+use serde_json::{Map, Value};
+use std::fmt::Write as _;
+
+/// Convert a root JSON Schema (with `$defs`) into TypeScript. The root is named `root_name`.
+pub fn schema_to_typescript(schema: &Value, root_name: &str) -> String {
+    let mut out = String::from("// Generated from the Notabot config JSON Schema. Do not edit.\n");
+    out.push('\n');
+    write_definition(&mut out, root_name, schema);
+    if let Some(defs) = schema.get("$defs").and_then(Value::as_object) {
+        for (name, def) in defs {
+            out.push('\n');
+            write_definition(&mut out, name, def);
+        }
+    }
+    out
+}
+
+fn write_definition(out: &mut String, name: &str, schema: &Value) {
+    write_doc(out, schema, "");
+    if is_plain_object(schema) {
+        let _ = writeln!(
+            out,
+            "export interface {name} {}",
+            object_body(schema_map(schema), 0)
+        );
+    } else {
+        let _ = writeln!(out, "export type {name} = {};", ts_type(schema, 0));
+    }
+}
+
+fn schema_map(schema: &Value) -> &Map<String, Value> {
+    static EMPTY: std::sync::OnceLock<Map<String, Value>> = std::sync::OnceLock::new();
+    schema
+        .as_object()
+        .unwrap_or_else(|| EMPTY.get_or_init(Map::new))
+}
+
+fn is_plain_object(schema: &Value) -> bool {
+    schema.get("properties").is_some()
+        && schema.get("oneOf").is_none()
+        && schema.get("anyOf").is_none()
+}
+
+fn write_doc(out: &mut String, schema: &Value, indent: &str) {
+    let Some(desc) = schema.get("description").and_then(Value::as_str) else {
+        return;
+    };
+    let _ = writeln!(out, "{indent}/**");
+    for line in desc.lines() {
+        let line = line.replace("*/", "*\\/");
+        if line.is_empty() {
+            let _ = writeln!(out, "{indent} *");
+        } else {
+            let _ = writeln!(out, "{indent} * {line}");
+        }
+    }
+    let _ = writeln!(out, "{indent} */");
+}
+
+/// `{ ... }` for an object schema with `properties`, indented by `level`.
+fn object_body(schema: &Map<String, Value>, level: usize) -> String {
+    let indent = "  ".repeat(level + 1);
+    let required: Vec<&str> = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|r| r.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    let mut out = String::from("{\n");
+    if let Some(props) = schema.get("properties").and_then(Value::as_object) {
+        for (name, prop) in props {
+            write_doc(&mut out, prop, &indent);
+            let optional = if required.contains(&name.as_str()) {
+                ""
+            } else {
+                "?"
+            };
+            let _ = writeln!(
+                out,
+                "{indent}{}{optional}: {};",
+                property_name(name),
+                ts_type(prop, level + 1)
+            );
+        }
+    }
+    out.push_str(&"  ".repeat(level));
+    out.push('}');
+    out
+}
+
+fn property_name(name: &str) -> String {
+    let ident = name.chars().enumerate().all(|(i, c)| {
+        c == '_' || c == '$' || c.is_ascii_alphabetic() || (i > 0 && c.is_ascii_digit())
+    });
+    if ident && !name.is_empty() {
+        name.to_string()
+    } else {
+        Value::String(name.to_string()).to_string()
+    }
+}
+
+/// TypeScript type expression for a (sub)schema.
+fn ts_type(schema: &Value, level: usize) -> String {
+    let Some(map) = schema.as_object() else {
+        // `true` schema accepts anything; `false` nothing.
+        return if schema == &Value::Bool(false) {
+            "never".into()
+        } else {
+            "unknown".into()
+        };
+    };
+
+    if let Some(reference) = map.get("$ref").and_then(Value::as_str) {
+        return reference
+            .rsplit('/')
+            .next()
+            .unwrap_or("unknown")
+            .to_string();
+    }
+    if let Some(constant) = map.get("const") {
+        return constant.to_string();
+    }
+    if let Some(values) = map.get("enum").and_then(Value::as_array) {
+        return union(values.iter().map(Value::to_string).collect());
+    }
+    for key in ["oneOf", "anyOf"] {
+        if let Some(variants) = map.get(key).and_then(Value::as_array) {
+            return union(variants.iter().map(|v| ts_type(v, level)).collect());
+        }
+    }
+
+    match map.get("type") {
+        Some(Value::Array(types)) => union(
+            types
+                .iter()
+                .filter_map(Value::as_str)
+                .map(|t| primitive_type(t, map, level))
+                .collect(),
+        ),
+        Some(Value::String(t)) => primitive_type(t, map, level),
+        _ if map.contains_key("properties") => object_body(map, level),
+        _ => "unknown".into(),
+    }
+}
+
+fn primitive_type(ty: &str, map: &Map<String, Value>, level: usize) -> String {
+    match ty {
+        "string" => "string".into(),
+        "integer" | "number" => "number".into(),
+        "boolean" => "boolean".into(),
+        "null" => "null".into(),
+        "array" if map.contains_key("prefixItems") => {
+            let items: Vec<String> = map["prefixItems"]
+                .as_array()
+                .map(|items| items.iter().map(|i| ts_type(i, level)).collect())
+                .unwrap_or_default();
+            format!("[{}]", items.join(", "))
+        }
+        "array" => {
+            let item = map
+                .get("items")
+                .map(|i| ts_type(i, level))
+                .unwrap_or_else(|| "unknown".into());
+            if item.contains(' ') || item.contains('\n') {
+                format!("Array<{item}>")
+            } else {
+                format!("{item}[]")
+            }
+        }
+        "object" if map.contains_key("properties") => object_body(map, level),
+        "object" => {
+            let value = match map.get("additionalProperties") {
+                Some(Value::Bool(false)) => "never".into(),
+                Some(schema @ Value::Object(_)) => ts_type(schema, level),
+                _ => "unknown".into(),
+            };
+            format!("Record<string, {value}>")
+        }
+        _ => "unknown".into(),
+    }
+}
+
+fn union(mut members: Vec<String>) -> String {
+    members.dedup();
+    match members.len() {
+        0 => "never".into(),
+        1 => members.remove(0),
+        _ => members.join(" | "),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn renders_common_constructs() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "name": { "type": "string", "description": "The name." },
+                "tags": { "type": "array", "items": { "type": "string" } },
+                "limit": { "type": ["integer", "null"] },
+                "kind": { "$ref": "#/$defs/Kind" },
+                "extra": { "type": "object", "additionalProperties": { "type": "boolean" } }
+            },
+            "required": ["name"],
+            "$defs": {
+                "Kind": { "type": "string", "enum": ["a", "b"] }
+            }
+        });
+        let ts = schema_to_typescript(&schema, "Root");
+        assert!(ts.contains("export interface Root {"), "{ts}");
+        assert!(
+            ts.contains("  /**\n   * The name.\n   */\n  name: string;"),
+            "{ts}"
+        );
+        assert!(ts.contains("  tags?: string[];"), "{ts}");
+        assert!(ts.contains("  limit?: number | null;"), "{ts}");
+        assert!(ts.contains("  kind?: Kind;"), "{ts}");
+        assert!(ts.contains("  extra?: Record<string, boolean>;"), "{ts}");
+        assert!(ts.contains(r#"export type Kind = "a" | "b";"#), "{ts}");
+    }
+
+    #[test]
+    fn config_typescript_has_root_interface_and_action_union() {
+        let ts = crate::config::generate_typescript();
+        assert!(ts.contains("export interface Config {"), "{ts}");
+        assert!(
+            ts.contains("  workflows?: Record<string, ActionDef[]>;"),
+            "{ts}"
+        );
+        assert!(ts.contains("export type ActionDef = {"), "{ts}");
+        assert!(ts.contains(r#"type: "mouse_move";"#), "{ts}");
+        assert!(ts.contains("  from: [Coord, Coord];"), "{ts}");
+        assert!(ts.contains(r#"export type MouseButton = "left" | "middle" | "right";"#));
+    }
+}
@@ -0,0 +1,162 @@
+//! Hot-reload the config file of a running process (`--watch-config`).
+//!
+//! `ConfigWatcher` watches the config file with `notify`. After each change it reloads
+//! and re-validates the file and sends the new `Config` to the runtime (see
+//! `Runtime::config_update_sender`), which swaps it in between workflows. A reload that
+//! fails to parse or validate is logged and ignored; the previous config stays active.
+
+use anyhow::{Context, Result};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::sync::mpsc::{UnboundedSender, unbounded_channel};
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+use super::loader::load_from_path_async;
+use super::models::Config;
+
+/// Quiet period after a change before reloading, so editors that write in several
+/// steps (truncate, write, rename) trigger a single reload of the final content.
+const RELOAD_DEBOUNCE: Duration = Duration::from_millis(150);
+
+/// Watches a config file and sends every successfully reloaded `Config`.
+/// Watching stops when the watcher is dropped.
+pub struct ConfigWatcher {
+    _watcher: RecommendedWatcher,
+    task: JoinHandle<()>,
+}
+
+impl ConfigWatcher {
+    /// Start watching `path`, sending each valid new config on `updates`.
+    ///
+    /// The parent directory is watched (not the file itself) so atomic saves that
+    /// replace the file are picked up too.
+    pub fn spawn(path: impl Into<PathBuf>, updates: UnboundedSender<Config>) -> Result<Self> {
+        let path = path.into();
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_os_string())
+            .with_context(|| format!("Config path {} has no file name", path.display()))?;
+        let dir = match path.parent() {
+            Some(p) if !p.as_os_str().is_empty() => p.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+
+        let (changed_tx, mut changed_rx) = unbounded_channel::<()>();
+        let mut watcher =
+            notify::recommended_watcher(move |res: notify::Result<notify::Event>| match res {
+                Ok(event) if touches_file(&event, &file_name) => {
+                    let _ = changed_tx.send(());
+                }
+                Ok(_) => {}
+                Err(err) => warn!(target: "notabot", error = %err, "Config watch error"),
+            })
+            .context("Failed to create config file watcher")?;
+        watcher
+            .watch(&dir, RecursiveMode::NonRecursive)
+            .with_context(|| format!("Failed to watch {}", dir.display()))?;
+        info!(target: "notabot", path = %path.display(), "Watching config file for changes");
+
+        let task = tokio::spawn(async move {
+            while changed_rx.recv().await.is_some() {
+                tokio::time::sleep(RELOAD_DEBOUNCE).await;
+                while changed_rx.try_recv().is_ok() {}
+                if let Some(config) = reload(&path).await
+                    && updates.send(config).is_err()
+                {
+                    debug!(target: "notabot", "Runtime is gone; stopping config watcher");
+                    break;
+                }
+            }
+        });
+
+        Ok(Self {
+            _watcher: watcher,
+            task,
+        })
+    }
+}
+
+impl Drop for ConfigWatcher {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Whether `event` modifies (or creates/removes) the file named `file_name`.
+fn touches_file(event: &notify::Event, file_name: &OsString) -> bool {
+    !matches!(event.kind, EventKind::Access(_))
+        && event
+            .paths
+            .iter()
+            .any(|p| p.file_name() == Some(file_name.as_os_str()))
+}
+
+/// Load and validate `path`, logging (and swallowing) failures.
+async fn reload(path: &Path) -> Option<Config> {
+    match load_from_path_async(path).await {
+        Ok(config) => {
+            info!(target: "notabot", path = %path.display(), "Config file changed; reloaded");
+            Some(config)
+        }
+        Err(err) => {
+            warn!(
+                target: "notabot",
+                path = %path.display(),
+                error = %format!("{err:#}"),
+                "Config reload failed; keeping the previous config"
+            );
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::mpsc::UnboundedReceiver;
+
+    async fn next_config(rx: &mut UnboundedReceiver<Config>, wait: Duration) -> Option<Config> {
+        tokio::time::timeout(wait, rx.recv()).await.ok().flatten()
+    }
+
+    #[tokio::test]
+    async fn valid_edits_are_sent_and_invalid_ones_ignored() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        std::fs::write(&path, r#"{"workflows": {}}"#).unwrap();
+
+        let (tx, mut rx) = unbounded_channel();
+        let _watcher = ConfigWatcher::spawn(&path, tx).unwrap();
+
+        std::fs::write(&path, r#"{"workflows": {"new": []}}"#).unwrap();
+        let config = next_config(&mut rx, Duration::from_secs(5))
+            .await
+            .expect("valid edit should be reloaded");
+        assert!(config.workflows.contains_key("new"));
+
+        // Parse and validation errors are not forwarded
+        std::fs::write(&path, "{ not json").unwrap();
+        assert!(
+            next_config(&mut rx, Duration::from_millis(500))
+                .await
+                .is_none()
+        );
+        std::fs::write(&path, r#"{"events": {"e": {"workflow": "missing"}}}"#).unwrap();
+        assert!(
+            next_config(&mut rx, Duration::from_millis(500))
+                .await
+                .is_none()
+        );
+
+        // Other files in the directory are ignored
+        std::fs::write(dir.path().join("other.json"), "{}").unwrap();
+        assert!(
+            next_config(&mut rx, Duration::from_millis(500))
+                .await
+                .is_none()
+        );
+    }
+}
@@ -1,20 +1,108 @@
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use enigo::Keyboard as _;
 use enigo::Mouse as _;
-use enigo::{Axis, Button as EButton, Coordinate, Direction, Enigo, Settings};
+use enigo::{Axis, Button as EButton, Coordinate, Direction, Enigo, NewConError, Settings};
 use rand::random_range;
-use std::thread;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::time::timeout;
 use tracing::{debug, info, trace, warn};
 
 use crate::config::models::{LogLevel, MouseButton as CMouseButton, Rect};
-use crate::utils::window;
+use crate::config::{InputSettings, Key};
+use crate::utils::screen::{self, Rgb};
+use crate::utils::{clipboard, http, ocr, window};
+
+/// Connect/read/write timeout used by `tcp_send`.
+const TCP_SEND_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Overall timeout of a single HTTP request.
+const HTTP_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Display size reported by `screen_size` in dry-run.
+pub const DRY_RUN_SCREEN_SIZE: (i32, i32) = (1920, 1080);
+
+/// Constructor used to create the Enigo instance (swappable in tests to force failures).
+pub(crate) type EnigoInit = fn(&Settings) -> Result<Enigo, NewConError>;
+
+/// A low-level action recorded by a capturing executor (see `notabot::testing`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CapturedAction {
+    MouseMove {
+        x: i32,
+        y: i32,
+    },
+    MouseMoveWindowRel {
+        title_contains: String,
+        x: i32,
+        y: i32,
+    },
+    MouseClick {
+        button: CMouseButton,
+        count: u8,
+    },
+    MouseScroll {
+        delta_x: i32,
+        delta_y: i32,
+    },
+    MouseDrag {
+        from: (i32, i32),
+        to: (i32, i32),
+        button: CMouseButton,
+    },
+    CursorLocation,
+    ScreenSize {
+        monitor: usize,
+    },
+    KeySequence(String),
+    KeyDown(Key),
+    KeyUp(Key),
+    TypeText(String),
+    /// `sleep_ms` / `sleep_rand_ms` (the drawn value); post-delays are not recorded.
+    Sleep {
+        ms: u64,
+    },
+    FocusWindow(String),
+    ClipboardSet(String),
+    ClipboardGet,
+    RunCommand {
+        program: String,
+        args: Vec<String>,
+    },
+    TcpSend {
+        addr: String,
+        line: String,
+    },
+    HttpRequest {
+        method: String,
+        url: String,
+        body: Option<String>,
+    },
+    Log {
+        level: LogLevel,
+        message: String,
+    },
+}
+
+/// Shared list that a capturing executor appends to.
+pub(crate) type CaptureLog = Arc<Mutex<Vec<CapturedAction>>>;
 
 /// Executes low-level actions (mouse/keyboard/sleep/log) with optional dry-run mode.
 /// In dry-run mode, actions are only logged and no real input is simulated.
 pub struct ActionExecutor {
     dry_run: bool,
+    dry_run_time_scale: f32,
     enigo: Option<Enigo>,
+    enigo_init: EnigoInit,
+    enigo_settings: Settings,
+    capture: Option<CaptureLog>,
+    /// Keys pressed by `key_down` and not released yet, in press order.
+    held_keys: Vec<Key>,
 }
 
 impl ActionExecutor {
@@ -23,7 +111,12 @@ impl ActionExecutor {
     pub fn new(dry_run: bool) -> Self {
         Self {
             dry_run,
+            dry_run_time_scale: 0.0,
             enigo: None,
+            enigo_init: Enigo::new,
+            enigo_settings: Settings::default(),
+            capture: None,
+            held_keys: Vec::new(),
         }
     }
 
@@ -37,8 +130,82 @@ impl ActionExecutor {
         self.dry_run = dry_run;
     }
 
+    /// Verify that input simulation is available, initializing Enigo eagerly.
+    ///
+    /// Intended to be called once at startup so a missing display or missing
+    /// permissions surface as a clear, actionable error before any event is processed.
+    /// No-op in dry-run mode. Without this call Enigo is still initialized lazily.
+    pub fn check_input_available(&mut self) -> Result<()> {
+        if self.dry_run {
+            trace!(target: "notabot::actions", "Skipping input preflight in dry-run mode");
+            return Ok(());
+        }
+        self.ensure_enigo()?;
+        debug!(target: "notabot::actions", "Input simulation is available");
+        Ok(())
+    }
+
+    /// Apply input backend settings. The current Enigo connection (if any) is dropped
+    /// when they change, so the next input action reconnects with the new settings.
+    pub fn set_input_settings(&mut self, settings: &InputSettings) {
+        let settings = enigo_settings(settings);
+        if settings != self.enigo_settings {
+            self.enigo = None;
+            self.enigo_settings = settings;
+        }
+    }
+
+    /// Replace the Enigo constructor (tests use this to force init failures).
+    #[cfg(test)]
+    pub(crate) fn set_enigo_init(&mut self, init: EnigoInit) {
+        self.enigo = None;
+        self.enigo_init = init;
+    }
+
+    /// Record every action into `log` instead of simulating input (forces dry-run).
+    #[cfg(any(test, feature = "testing"))]
+    pub(crate) fn set_capture(&mut self, log: CaptureLog) {
+        self.dry_run = true;
+        self.capture = Some(log);
+    }
+
+    /// A second executor with the same mode and capture log, but without this one's
+    /// Enigo handle. Used by `parallel`, whose steps never simulate input.
+    pub(crate) fn fork(&self) -> Self {
+        Self {
+            dry_run: self.dry_run,
+            dry_run_time_scale: self.dry_run_time_scale,
+            enigo: None,
+            enigo_init: self.enigo_init,
+            enigo_settings: self.enigo_settings.clone(),
+            capture: self.capture.clone(),
+            held_keys: Vec::new(),
+        }
+    }
+
+    fn record(&self, action: impl FnOnce() -> CapturedAction) {
+        if let Some(log) = &self.capture {
+            log.lock().unwrap_or_else(|e| e.into_inner()).push(action());
+        }
+    }
+
+    /// Returns the dry-run sleep scale factor (0 means dry-run sleeps are instant).
+    pub fn dry_run_time_scale(&self) -> f32 {
+        self.dry_run_time_scale
+    }
+
+    /// Set the dry-run sleep scale factor. Negative or non-finite values are treated as 0.
+    pub fn set_dry_run_time_scale(&mut self, scale: f32) {
+        self.dry_run_time_scale = if scale.is_finite() && scale > 0.0 {
+            scale
+        } else {
+            0.0
+        };
+    }
+
     /// Move mouse cursor to absolute screen coordinates.
     pub fn mouse_move_to(&mut self, x: i32, y: i32) -> Result<()> {
+        self.record(|| CapturedAction::MouseMove { x, y });
         if self.dry_run {
             info!(target: "notabot::actions", x, y, "DRY-RUN mouse_move_to");
             return Ok(());
@@ -49,9 +216,30 @@ impl ActionExecutor {
         Ok(())
     }
 
+    /// Move the mouse to (x, y) relative to the top-left corner of the first window
+    /// whose title contains `title_contains`. Errors if no such window is found.
+    pub fn mouse_move_window_rel(&mut self, title_contains: &str, x: i32, y: i32) -> Result<()> {
+        if self.dry_run {
+            self.record(|| CapturedAction::MouseMoveWindowRel {
+                title_contains: title_contains.to_string(),
+                x,
+                y,
+            });
+            info!(target: "notabot::actions", %title_contains, x, y, "DRY-RUN mouse_move_window_rel");
+            return Ok(());
+        }
+        let bounds = window::window_bounds(title_contains)
+            .with_context(|| format!("window_bounds({title_contains}) failed"))?
+            .with_context(|| format!("No window found with title containing '{title_contains}'"))?;
+        let (abs_x, abs_y) = window::relative_to_window(bounds, x, y);
+        trace!(target: "notabot::actions", %title_contains, ?bounds, abs_x, abs_y, "mouse_move_window_rel");
+        self.mouse_move_to(abs_x, abs_y)
+    }
+
     /// Click a mouse button one or more times.
     pub fn mouse_click(&mut self, button: CMouseButton, count: Option<u8>) -> Result<()> {
         let count = count.unwrap_or(1).max(1);
+        self.record(|| CapturedAction::MouseClick { button, count });
         if self.dry_run {
             info!(target: "notabot::actions", ?button, count, "DRY-RUN mouse_click");
             return Ok(());
@@ -65,9 +253,65 @@ impl ActionExecutor {
         Ok(())
     }
 
+    /// Press `button` at `from`, move to `to` while holding it, then release.
+    pub fn mouse_drag(
+        &mut self,
+        from: (i32, i32),
+        to: (i32, i32),
+        button: CMouseButton,
+    ) -> Result<()> {
+        self.record(|| CapturedAction::MouseDrag { from, to, button });
+        if self.dry_run {
+            info!(target: "notabot::actions", ?from, ?to, ?button, "DRY-RUN mouse_drag");
+            return Ok(());
+        }
+        let enigo = self.ensure_enigo()?;
+        let btn = map_mouse_button(button);
+        trace!(target: "notabot::actions", ?from, ?to, ?button, "mouse_drag");
+        enigo.move_mouse(from.0, from.1, Coordinate::Abs)?;
+        enigo.button(btn, Direction::Press)?;
+        let moved = enigo.move_mouse(to.0, to.1, Coordinate::Abs);
+        // Release even if the move failed so the button is not left held down
+        enigo.button(btn, Direction::Release)?;
+        moved?;
+        Ok(())
+    }
+
+    /// Current cursor position in screen coordinates. Dry-run reports (0, 0).
+    pub fn cursor_location(&mut self) -> Result<(i32, i32)> {
+        self.record(|| CapturedAction::CursorLocation);
+        if self.dry_run {
+            info!(target: "notabot::actions", "DRY-RUN cursor_location");
+            return Ok((0, 0));
+        }
+        let enigo = self.ensure_enigo()?;
+        let (x, y) = enigo.location()?;
+        trace!(target: "notabot::actions", x, y, "cursor_location");
+        Ok((x, y))
+    }
+
+    /// Size in pixels of display `monitor`. Enigo only exposes the main display, so any
+    /// other index is an error. Dry-run reports `DRY_RUN_SCREEN_SIZE`.
+    pub fn screen_size(&mut self, monitor: usize) -> Result<(i32, i32)> {
+        self.record(|| CapturedAction::ScreenSize { monitor });
+        if monitor != 0 {
+            bail!("Monitor {monitor} is not supported; only the main display (0) can be queried");
+        }
+        if self.dry_run {
+            let (width, height) = DRY_RUN_SCREEN_SIZE;
+            info!(target: "notabot::actions", width, height, "DRY-RUN screen_size");
+            return Ok(DRY_RUN_SCREEN_SIZE);
+        }
+        let enigo = self.ensure_enigo()?;
+        let (width, height) = enigo.main_display()?;
+        trace!(target: "notabot::actions", width, height, "screen_size");
+        Ok((width, height))
+    }
+
     /// Scroll the mouse wheel. Currently a best-effort implementation:
     /// If unsupported by the underlying enigo version, this will log a warning.
     pub fn mouse_scroll(&mut self, delta_x: i32, delta_y: i32) -> Result<()> {
+        self.record(|| CapturedAction::MouseScroll { delta_x, delta_y });
         if self.dry_run {
             info!(target: "notabot::actions", delta_x, delta_y, "DRY-RUN mouse_scroll");
             return Ok(());
@@ -83,8 +327,28 @@ impl ActionExecutor {
         Ok(())
     }
 
+    /// Scroll by (`delta_x`, `delta_y`) in `steps` smaller scrolls, pausing `step_delay_ms`
+    /// between two steps. The step deltas add up to the requested totals.
+    pub async fn mouse_scroll_steps(
+        &mut self,
+        delta_x: i32,
+        delta_y: i32,
+        steps: u32,
+        step_delay_ms: u64,
+    ) -> Result<()> {
+        let mut parts = scroll_steps(delta_x, delta_y, steps).peekable();
+        while let Some((dx, dy)) = parts.next() {
+            self.mouse_scroll(dx, dy)?;
+            if parts.peek().is_some() && step_delay_ms > 0 {
+                self.sleep_ms(step_delay_ms).await?;
+            }
+        }
+        Ok(())
+    }
+
     /// Send a key sequence. Supports enigo's special key syntax like "{ENTER}".
     pub fn key_sequence(&mut self, text: &str) -> Result<()> {
+        self.record(|| CapturedAction::KeySequence(text.to_string()));
         if self.dry_run {
             info!(target: "notabot::actions", %text, "DRY-RUN key_sequence");
             return Ok(());
@@ -95,9 +359,53 @@ impl ActionExecutor {
         Ok(())
     }
 
+    /// Press and hold `key` (released by `key_up`).
+    pub fn key_down(&mut self, key: Key) -> Result<()> {
+        self.key_direction(key, Direction::Press)
+    }
+
+    /// Release `key`.
+    pub fn key_up(&mut self, key: Key) -> Result<()> {
+        self.key_direction(key, Direction::Release)
+    }
+
+    /// Release every key still held by `key_down`, most recent first.
+    /// Runs on cleanup paths with no caller to report to, so failures are only logged.
+    pub fn release_held_keys(&mut self) {
+        while let Some(key) = self.held_keys.pop() {
+            warn!(target: "notabot::actions", %key, "Releasing key left held");
+            if let Err(e) = self.key_direction(key, Direction::Release) {
+                warn!(target: "notabot::actions", error = %format!("{e:#}"), "Failed to release held key");
+            }
+        }
+    }
+
+    fn key_direction(&mut self, key: Key, direction: Direction) -> Result<()> {
+        self.record(|| match direction {
+            Direction::Release => CapturedAction::KeyUp(key),
+            _ => CapturedAction::KeyDown(key),
+        });
+        if self.dry_run {
+            info!(target: "notabot::actions", %key, ?direction, "DRY-RUN key");
+        } else {
+            let enigo = self.ensure_enigo()?;
+            trace!(target: "notabot::actions", %key, ?direction, "key");
+            enigo
+                .key(key.into(), direction)
+                .with_context(|| format!("Failed to {direction:?} key '{key}'"))?;
+        }
+        match direction {
+            Direction::Press if !self.held_keys.contains(&key) => self.held_keys.push(key),
+            Direction::Release => self.held_keys.retain(|&k| k != key),
+            _ => {}
+        }
+        Ok(())
+    }
+
     /// Type literal text (unicode).
     /// Implementation uses enigo's `key_sequence`, which handles plain text well.
     pub fn type_text(&mut self, text: &str) -> Result<()> {
+        self.record(|| CapturedAction::TypeText(text.to_string()));
         if self.dry_run {
             info!(target: "notabot::actions", %text, "DRY-RUN type_text");
             return Ok(());
@@ -108,34 +416,75 @@ impl ActionExecutor {
         Ok(())
     }
 
-    /// Sleep for a fixed duration in milliseconds (blocking).
-    /// Consider using an async sleep in higher-level async contexts.
-    pub fn sleep_ms(&self, ms: u64) -> Result<()> {
+    /// Type literal text one character at a time, waiting `per_char_ms` between two
+    /// characters. Recorded as a single `TypeText`; dry-run logs the character count and
+    /// the total delay.
+    pub async fn type_text_per_char(&mut self, text: &str, per_char_ms: u64) -> Result<()> {
+        self.record(|| CapturedAction::TypeText(text.to_string()));
+        let chars = text.chars().count();
+        let total_ms = per_char_ms.saturating_mul(chars.saturating_sub(1) as u64);
+        if self.dry_run {
+            info!(target: "notabot::actions", %text, chars, per_char_ms, total_ms, "DRY-RUN type_text");
+            self.dry_run_wait(total_ms).await;
+            return Ok(());
+        }
+        trace!(target: "notabot::actions", %text, chars, per_char_ms, "type_text per char");
+        let mut buf = [0u8; 4];
+        for (i, c) in text.chars().enumerate() {
+            if i > 0 && per_char_ms > 0 {
+                tokio::time::sleep(Duration::from_millis(per_char_ms)).await;
+            }
+            let _ = self.ensure_enigo()?.text(c.encode_utf8(&mut buf));
+        }
+        Ok(())
+    }
+
+    /// Sleep for a fixed duration in milliseconds without blocking the Tokio worker.
+    pub async fn sleep_ms(&self, ms: u64) -> Result<()> {
+        self.record(|| CapturedAction::Sleep { ms });
         if self.dry_run {
             info!(target: "notabot::actions", ms, "DRY-RUN sleep_ms");
+            self.dry_run_wait(ms).await;
             return Ok(());
         }
         trace!(target: "notabot::actions", ms, "sleep_ms");
-        thread::sleep(Duration::from_millis(ms));
+        tokio::time::sleep(Duration::from_millis(ms)).await;
+        Ok(())
+    }
+
+    /// Wait after an input action completes. In dry-run the delay is logged and only
+    /// elapses according to the dry-run time scale.
+    pub async fn post_delay(&self, ms: u64) -> Result<()> {
+        if self.dry_run {
+            debug!(target: "notabot::actions", ms, "DRY-RUN post_delay");
+            self.dry_run_wait(ms).await;
+            return Ok(());
+        }
+        trace!(target: "notabot::actions", ms, "post_delay");
+        tokio::time::sleep(Duration::from_millis(ms)).await;
         Ok(())
     }
 
-    /// Sleep for a random duration in milliseconds within [min, max] inclusive (blocking).
-    pub fn sleep_rand_ms(&self, min: u64, max: u64) -> Result<()> {
+    /// Sleep for a random duration in milliseconds within [min, max] inclusive.
+    /// Returns the duration that was drawn.
+    pub async fn sleep_rand_ms(&self, min: u64, max: u64) -> Result<u64> {
         let (lo, hi) = if min <= max { (min, max) } else { (max, min) };
         let delay = if lo == hi { lo } else { random_range(lo..=hi) };
+        self.record(|| CapturedAction::Sleep { ms: delay });
         if self.dry_run {
             info!(target: "notabot::actions", min = lo, max = hi, delay, "DRY-RUN sleep_rand_ms");
-            return Ok(());
+            self.dry_run_wait(delay).await;
+            return Ok(delay);
         }
         trace!(target: "notabot::actions", min = lo, max = hi, delay, "sleep_rand_ms");
-        thread::sleep(Duration::from_millis(delay));
-        Ok(())
+        tokio::time::sleep(Duration::from_millis(delay)).await;
+        Ok(delay)
     }
 
     /// Try to focus a window with title containing the substring.
     /// Returns Ok(true) if a window was focused.
     pub fn focus_window(&self, title_contains: &str) -> Result<bool> {
+        self.record(|| CapturedAction::FocusWindow(title_contains.to_string()));
         if self.dry_run {
             info!(target: "notabot::actions", %title_contains, "DRY-RUN focus_window");
             return Ok(false);
@@ -151,8 +500,205 @@ impl ActionExecutor {
         Ok(focused)
     }
 
+    /// Whether a window whose title contains `title_contains` exists.
+    /// Not dry-run aware: callers decide.
+    pub fn find_window(&self, title_contains: &str) -> Result<bool> {
+        trace!(target: "notabot::actions", %title_contains, "find_window");
+        window::find_window(title_contains)
+            .with_context(|| format!("find_window({title_contains}) failed"))
+    }
+
+    /// Replace the clipboard contents with `text`.
+    pub fn clipboard_set(&self, text: &str) -> Result<()> {
+        self.record(|| CapturedAction::ClipboardSet(text.to_string()));
+        if self.dry_run {
+            info!(target: "notabot::actions", len = text.len(), "DRY-RUN clipboard_set");
+            return Ok(());
+        }
+        trace!(target: "notabot::actions", len = text.len(), "clipboard_set");
+        clipboard::set_text(text)
+    }
+
+    /// Read the clipboard text. In dry-run nothing is read and `None` is returned.
+    pub fn clipboard_get(&self) -> Result<Option<String>> {
+        self.record(|| CapturedAction::ClipboardGet);
+        if self.dry_run {
+            info!(target: "notabot::actions", "DRY-RUN clipboard_get");
+            return Ok(None);
+        }
+        trace!(target: "notabot::actions", "clipboard_get");
+        clipboard::get_text().map(Some)
+    }
+
+    /// Run `program` with `args` and wait for it without blocking the Tokio worker.
+    /// Returns its standard output. A non-zero exit code is an error (including stderr)
+    /// unless `ignore_exit_code`. The program is killed when it outlives `timeout`, or
+    /// when the action is cancelled (e.g. by a workflow timeout).
+    /// In dry-run the command line is only logged and `None` is returned.
+    pub async fn run_command(
+        &self,
+        program: &str,
+        args: &[String],
+        ignore_exit_code: bool,
+        timeout: Duration,
+    ) -> Result<Option<String>> {
+        self.record(|| CapturedAction::RunCommand {
+            program: program.to_string(),
+            args: args.to_vec(),
+        });
+        if self.dry_run {
+            info!(target: "notabot::actions", %program, ?args, "DRY-RUN run_command");
+            return Ok(None);
+        }
+        trace!(target: "notabot::actions", %program, ?args, ?timeout, "run_command");
+        let child = tokio::process::Command::new(program)
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("Failed to start '{program}'"))?;
+        // Timing out drops the child, which kills it
+        let output = tokio::time::timeout(timeout, child.wait_with_output())
+            .await
+            .with_context(|| format!("'{program}' did not exit within {timeout:?}; killed it"))?
+            .with_context(|| format!("Failed to wait for '{program}'"))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if !ignore_exit_code {
+                bail!(
+                    "'{program}' exited with {}: {}",
+                    output.status,
+                    stderr.trim()
+                );
+            }
+            warn!(target: "notabot::actions", %program, status = %output.status, "run_command exited unsuccessfully (ignored)");
+        }
+        Ok(Some(String::from_utf8_lossy(&output.stdout).into_owned()))
+    }
+
+    /// Send a single line to a TCP peer over a short-lived connection.
+    /// When `expect_ack` is set, waits for a response line and errors unless it is `OK`.
+    /// Connecting, writing and reading the ACK each time out after `TCP_SEND_TIMEOUT`.
+    pub async fn tcp_send(&self, addr: &str, line: &str, expect_ack: bool) -> Result<()> {
+        self.record(|| CapturedAction::TcpSend {
+            addr: addr.to_string(),
+            line: line.to_string(),
+        });
+        if self.dry_run {
+            info!(target: "notabot::actions", %addr, %line, expect_ack, "DRY-RUN tcp_send");
+            return Ok(());
+        }
+        trace!(target: "notabot::actions", %addr, %line, expect_ack, "tcp_send");
+
+        let mut stream = timeout(TCP_SEND_TIMEOUT, TcpStream::connect(addr))
+            .await
+            .with_context(|| format!("tcp_send: timed out connecting to {addr}"))?
+            .with_context(|| format!("tcp_send: failed to connect to {addr}"))?;
+
+        timeout(
+            TCP_SEND_TIMEOUT,
+            stream.write_all(format!("{line}\n").as_bytes()),
+        )
+        .await
+        .with_context(|| format!("tcp_send: timed out writing to {addr}"))?
+        .with_context(|| format!("tcp_send: failed to write to {addr}"))?;
+
+        if expect_ack {
+            let mut response = String::new();
+            timeout(
+                TCP_SEND_TIMEOUT,
+                BufReader::new(&mut stream).read_line(&mut response),
+            )
+            .await
+            .with_context(|| format!("tcp_send: timed out waiting for ACK from {addr}"))?
+            .with_context(|| format!("tcp_send: failed to read ACK from {addr}"))?;
+            let response = response.trim();
+            if response != "OK" {
+                bail!("tcp_send: expected 'OK' from {addr}, got '{response}'");
+            }
+            debug!(target: "notabot::actions", %addr, "tcp_send: ACK received");
+        }
+        Ok(())
+    }
+
+    /// GET `url` and parse the response body as JSON.
+    /// Non-2xx statuses and invalid JSON are errors. Not dry-run aware: callers decide.
+    pub async fn http_get_json(&self, url: &str) -> Result<serde_json::Value> {
+        trace!(target: "notabot::actions", %url, "http_get_json");
+        let response = http::send("GET", url, &BTreeMap::new(), None, HTTP_REQUEST_TIMEOUT)
+            .await
+            .with_context(|| format!("GET {url} failed"))?;
+        if !response.is_success() {
+            bail!("GET {url} returned {}", response.status);
+        }
+        serde_json::from_str(&response.body).with_context(|| format!("GET {url}: body is not JSON"))
+    }
+
+    /// Send an HTTP request and return the response body (`None` in dry-run).
+    /// Non-2xx statuses are errors unless `allow_error_status` is set.
+    /// Needs the `http-client` feature outside dry-run.
+    pub async fn http_request(
+        &self,
+        method: &str,
+        url: &str,
+        headers: &BTreeMap<String, String>,
+        body: Option<&str>,
+        allow_error_status: bool,
+    ) -> Result<Option<String>> {
+        let method = http::parse_method(method)?;
+        self.record(|| CapturedAction::HttpRequest {
+            method: method.clone(),
+            url: url.to_string(),
+            body: body.map(str::to_string),
+        });
+        if self.dry_run {
+            info!(target: "notabot::actions", %method, %url, ?headers, ?body, "DRY-RUN http_request");
+            return Ok(None);
+        }
+        trace!(target: "notabot::actions", %method, %url, "http_request");
+
+        let response = http::send(&method, url, headers, body, HTTP_REQUEST_TIMEOUT)
+            .await
+            .with_context(|| format!("{method} {url} failed"))?;
+        let status = response.status;
+        if !response.is_success() {
+            if !allow_error_status {
+                bail!("{method} {url} returned {status}: {}", response.body.trim());
+            }
+            warn!(target: "notabot::actions", %method, %url, status, "http_request returned an error status (allowed)");
+        }
+        Ok(Some(response.body))
+    }
+
+    /// Write a JSON state snapshot to `path` (pretty-printed), creating parent directories.
+    pub async fn dump_state(&self, path: &str, state: &serde_json::Value) -> Result<()> {
+        if self.dry_run {
+            info!(target: "notabot::actions", %path, %state, "DRY-RUN dump_state");
+            return Ok(());
+        }
+        trace!(target: "notabot::actions", %path, "dump_state");
+        let path_ref = std::path::Path::new(path);
+        if let Some(parent) = path_ref.parent().filter(|p| !p.as_os_str().is_empty()) {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .with_context(|| format!("dump_state: failed to create {}", parent.display()))?;
+        }
+        let json = serde_json::to_string_pretty(state)?;
+        tokio::fs::write(path_ref, json)
+            .await
+            .with_context(|| format!("dump_state: failed to write {path}"))?;
+        debug!(target: "notabot::actions", %path, "State snapshot written");
+        Ok(())
+    }
+
     /// Log a message with a given level, useful within workflows.
     pub fn log_message(&self, level: LogLevel, message: &str) {
+        self.record(|| CapturedAction::Log {
+            level,
+            message: message.to_string(),
+        });
         match level {
             LogLevel::Trace => trace!(target: "notabot", "{message}"),
             LogLevel::Debug => debug!(target: "notabot", "{message}"),
@@ -162,44 +708,155 @@ impl ActionExecutor {
         }
     }
 
-    /// Placeholder for OCR check. Not implemented; logs intent and returns Ok(false).
-    pub fn ocr_check(&self, region: Option<Rect>, must_contain: &str) -> Result<bool> {
+    /// Check whether `must_contain` appears (case-insensitively) in the text recognized
+    /// from `image`, or from a capture of `region` (the whole main screen when `None`)
+    /// without an image. Fails without the `ocr` feature. Dry-run always reports a match.
+    pub async fn ocr_check(
+        &self,
+        region: Option<Rect>,
+        must_contain: &str,
+        image: Option<&str>,
+    ) -> Result<bool> {
         if self.dry_run {
-            info!(target: "notabot::actions", ?region, %must_contain, "DRY-RUN ocr_check");
+            info!(target: "notabot::actions", ?region, %must_contain, ?image, "DRY-RUN ocr_check");
             return Ok(true);
         }
-        warn!(
-            target: "notabot::actions",
-            ?region, %must_contain,
-            "ocr_check not implemented; returning false"
-        );
-        Ok(false)
+        if !cfg!(feature = "ocr") {
+            bail!("ocr_check requires the `ocr` feature, which is disabled in this build");
+        }
+        let text = match image {
+            Some(image) => ocr::recognize_text(Path::new(image))
+                .await
+                .with_context(|| format!("ocr_check failed to read text from {image}"))?,
+            None => {
+                let png = screen::capture(region)
+                    .and_then(|shot| shot.to_png())
+                    .context("ocr_check failed to capture the screen")?;
+                ocr::recognize_png(&png)
+                    .await
+                    .context("ocr_check failed to read text from the screen")?
+            }
+        };
+        let matched = ocr::text_contains(&text, must_contain);
+        debug!(target: "notabot::actions", ?image, ?region, %must_contain, matched, "ocr_check");
+        Ok(matched)
     }
 
-    /// Placeholder for screen capture. Not implemented; logs intent and returns Ok(()).
-    pub fn capture_screen(&self, path: &str, region: Option<Rect>) -> Result<()> {
+    /// Whether the screen pixel at (`x`, `y`) is within `tolerance` of `expected` on
+    /// every channel. Dry-run always reports a match.
+    pub fn pixel_color_check(&self, x: i32, y: i32, expected: Rgb, tolerance: u8) -> Result<bool> {
         if self.dry_run {
-            info!(target: "notabot::actions", %path, ?region, "DRY-RUN capture_screen");
+            info!(target: "notabot::actions", x, y, %expected, tolerance, "DRY-RUN pixel_color_check");
+            return Ok(true);
+        }
+        let actual = screen::pixel_color(x, y)?;
+        let matched = actual.within(expected, tolerance);
+        debug!(target: "notabot::actions", x, y, %expected, %actual, tolerance, matched, "pixel_color_check");
+        Ok(matched)
+    }
+
+    /// Capture `region` of the screen (the whole main screen when `None`) and save it as
+    /// a PNG at `path`, creating parent directories. With `to_clipboard` the image is also
+    /// copied to the clipboard, which needs the `clipboard-image` feature.
+    /// Same platform support as `pixel_color_check`.
+    pub async fn capture_screen(
+        &self,
+        path: &str,
+        region: Option<Rect>,
+        to_clipboard: bool,
+    ) -> Result<()> {
+        if self.dry_run {
+            info!(target: "notabot::actions", %path, ?region, to_clipboard, "DRY-RUN capture_screen");
             return Ok(());
         }
-        warn!(
-            target: "notabot::actions",
-            %path, ?region,
-            "capture_screen not implemented"
-        );
+        if to_clipboard && !cfg!(feature = "clipboard-image") {
+            bail!("capture_screen: `to_clipboard` requires the `clipboard-image` feature");
+        }
+        let shot = screen::capture(region)?;
+        trace!(target: "notabot::actions", %path, width = shot.width, height = shot.height, "capture_screen");
+        if to_clipboard {
+            clipboard::set_image(shot.width, shot.height, &shot.rgba)?;
+        }
+        let png = shot.to_png()?;
+        let path_ref = Path::new(path);
+        if let Some(parent) = path_ref.parent().filter(|p| !p.as_os_str().is_empty()) {
+            tokio::fs::create_dir_all(parent).await.with_context(|| {
+                format!("capture_screen: failed to create {}", parent.display())
+            })?;
+        }
+        tokio::fs::write(path_ref, png)
+            .await
+            .with_context(|| format!("capture_screen: failed to write {path}"))?;
+        debug!(target: "notabot::actions", %path, to_clipboard, "Screen captured");
         Ok(())
     }
 
+    /// In dry-run, wait `ms * dry_run_time_scale` so traces keep a realistic pace.
+    async fn dry_run_wait(&self, ms: u64) {
+        if self.dry_run_time_scale > 0.0 {
+            let scaled =
+                Duration::from_secs_f64(ms as f64 / 1000.0 * self.dry_run_time_scale as f64);
+            trace!(target: "notabot::actions", ms, scaled_ms = scaled.as_millis() as u64, "DRY-RUN scaled wait");
+            tokio::time::sleep(scaled).await;
+        }
+    }
+
     fn ensure_enigo(&mut self) -> Result<&mut Enigo> {
         if self.enigo.is_none() {
             trace!(target: "notabot::actions", "Initializing Enigo");
-            self.enigo =
-                Some(Enigo::new(&Settings::default()).context("Failed to initialize Enigo")?);
+            let enigo = (self.enigo_init)(&self.enigo_settings)
+                .map_err(|e| anyhow::anyhow!(describe_init_error(&e)))?;
+            self.enigo = Some(enigo);
         }
         Ok(self.enigo.as_mut().expect("Enigo must be initialized"))
     }
 }
 
+/// Enigo settings with the configured overrides applied on top of Enigo's defaults.
+fn enigo_settings(input: &InputSettings) -> Settings {
+    let mut settings = Settings::default();
+    if let Some(delay) = input.linux_delay {
+        settings.linux_delay = delay;
+    }
+    if let Some(display) = &input.x11_display {
+        settings.x11_display = Some(display.clone());
+    }
+    if let Some(display) = &input.wayland_display {
+        settings.wayland_display = Some(display.clone());
+    }
+    if let Some(release) = input.release_keys_when_dropped {
+        settings.release_keys_when_dropped = release;
+    }
+    if let Some(prompt) = input.open_prompt_to_get_permissions {
+        settings.open_prompt_to_get_permissions = prompt;
+    }
+    if let Some(independent) = input.independent_of_keyboard_state {
+        settings.independent_of_keyboard_state = independent;
+    }
+    if let Some(accel) = input.windows_subject_to_mouse_speed_and_acceleration_level {
+        settings.windows_subject_to_mouse_speed_and_acceleration_level = accel;
+    }
+    settings
+}
+
+/// Turn an Enigo connection error into an actionable message.
+fn describe_init_error(err: &NewConError) -> String {
+    let hint = match err {
+        NewConError::EstablishCon(_) => {
+            "no display/input connection is available; start a graphical session \
+             (e.g. check DISPLAY/WAYLAND_DISPLAY) or run with --dry-run"
+        }
+        NewConError::NoPermission => {
+            "the process lacks permission to simulate input; grant accessibility/input \
+             permissions or run with --dry-run"
+        }
+        NewConError::Reply | NewConError::NoEmptyKeycodes => {
+            "the display server rejected the input connection; retry or run with --dry-run"
+        }
+    };
+    format!("Failed to initialize input simulation (Enigo): {err}; {hint}")
+}
+
 fn map_mouse_button(btn: CMouseButton) -> EButton {
     match btn {
         CMouseButton::Left => EButton::Left,
@@ -207,3 +864,285 @@ fn map_mouse_button(btn: CMouseButton) -> EButton {
         CMouseButton::Right => EButton::Right,
     }
 }
+
+/// Split a scroll into `steps` parts (at least one) whose deltas sum to the totals.
+/// Remainders go to the first steps, e.g. 10 over 3 steps -> 4, 3, 3. Steps beyond the
+/// larger delta would only scroll by zero, so there are at most that many.
+fn scroll_steps(delta_x: i32, delta_y: i32, steps: u32) -> impl Iterator<Item = (i32, i32)> {
+    let most = delta_x.unsigned_abs().max(delta_y.unsigned_abs()).max(1);
+    let steps = steps.clamp(1, most);
+    split_delta(delta_x, steps).zip(split_delta(delta_y, steps))
+}
+
+fn split_delta(delta: i32, steps: u32) -> impl Iterator<Item = i32> {
+    let n = i64::from(steps);
+    let delta = i64::from(delta);
+    let (base, rem) = (delta / n, (delta % n).abs());
+    // |base| + 1 <= |delta|, so every part fits in an i32
+    (0..n).map(move |i| (base + if i < rem { delta.signum() } else { 0 }) as i32)
+}
+
+impl From<Key> for enigo::Key {
+    fn from(key: Key) -> Self {
+        match key {
+            Key::Alt => enigo::Key::Alt,
+            Key::Backspace => enigo::Key::Backspace,
+            Key::CapsLock => enigo::Key::CapsLock,
+            Key::Control => enigo::Key::Control,
+            Key::LControl => enigo::Key::LControl,
+            Key::RControl => enigo::Key::RControl,
+            Key::Shift => enigo::Key::Shift,
+            Key::LShift => enigo::Key::LShift,
+            Key::RShift => enigo::Key::RShift,
+            Key::Meta => enigo::Key::Meta,
+            Key::Delete => enigo::Key::Delete,
+            Key::Enter => enigo::Key::Return,
+            Key::Escape => enigo::Key::Escape,
+            Key::Space => enigo::Key::Space,
+            Key::Tab => enigo::Key::Tab,
+            Key::Up => enigo::Key::UpArrow,
+            Key::Down => enigo::Key::DownArrow,
+            Key::Left => enigo::Key::LeftArrow,
+            Key::Right => enigo::Key::RightArrow,
+            Key::Home => enigo::Key::Home,
+            Key::End => enigo::Key::End,
+            Key::PageUp => enigo::Key::PageUp,
+            Key::PageDown => enigo::Key::PageDown,
+            Key::F1 => enigo::Key::F1,
+            Key::F2 => enigo::Key::F2,
+            Key::F3 => enigo::Key::F3,
+            Key::F4 => enigo::Key::F4,
+            Key::F5 => enigo::Key::F5,
+            Key::F6 => enigo::Key::F6,
+            Key::F7 => enigo::Key::F7,
+            Key::F8 => enigo::Key::F8,
+            Key::F9 => enigo::Key::F9,
+            Key::F10 => enigo::Key::F10,
+            Key::F11 => enigo::Key::F11,
+            Key::F12 => enigo::Key::F12,
+            Key::VolumeUp => enigo::Key::VolumeUp,
+            Key::VolumeDown => enigo::Key::VolumeDown,
+            Key::VolumeMute => enigo::Key::VolumeMute,
+            Key::MediaPlayPause => enigo::Key::MediaPlayPause,
+            Key::MediaNextTrack => enigo::Key::MediaNextTrack,
+            Key::MediaPrevTrack => enigo::Key::MediaPrevTrack,
+            Key::Char(c) => enigo::Key::Unicode(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    #[tokio::test]
+    async fn dry_run_sleep_is_instant_by_default() {
+        let exec = ActionExecutor::new(true);
+        let start = Instant::now();
+        exec.sleep_ms(500).await.unwrap();
+        assert!(start.elapsed() < Duration::from_millis(100));
+    }
+
+    #[tokio::test]
+    async fn dry_run_sleep_honors_time_scale() {
+        let mut exec = ActionExecutor::new(true);
+        exec.set_dry_run_time_scale(0.5);
+        let start = Instant::now();
+        exec.sleep_ms(200).await.unwrap();
+        let elapsed = start.elapsed();
+        assert!(
+            elapsed >= Duration::from_millis(100),
+            "elapsed: {elapsed:?}"
+        );
+        assert!(elapsed < Duration::from_millis(180), "elapsed: {elapsed:?}");
+    }
+
+    #[tokio::test]
+    async fn capture_screen_to_clipboard_dry_run_only_logs() {
+        let exec = ActionExecutor::new(true);
+        exec.capture_screen("shot.png", None, true).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn ocr_check_without_image_captures_the_region() {
+        let region = Some(Rect {
+            x: 0,
+            y: 0,
+            width: 800,
+            height: 200,
+        });
+        let exec = ActionExecutor::new(false);
+        let err = format!(
+            "{:#}",
+            exec.ocr_check(region, "ERROR", None).await.unwrap_err()
+        );
+        // Without a screen to capture (or tesseract to read it), the capture path fails
+        if cfg!(feature = "ocr") {
+            assert!(
+                err.contains("failed to capture the screen")
+                    || err.contains("failed to read text from the screen"),
+                "{err}"
+            );
+        } else {
+            assert!(err.contains("`ocr` feature"), "{err}");
+        }
+        assert!(
+            ActionExecutor::new(true)
+                .ocr_check(region, "ERROR", None)
+                .await
+                .unwrap()
+        );
+    }
+
+    #[cfg(not(all(feature = "clipboard-image", any(windows, feature = "x11"))))]
+    #[tokio::test]
+    async fn capture_screen_to_clipboard_fails_without_support() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("shot.png");
+        let exec = ActionExecutor::new(false);
+        let err = exec
+            .capture_screen(path.to_str().unwrap(), None, true)
+            .await
+            .unwrap_err();
+        let err = format!("{err:#}");
+        assert!(
+            err.contains("clipboard-image") || err.contains("not supported"),
+            "{err}"
+        );
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn capture_screen_to_clipboard_flag_parsing() {
+        use crate::config::ActionDef;
+
+        let with: ActionDef = serde_json::from_str(
+            r#"{ "type": "capture_screen", "path": "a.png", "to_clipboard": true }"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            with,
+            ActionDef::CaptureScreen {
+                to_clipboard: true,
+                ..
+            }
+        ));
+
+        let without: ActionDef =
+            serde_json::from_str(r#"{ "type": "capture_screen", "path": "a.png" }"#).unwrap();
+        assert!(matches!(
+            without,
+            ActionDef::CaptureScreen {
+                to_clipboard: false,
+                ..
+            }
+        ));
+    }
+
+    fn failing_init(_: &Settings) -> Result<Enigo, NewConError> {
+        Err(NewConError::EstablishCon("forced failure"))
+    }
+
+    #[test]
+    fn check_input_available_reports_friendly_error() {
+        let mut exec = ActionExecutor::new(false);
+        exec.set_enigo_init(failing_init);
+        let err = exec.check_input_available().unwrap_err().to_string();
+        assert!(err.contains("forced failure"), "{err}");
+        assert!(err.contains("--dry-run"), "{err}");
+    }
+
+    fn settings_reporting_init(settings: &Settings) -> Result<Enigo, NewConError> {
+        Err(NewConError::EstablishCon(
+            if settings.linux_delay == 40 && !settings.release_keys_when_dropped {
+                "configured settings"
+            } else {
+                "default settings"
+            },
+        ))
+    }
+
+    #[test]
+    fn input_settings_override_enigo_defaults() {
+        let mut exec = ActionExecutor::new(false);
+        exec.set_enigo_init(settings_reporting_init);
+        let err = exec.check_input_available().unwrap_err().to_string();
+        assert!(err.contains("default settings"), "{err}");
+
+        exec.set_input_settings(&InputSettings {
+            linux_delay: Some(40),
+            release_keys_when_dropped: Some(false),
+            ..Default::default()
+        });
+        let err = exec.check_input_available().unwrap_err().to_string();
+        assert!(err.contains("configured settings"), "{err}");
+
+        // Unset fields keep Enigo's defaults
+        let settings = enigo_settings(&InputSettings::default());
+        assert_eq!(settings, Settings::default());
+    }
+
+    #[test]
+    fn check_input_available_is_noop_in_dry_run() {
+        let mut exec = ActionExecutor::new(true);
+        exec.set_enigo_init(failing_init);
+        exec.check_input_available().unwrap();
+    }
+
+    #[test]
+    fn invalid_time_scale_is_clamped_to_zero() {
+        let mut exec = ActionExecutor::new(true);
+        exec.set_dry_run_time_scale(-2.0);
+        assert_eq!(exec.dry_run_time_scale(), 0.0);
+        exec.set_dry_run_time_scale(f32::NAN);
+        assert_eq!(exec.dry_run_time_scale(), 0.0);
+    }
+
+    #[test]
+    fn scroll_steps_split_totals_with_remainders() {
+        let parts: Vec<_> = scroll_steps(10, -7, 3).collect();
+        assert_eq!(parts, vec![(4, -3), (3, -2), (3, -2)]);
+        assert_eq!(parts.iter().map(|p| p.0).sum::<i32>(), 10);
+        assert_eq!(parts.iter().map(|p| p.1).sum::<i32>(), -7);
+
+        // Default (and 0) is a single scroll with the full delta
+        assert!(scroll_steps(5, 120, 1).eq([(5, 120)]));
+        assert!(scroll_steps(5, 120, 0).eq([(5, 120)]));
+
+        // No more steps than wheel units, however many are asked for
+        assert!(scroll_steps(2, -1, u32::MAX).eq([(1, -1), (1, 0)]));
+        assert!(scroll_steps(0, 0, u32::MAX).eq([(0, 0)]));
+
+        let parts: Vec<_> = scroll_steps(0, i32::MIN, 7).collect();
+        assert_eq!(parts.len(), 7);
+        assert_eq!(
+            parts.iter().map(|p| i64::from(p.1)).sum::<i64>(),
+            i64::from(i32::MIN)
+        );
+    }
+
+    #[tokio::test]
+    async fn dry_run_scroll_steps_pause_between_steps() {
+        let mut exec = ActionExecutor::new(true);
+        exec.set_dry_run_time_scale(1.0);
+        let start = Instant::now();
+        exec.mouse_scroll_steps(0, 300, 4, 30).await.unwrap();
+        // Three pauses between four steps, none after the last one
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(90), "{elapsed:?}");
+        assert!(elapsed < Duration::from_millis(400), "{elapsed:?}");
+    }
+
+    #[tokio::test]
+    async fn dry_run_per_char_typing_waits_between_characters() {
+        let mut exec = ActionExecutor::new(true);
+        exec.set_dry_run_time_scale(1.0);
+        let start = Instant::now();
+        exec.type_text_per_char("héllo", 25).await.unwrap();
+        // Four pauses between five characters (counted as chars, not bytes)
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(100), "{elapsed:?}");
+        assert!(elapsed < Duration::from_millis(400), "{elapsed:?}");
+    }
+}
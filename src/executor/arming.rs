@@ -0,0 +1,130 @@
+//! Safe mode: input actions stay disabled until the user arms them.
+//!
+//! With `Config::require_arm`, the runtime starts disarmed: mouse/keyboard actions are
+//! skipped (and logged) until `arm_key` is pressed in the Notabot console. Non-input
+//! actions (logging, variables, networking, ...) run normally in the meantime.
+
+use anyhow::{Context, Result, bail};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::terminal;
+use std::io::IsTerminal;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing::{info, warn};
+
+use crate::config::Key;
+
+/// Key used to arm input when `Config::arm_key` is not set.
+pub const DEFAULT_ARM_KEY: Key = Key::F12;
+
+/// Shared armed/disarmed flag; clones observe and flip the same state.
+#[derive(Debug, Clone)]
+pub struct ArmSwitch {
+    armed: Arc<AtomicBool>,
+}
+
+impl ArmSwitch {
+    /// Create a switch in the given state.
+    pub fn new(armed: bool) -> Self {
+        Self {
+            armed: Arc::new(AtomicBool::new(armed)),
+        }
+    }
+
+    /// Whether input actions currently reach the input backend.
+    pub fn is_armed(&self) -> bool {
+        self.armed.load(Ordering::SeqCst)
+    }
+
+    /// Enable real input. Logs the transition (no-op if already armed).
+    pub fn arm(&self) {
+        if !self.armed.swap(true, Ordering::SeqCst) {
+            warn!(target: "notabot::runtime", "Input ARMED: input actions will now be performed");
+        }
+    }
+
+    /// Disable real input. Logs the transition (no-op if already disarmed).
+    pub fn disarm(&self) {
+        if self.armed.swap(false, Ordering::SeqCst) {
+            info!(target: "notabot::runtime", "Input disarmed: input actions will be skipped");
+        }
+    }
+}
+
+/// How `wait_for_arm_key` returned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArmWait {
+    /// The arm key was pressed and the switch is now armed.
+    Armed,
+    /// Ctrl+C was pressed while waiting; the switch is left disarmed.
+    Interrupted,
+}
+
+/// Fail unless stdin is an interactive terminal, so a `require_arm` setup that could
+/// never be armed is reported at startup instead of staying disarmed forever.
+pub fn ensure_console() -> Result<()> {
+    if !std::io::stdin().is_terminal() {
+        bail!(
+            "require_arm needs an interactive terminal to read arm_key from, but stdin is not a TTY"
+        );
+    }
+    Ok(())
+}
+
+/// Block until `key` is pressed in the controlling terminal, then arm `switch`.
+///
+/// The terminal is put in raw mode while waiting (so Ctrl+C arrives as a key press and
+/// is reported as `ArmWait::Interrupted`). Fails if there is no interactive terminal.
+pub fn wait_for_arm_key(switch: &ArmSwitch, key: Key) -> Result<ArmWait> {
+    let code = key
+        .console_code()
+        .with_context(|| format!("arm_key '{key}' cannot be read from the console"))?;
+    terminal::enable_raw_mode().context("require_arm needs an interactive terminal")?;
+    let outcome = wait_for_key_code(code);
+    let _ = terminal::disable_raw_mode();
+
+    let outcome = outcome?;
+    if outcome == ArmWait::Armed {
+        switch.arm();
+    }
+    Ok(outcome)
+}
+
+fn wait_for_key_code(code: KeyCode) -> Result<ArmWait> {
+    loop {
+        let Event::Key(pressed) = event::read().context("Failed to read console input")? else {
+            continue;
+        };
+        if pressed.kind != KeyEventKind::Press {
+            continue;
+        }
+        if is_ctrl_c(&pressed) {
+            return Ok(ArmWait::Interrupted);
+        }
+        if pressed.code == code {
+            return Ok(ArmWait::Armed);
+        }
+    }
+}
+
+fn is_ctrl_c(event: &KeyEvent) -> bool {
+    event.modifiers.contains(KeyModifiers::CONTROL)
+        && matches!(event.code, KeyCode::Char('c') | KeyCode::Char('C'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn switch_clones_share_state() {
+        let switch = ArmSwitch::new(false);
+        let other = switch.clone();
+        assert!(!other.is_armed());
+
+        switch.arm();
+        assert!(other.is_armed());
+        other.disarm();
+        assert!(!switch.is_armed());
+    }
+}
@@ -6,10 +6,13 @@ Executor module for Notabot.
 This module wires together:
 - `actions`: low-level input simulation and helpers (mouse, keyboard, sleep, logging, window ops)
 - `runtime`: high-level workflow execution with interpolation and variable mapping
+- `watchdog`: idle timer that triggers a recovery workflow when events stop arriving
+- `arming`: safe mode switch that keeps input disabled until the user arms it
 
 Typical usage:
 - Construct a `Runtime` with a loaded `Config`.
-- Call `Runtime::run_event` with an incoming JSON event, or `run_workflow_by_name` directly.
+- Await `Runtime::run_event` with an incoming JSON event, or `run_workflow_by_name` directly.
+  Both are async: sleeps and post-delays yield to Tokio instead of blocking the worker thread.
 
 Example:
 ```no_run
@@ -21,17 +24,29 @@ use serde_json::json;
 let cfg: Config = Default::default();
 let mut rt = Runtime::new(cfg, true); // dry-run mode
 let event = json!({"type": "send_text", "text": "Hello"});
-// rt.run_event(&event)?;
+// rt.run_event(&event).await?;
 ```
 
 Public re-exports:
 - `ActionExecutor`: performs low-level actions (respecting dry-run).
 - `Runtime`: orchestrates workflows and executes actions.
+- `EventOutcome`: structured result of `Runtime::run_event_detailed`.
+- `ActionRecord` / `ActionResult`: what each action of the last workflow run did.
+- `TraceEntry`: one executed action recorded by `Runtime::set_trace`, serializable to JSON.
+- `ScheduledRun`: a delayed workflow run produced by the `schedule_workflow` action.
+- `IdleWatchdog`: reset-on-event timer for `Config::idle_watchdog`.
+- `ArmSwitch`: armed/disarmed state for `Config::require_arm`.
 */
 
 pub mod actions;
+pub mod arming;
 pub mod runtime;
+pub mod watchdog;
 
 // Re-exports for convenient access from `notabot::executor::*`
 pub use actions::ActionExecutor;
-pub use runtime::Runtime;
+pub use arming::ArmSwitch;
+pub use runtime::{
+    ActionRecord, ActionResult, Branch, EventOutcome, Runtime, ScheduledRun, TraceEntry,
+};
+pub use watchdog::IdleWatchdog;
@@ -1,15 +1,241 @@
 use anyhow::{Context, Result, bail};
+use futures_util::future::try_join_all;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
-use tracing::{debug, info, trace, warn};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
+use tracing::{Instrument, Level, Span, debug, info, info_span, trace, warn};
 
-use crate::config::{ActionDef, Config, EventBinding};
+use crate::config::loader::schema_violations;
+use crate::config::{ActionDef, CompareOp, Config, Coord, EventBinding, MathOp};
 use crate::executor::actions::ActionExecutor;
-use crate::utils::interpolation;
+use crate::executor::arming::ArmSwitch;
+use crate::utils::interpolation::{Template, get_json_path, json_value_to_string};
+use crate::utils::pattern::{literal_prefix_len, simple_pattern_match};
+use crate::utils::screen::Rgb;
 
 /// Maximum nesting depth for action execution (to protect against cycles).
 const MAX_DEPTH: usize = 64;
 
+/// Event field that selects a workflow directly (see `Config::allow_inline_workflow`).
+const INLINE_WORKFLOW_FIELD: &str = "_workflow";
+
+/// Event field counting how many `emit_event`s led to an event (absent means 0).
+pub const EVENT_HOPS_FIELD: &str = "_hops";
+
+/// Default for `Config::max_event_hops`.
+const DEFAULT_MAX_EVENT_HOPS: u32 = 8;
+
+/// Default cap on pending `schedule_workflow` timers.
+const DEFAULT_MAX_PENDING_SCHEDULES: usize = 64;
+
+/// Default `http_poll_until` interval and timeout.
+const DEFAULT_HTTP_POLL_INTERVAL_MS: u64 = 1_000;
+const DEFAULT_HTTP_POLL_TIMEOUT_MS: u64 = 30_000;
+const DEFAULT_WINDOW_POLL_MS: u64 = 250;
+
+/// Default `run_command` timeout.
+const DEFAULT_RUN_COMMAND_TIMEOUT_MS: u64 = 60_000;
+
+/// Default envelope key for batched events (see `Config::batch_key`).
+const DEFAULT_BATCH_KEY: &str = "batch";
+
+/// Variable holding the current iteration of a `repeat` body.
+const REPEAT_INDEX_VAR: &str = "repeat_index";
+
+/// Default bound on `while` iterations.
+const DEFAULT_WHILE_MAX_ITERATIONS: u32 = 1_000;
+
+/// Variable holding the current (one-based) attempt of a `retry` body.
+const ATTEMPT_VAR: &str = "attempt";
+
+/// Variable holding the error message caught by `try`.
+const ERROR_VAR: &str = "error";
+
+/// Variable set by `ocr_check` to "true" or "false".
+const OCR_MATCHED_VAR: &str = "ocr_matched";
+
+/// Upper bound on cached parsed templates; the cache is cleared when it fills up.
+const MAX_CACHED_TEMPLATES: usize = 1024;
+
+/// Upper bound on cached compiled `regex_extract` patterns (cleared when full).
+const MAX_CACHED_REGEXES: usize = 64;
+
+/// Records kept by `Runtime::last_action_results`: long loops drop the oldest ones.
+const MAX_ACTION_RECORDS: usize = 10_000;
+
+/// Capture group stored by `regex_extract` by default.
+pub(crate) const DEFAULT_REGEX_GROUP: usize = 1;
+
+/// Structured result of a successfully handled event, for embedders.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventOutcome {
+    /// Name of the workflow that ran.
+    pub workflow: String,
+    /// Number of top-level workflow steps executed.
+    pub steps_executed: usize,
+    /// Workflow variables as they were when the workflow finished.
+    pub vars_final: HashMap<String, String>,
+    /// True when the event was dropped by `rate_limits` (nothing was executed).
+    pub rate_limited: bool,
+    /// True when the binding was skipped by the tag filter (nothing was executed).
+    pub tag_filtered: bool,
+    /// True when an `abort` action stopped the workflow early.
+    pub aborted: bool,
+}
+
+impl EventOutcome {
+    /// Outcome of an event whose `workflow` was not run.
+    fn skipped(workflow: String, rate_limited: bool, tag_filtered: bool) -> Self {
+        Self {
+            workflow,
+            steps_executed: 0,
+            vars_final: HashMap::new(),
+            rate_limited,
+            tag_filtered,
+            aborted: false,
+        }
+    }
+}
+
+/// What a single executed action did (see `Runtime::last_action_results`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ActionResult {
+    /// The action ran with nothing further to report (input, logging, networking, ...).
+    Done,
+    /// A container (`sequence`, `ref`); its steps are recorded as separate entries.
+    Composite,
+    /// The action was not executed (e.g., input while disarmed, debounced `ref`).
+    Skipped { reason: String },
+    /// The action failed with this error (the workflow stops here).
+    Failed { error: String },
+    /// The action never finished: it was cancelled while running (the workflow hit its
+    /// `timeout_ms`, or a sibling `parallel` step failed).
+    Cancelled,
+    /// A variable was set (`set_var`, `capture_screen { save_path_to }`).
+    VarSet { name: String, value: String },
+    /// `clear_vars` removed this many variables.
+    VarsCleared { removed: usize },
+    /// A loop ran its body this many times.
+    Looped { iterations: u32 },
+    /// `abort` stopped the workflow (with its interpolated message, if any).
+    Aborted { message: Option<String> },
+    /// A `retry` body succeeded on this (one-based) attempt.
+    Retried { attempts: u32 },
+    /// A `try` body failed with this error and `catch` ran instead.
+    Caught { error: String },
+    /// A `conditional` (or `pixel_color_check`, `ocr_check` with branches) took this branch.
+    Branch(Branch),
+    /// `focus_window` ran; `focused` is false when no matching window was found.
+    WindowFocused { focused: bool },
+    /// `get_cursor` read the cursor at this position.
+    CursorLocated { x: i32, y: i32 },
+    /// `get_screen_size` read this display size.
+    ScreenSize { width: i32, height: i32 },
+    /// A sleep of this many milliseconds (the drawn value for `sleep_rand_ms`).
+    Slept { ms: u64 },
+    /// A workflow run was scheduled.
+    Scheduled { workflow: String, delay_ms: u64 },
+    /// An event was queued by `emit_event`, `hops` events away from the original.
+    EventEmitted {
+        event_type: Option<String>,
+        hops: u64,
+    },
+}
+
+/// Branch taken by a `conditional`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Branch {
+    /// The condition matched and `then` ran.
+    Then,
+    /// The condition did not match and `else` ran.
+    Else,
+    /// The condition was false and there is no `else`.
+    Neither,
+}
+
+/// One executed action in the last workflow run, in execution order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActionRecord {
+    /// The action's `type` tag (e.g., `"conditional"`).
+    pub action: &'static str,
+    /// Nesting depth (0 for top-level workflow steps).
+    pub depth: usize,
+    pub result: ActionResult,
+}
+
+/// One executed action, recorded while tracing is enabled (see `Runtime::set_trace`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TraceEntry {
+    /// The action's `type` tag (e.g., `"mouse_click"`).
+    pub action: String,
+    /// The remaining fields of the action as configured (templates not interpolated).
+    pub params: Value,
+    /// When the action started (RFC 3339, UTC).
+    pub timestamp: String,
+}
+
+impl TraceEntry {
+    fn new(action: &ActionDef) -> Self {
+        let mut params = serde_json::to_value(action).unwrap_or(Value::Null);
+        if let Value::Object(fields) = &mut params {
+            fields.remove("type");
+        }
+        Self {
+            action: action.type_name().to_string(),
+            params,
+            timestamp: humantime::format_rfc3339_millis(std::time::SystemTime::now()).to_string(),
+        }
+    }
+}
+
+/// Token bucket backing `Config::rate_limits` for a single event type.
+#[derive(Debug, Clone)]
+struct TokenBucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(per_second: u32) -> Self {
+        Self {
+            tokens: f64::from(per_second),
+            last_refill: Instant::now(),
+        }
+    }
+
+    /// Refill according to elapsed time, then try to take one token.
+    fn try_acquire(&mut self, per_second: u32) -> bool {
+        let now = Instant::now();
+        let capacity = f64::from(per_second);
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * capacity).min(capacity);
+        self.last_refill = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// A workflow run requested by a `schedule_workflow` action whose delay has elapsed.
+///
+/// Obtain the stream of these with `Runtime::take_schedule_receiver` and feed each
+/// back into `Runtime::run_workflow_by_name`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScheduledRun {
+    /// Workflow to run.
+    pub workflow: String,
+    /// Interpolated variables for the run.
+    pub vars: HashMap<String, String>,
+}
+
 /// Runtime is responsible for:
 /// - mapping incoming event data to workflow variables
 /// - interpolating strings using variables and globals
@@ -17,17 +243,221 @@ const MAX_DEPTH: usize = 64;
 pub struct Runtime {
     config: Config,
     executor: ActionExecutor,
+    schedule_tx: UnboundedSender<ScheduledRun>,
+    schedule_rx: Option<UnboundedReceiver<ScheduledRun>>,
+    /// Events produced by `emit_event`, handled by the event loop like source events.
+    emit_tx: UnboundedSender<Value>,
+    emit_rx: Option<UnboundedReceiver<Value>>,
+    config_tx: UnboundedSender<Config>,
+    config_rx: Option<UnboundedReceiver<Config>>,
+    pending_schedules: Arc<AtomicUsize>,
+    /// Token buckets of `Config::rate_limits`, shared with `parallel` forks.
+    rate_buckets: Arc<Mutex<HashMap<String, TokenBucket>>>,
+    only_tags: Option<Vec<String>>,
+    /// Last run of each debounced named action (see `ActionMeta::debounce_ms`).
+    last_fired: HashMap<String, Instant>,
+    /// Parsed templates keyed by their source text (see `interp`).
+    templates: Mutex<HashMap<String, Arc<Template>>>,
+    /// Compiled `regex_extract` patterns keyed by their (interpolated) source.
+    regexes: Mutex<HashMap<String, Regex>>,
+    /// Compiled event binding schemas keyed by their source text.
+    event_schemas: HashMap<String, jsonschema::Validator>,
+    /// Input actions are skipped while disarmed (see `Config::require_arm`).
+    arm: ArmSwitch,
+    /// Per-action results of the last workflow run (see `last_action_results`). Holds
+    /// up to twice `MAX_ACTION_RECORDS` so the oldest can be dropped in batches.
+    action_results: Vec<ActionRecord>,
+    /// How many of the run's oldest records were dropped from `action_results`.
+    action_results_dropped: usize,
+    /// Set on the forks running `parallel` steps, which must not simulate input.
+    in_parallel: bool,
+    /// Set by `abort`: containers stop running steps until the workflow ends.
+    aborting: bool,
+    /// Executed actions across runs while tracing is enabled (see `set_trace`).
+    trace: Option<Vec<TraceEntry>>,
 }
 
 impl Runtime {
     /// Create a new runtime with the given config and dry-run mode.
     pub fn new(config: Config, dry_run: bool) -> Self {
+        let mut executor = ActionExecutor::new(dry_run);
+        executor.set_dry_run_time_scale(config.dry_run_time_scale);
+        executor.set_input_settings(&config.input_settings);
+        let (schedule_tx, schedule_rx) = unbounded_channel();
+        let (emit_tx, emit_rx) = unbounded_channel();
+        let (config_tx, config_rx) = unbounded_channel();
+        let arm = ArmSwitch::new(!config.require_arm);
         Self {
             config,
-            executor: ActionExecutor::new(dry_run),
+            executor,
+            schedule_tx,
+            schedule_rx: Some(schedule_rx),
+            emit_tx,
+            emit_rx: Some(emit_rx),
+            config_tx,
+            config_rx: Some(config_rx),
+            pending_schedules: Arc::new(AtomicUsize::new(0)),
+            rate_buckets: Arc::new(Mutex::new(HashMap::new())),
+            only_tags: None,
+            last_fired: HashMap::new(),
+            templates: Mutex::new(HashMap::new()),
+            regexes: Mutex::new(HashMap::new()),
+            event_schemas: HashMap::new(),
+            arm,
+            action_results: Vec::new(),
+            action_results_dropped: 0,
+            in_parallel: false,
+            aborting: false,
+            trace: None,
+        }
+    }
+
+    /// A runtime for one `parallel` step: it shares the scheduler, arm switch, rate-limit
+    /// buckets and capture log, but has its own executor (no input device) and result records.
+    fn fork(&self) -> Self {
+        Self {
+            config: self.config.clone(),
+            executor: self.executor.fork(),
+            schedule_tx: self.schedule_tx.clone(),
+            schedule_rx: None,
+            emit_tx: self.emit_tx.clone(),
+            emit_rx: None,
+            config_tx: self.config_tx.clone(),
+            config_rx: None,
+            pending_schedules: Arc::clone(&self.pending_schedules),
+            rate_buckets: Arc::clone(&self.rate_buckets),
+            only_tags: self.only_tags.clone(),
+            last_fired: self.last_fired.clone(),
+            templates: Mutex::new(HashMap::new()),
+            regexes: Mutex::new(HashMap::new()),
+            event_schemas: HashMap::new(),
+            arm: self.arm.clone(),
+            action_results: Vec::new(),
+            action_results_dropped: 0,
+            in_parallel: true,
+            aborting: false,
+            trace: self.trace.as_ref().map(|_| Vec::new()),
+        }
+    }
+
+    /// Verify up front that input simulation works (no-op in dry-run mode).
+    /// Call before processing events to fail fast with an actionable message
+    /// (e.g. missing display or permissions) instead of mid-workflow.
+    pub fn check_input_ready(&mut self) -> Result<()> {
+        self.executor.check_input_available()
+    }
+
+    /// Release the keys still held by `key_down`. Workflows do this when they end; call
+    /// it before leaving the process without dropping the runtime (e.g. `process::exit`).
+    pub fn release_held_keys(&mut self) {
+        self.executor.release_held_keys();
+    }
+
+    /// Handle to the armed/disarmed state; input actions are skipped while disarmed.
+    /// Starts disarmed when `Config::require_arm` is set.
+    pub fn arm_switch(&self) -> ArmSwitch {
+        self.arm.clone()
+    }
+
+    /// What each action of the most recent workflow run did, in execution order
+    /// (containers before their steps). Replaced at the start of every workflow run.
+    /// Only the last `MAX_ACTION_RECORDS` are kept.
+    pub fn last_action_results(&self) -> &[ActionRecord] {
+        let len = self.action_results.len();
+        &self.action_results[len.saturating_sub(MAX_ACTION_RECORDS)..]
+    }
+
+    /// Drop the oldest records once twice `MAX_ACTION_RECORDS` have piled up.
+    fn trim_action_results(&mut self) {
+        let len = self.action_results.len();
+        if len >= 2 * MAX_ACTION_RECORDS {
+            let excess = len - MAX_ACTION_RECORDS;
+            self.action_results.drain(..excess);
+            self.action_results_dropped += excess;
         }
     }
 
+    /// Set the result of the record reserved as `slot` (counted from the start of the
+    /// run), unless it was dropped meanwhile.
+    fn set_action_result(&mut self, slot: usize, result: ActionResult) {
+        if let Some(record) = slot
+            .checked_sub(self.action_results_dropped)
+            .and_then(|i| self.action_results.get_mut(i))
+        {
+            record.result = result;
+        }
+    }
+
+    /// Start (or stop) recording every executed action as a `TraceEntry`.
+    /// Disabling drops the entries recorded so far.
+    pub fn set_trace(&mut self, enabled: bool) {
+        self.trace = enabled.then(|| self.trace.take().unwrap_or_default());
+    }
+
+    /// Take the actions recorded since tracing was enabled (or since the last call),
+    /// across workflow runs, in execution order (the steps of a `parallel` follow it in
+    /// step order). Empty when tracing is disabled.
+    pub fn take_trace(&mut self) -> Vec<TraceEntry> {
+        self.trace.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    /// Record low-level actions into `log` instead of simulating input (see `testing`).
+    #[cfg(any(test, feature = "testing"))]
+    pub(crate) fn set_capture(&mut self, log: crate::executor::actions::CaptureLog) {
+        self.executor.set_capture(log);
+    }
+
+    /// Take the receiving end for workflows scheduled via `schedule_workflow`.
+    /// Returns `None` if it was already taken.
+    pub fn take_schedule_receiver(&mut self) -> Option<UnboundedReceiver<ScheduledRun>> {
+        self.schedule_rx.take()
+    }
+
+    /// Take the receiving end for events emitted via `emit_event`.
+    /// Returns `None` if it was already taken.
+    pub fn take_emitted_event_receiver(&mut self) -> Option<UnboundedReceiver<Value>> {
+        self.emit_rx.take()
+    }
+
+    /// Sender for replacement configs (e.g. from `config::watch::ConfigWatcher`).
+    /// The event loop applies them with `replace_config` between workflows.
+    pub fn config_update_sender(&self) -> UnboundedSender<Config> {
+        self.config_tx.clone()
+    }
+
+    /// Take the receiving end for `config_update_sender`.
+    /// Returns `None` if it was already taken.
+    pub fn take_config_update_receiver(&mut self) -> Option<UnboundedReceiver<Config>> {
+        self.config_rx.take()
+    }
+
+    /// Swap in a new (already validated) config. Takes `&mut self`, so it can only
+    /// happen between workflows: a running workflow always finishes with the config it
+    /// started with. Rate-limit buckets restart and changed `input_settings` reconnect
+    /// the input device; dry-run, the tag filter, the arm state and pending schedules
+    /// are kept. Event sources are not rebuilt.
+    pub fn replace_config(&mut self, config: Config) {
+        info!(
+            target: "notabot::runtime",
+            workflows = config.workflows.len(),
+            events = config.events.len(),
+            "Applying reloaded config"
+        );
+        self.executor
+            .set_dry_run_time_scale(config.dry_run_time_scale);
+        self.executor.set_input_settings(&config.input_settings);
+        self.rate_buckets
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+        self.config = config;
+    }
+
+    /// Number of `schedule_workflow` timers that have not fired yet.
+    pub fn pending_schedules(&self) -> usize {
+        self.pending_schedules.load(Ordering::SeqCst)
+    }
+
     /// Returns a reference to the configuration.
     pub fn config(&self) -> &Config {
         &self.config
@@ -38,6 +468,12 @@ impl Runtime {
         &mut self.config
     }
 
+    /// Serialize the effective (loaded and validated) configuration to JSON.
+    /// Handy for tooling and for debugging what the runtime actually sees.
+    pub fn effective_config_json(&self) -> Value {
+        serde_json::to_value(&self.config).unwrap_or(Value::Null)
+    }
+
     /// Enable or disable dry-run mode at runtime.
     pub fn set_dry_run(&mut self, dry_run: bool) {
         self.executor.set_dry_run(dry_run);
@@ -52,33 +488,256 @@ impl Runtime {
     /// - Expects a `"type"` field to select the appropriate workflow binding
     /// - Maps variables according to the event binding vars_map
     /// - Executes the referenced workflow
-    pub fn run_event(&mut self, event: &Value) -> Result<()> {
+    ///
+    /// Batch envelopes (see `Config::batch_key`) are unpacked and each element is
+    /// handled as its own event, in order.
+    ///
+    /// Async: `sleep_ms`, `sleep_rand_ms` and post-delays await a Tokio timer, so other
+    /// tasks (sources, schedules) keep running while a workflow waits.
+    pub async fn run_event(&mut self, event: &Value) -> Result<()> {
+        let Some(batch) = self.batch_elements(event) else {
+            return self.run_event_detailed(event).await.map(|_| ());
+        };
+
+        debug!(target: "notabot::runtime", size = batch.len(), "Processing event batch");
+        let mut failed = 0usize;
+        for (idx, element) in batch.iter().enumerate() {
+            if let Err(err) = self.run_event_detailed(element).await {
+                if !self.config.batch_continue_on_error {
+                    return Err(err.context(format!("Batch element {idx} failed")));
+                }
+                warn!(
+                    target: "notabot::runtime",
+                    index = idx,
+                    error = %format!("{err:#}"),
+                    "Batch element failed; continuing"
+                );
+                failed += 1;
+            }
+        }
+        if failed > 0 {
+            warn!(
+                target: "notabot::runtime",
+                failed,
+                total = batch.len(),
+                "Event batch completed with failures"
+            );
+        }
+        Ok(())
+    }
+
+    /// The elements of a batch envelope, or `None` if `event` is not one.
+    fn batch_elements<'a>(&self, event: &'a Value) -> Option<&'a Vec<Value>> {
+        let key = self.batch_key();
+        if key.is_empty() || event.get("type").is_some() {
+            return None;
+        }
+        event.get(key)?.as_array()
+    }
+
+    fn batch_key(&self) -> &str {
+        self.config
+            .batch_key
+            .as_deref()
+            .unwrap_or(DEFAULT_BATCH_KEY)
+    }
+
+    /// Remove the hop counter (`EVENT_HOPS_FIELD`) from an event read from a source,
+    /// including the elements of a batch envelope. Only `emit_event` sets it, so sources
+    /// cannot forge it to skip or exhaust `max_event_hops`.
+    pub fn strip_event_hops(&self, event: &mut Value) {
+        let is_batch = self.batch_elements(event).is_some();
+        let Some(fields) = event.as_object_mut() else {
+            return;
+        };
+        fields.remove(EVENT_HOPS_FIELD);
+        if let Some(elements) = fields
+            .get_mut(self.batch_key())
+            .and_then(Value::as_array_mut)
+            && is_batch
+        {
+            for element in elements.iter_mut().filter_map(Value::as_object_mut) {
+                element.remove(EVENT_HOPS_FIELD);
+            }
+        }
+    }
+
+    /// Like `run_event`, but reports which workflow ran, how many steps executed,
+    /// and the final variables.
+    pub async fn run_event_detailed(&mut self, event: &Value) -> Result<EventOutcome> {
+        if let Some(workflow) = event.get(INLINE_WORKFLOW_FIELD).and_then(|v| v.as_str()) {
+            if self.config.allow_inline_workflow {
+                // Same gates as bound events: the tag filter, then the rate limit of
+                // the event's `type` (if it has one)
+                if !self.workflow_matches_tags(workflow) {
+                    info!(
+                        target: "notabot::runtime",
+                        %workflow,
+                        "Inline workflow skipped: it has none of the selected tags"
+                    );
+                    return Ok(EventOutcome::skipped(workflow.to_string(), false, true));
+                }
+                if let Some(event_type) = event.get("type").and_then(Value::as_str)
+                    && !self.rate_limit_allows(event_type)
+                {
+                    debug!(
+                        target: "notabot::runtime",
+                        %event_type, %workflow,
+                        "Event dropped by rate limit"
+                    );
+                    return Ok(EventOutcome::skipped(workflow.to_string(), true, false));
+                }
+                debug!(target: "notabot::runtime", %workflow, "Running inline-selected workflow");
+                let vars = vars_from_top_level_fields(event);
+                return self.execute_workflow(workflow, event, vars).await;
+            }
+            debug!(
+                target: "notabot::runtime",
+                %workflow,
+                "Ignoring '_workflow' field (allow_inline_workflow is disabled)"
+            );
+        }
+
         let event_type = event
             .get("type")
             .and_then(|v| v.as_str())
             .ok_or_else(|| anyhow::anyhow!("Event is missing string field 'type'"))?;
 
-        let binding =
-            self.config.events.get(event_type).cloned().ok_or_else(|| {
-                anyhow::anyhow!("No event binding found for type '{}'", event_type)
-            })?;
+        let binding = match self.find_binding(event_type) {
+            Some((key, binding)) => {
+                debug!(
+                    target: "notabot::runtime",
+                    %event_type, binding = %key,
+                    "Using the matching binding"
+                );
+                binding.clone()
+            }
+            None => {
+                let workflow = self.config.default_workflow.clone().ok_or_else(|| {
+                    anyhow::anyhow!("No event binding found for type '{}'", event_type)
+                })?;
+                debug!(
+                    target: "notabot::runtime",
+                    %event_type, %workflow,
+                    "No binding for event type; using the default workflow"
+                );
+                EventBinding {
+                    workflow,
+                    vars_map: Default::default(),
+                    schema: None,
+                }
+            }
+        };
+
+        if !self.workflow_matches_tags(&binding.workflow) {
+            info!(
+                target: "notabot::runtime",
+                %event_type,
+                workflow = %binding.workflow,
+                "Binding skipped: workflow has none of the selected tags"
+            );
+            return Ok(EventOutcome::skipped(binding.workflow, false, true));
+        }
+
+        if let Some(schema) = &binding.schema {
+            self.check_event_schema(schema, event)
+                .with_context(|| format!("Rejected event of type '{}'", event_type))?;
+        }
+
+        if !self.rate_limit_allows(event_type) {
+            debug!(
+                target: "notabot::runtime",
+                %event_type,
+                "Event dropped by rate limit"
+            );
+            return Ok(EventOutcome::skipped(binding.workflow, true, false));
+        }
+
+        self.run_binding(binding, event).await
+    }
+
+    /// Restrict active event bindings to workflows tagged with any of `tags`
+    /// (see `Config::workflow_meta`). `None` disables the filter.
+    pub fn set_tag_filter(&mut self, tags: Option<Vec<String>>) {
+        self.only_tags = tags;
+    }
+
+    /// Whether `workflow` passes the tag filter (always true without a filter).
+    fn workflow_matches_tags(&self, workflow: &str) -> bool {
+        let Some(only) = &self.only_tags else {
+            return true;
+        };
+        self.config
+            .workflow_meta
+            .get(workflow)
+            .is_some_and(|meta| meta.tags.iter().any(|t| only.contains(t)))
+    }
 
-        self.run_binding(binding, event)
+    /// Check (and consume from) the token bucket for `event_type`, if it is rate limited.
+    fn rate_limit_allows(&self, event_type: &str) -> bool {
+        let Some(&per_second) = self.config.rate_limits.get(event_type) else {
+            return true;
+        };
+        self.rate_buckets
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(event_type.to_string())
+            .or_insert_with(|| TokenBucket::new(per_second))
+            .try_acquire(per_second)
     }
 
     /// Run a known workflow by name with a provided variables map (skips the event->vars mapping).
-    pub fn run_workflow_by_name(
+    pub async fn run_workflow_by_name(
         &mut self,
         workflow_name: &str,
         vars: HashMap<String, String>,
     ) -> Result<()> {
         self.execute_workflow(workflow_name, &Value::Null, vars)
+            .await
+            .map(|_| ())
+    }
+
+    /// The binding for `event_type` and its key: an exact key first, otherwise the
+    /// wildcard key (e.g. `order.*`) with the longest literal prefix. Ties go to the
+    /// key that sorts first.
+    fn find_binding(&self, event_type: &str) -> Option<(&str, &EventBinding)> {
+        if let Some((key, binding)) = self.config.events.get_key_value(event_type) {
+            return Some((key, binding));
+        }
+        let mut best: Option<(&str, &EventBinding)> = None;
+        for (key, binding) in &self.config.events {
+            if !key.contains('*') || !simple_pattern_match(event_type, key) {
+                continue;
+            }
+            if best
+                .is_none_or(|(best_key, _)| literal_prefix_len(key) > literal_prefix_len(best_key))
+            {
+                best = Some((key, binding));
+            }
+        }
+        best
+    }
+
+    /// Fail with every violation when `event` does not match a binding's `schema`.
+    /// Schemas are compiled on first use and cached by their source text.
+    fn check_event_schema(&mut self, schema: &Value, event: &Value) -> Result<()> {
+        let key = schema.to_string();
+        if !self.event_schemas.contains_key(&key) {
+            let validator = jsonschema::validator_for(schema)
+                .map_err(|e| anyhow::anyhow!("Invalid event schema: {}", e))?;
+            self.event_schemas.insert(key.clone(), validator);
+        }
+        let violations = schema_violations(&self.event_schemas[&key], event);
+        if !violations.is_empty() {
+            bail!("Event does not match its schema: {}", violations.join("; "));
+        }
+        Ok(())
     }
 
     /// Run an event binding (used by run_event)
-    fn run_binding(&mut self, binding: EventBinding, event: &Value) -> Result<()> {
+    async fn run_binding(&mut self, binding: EventBinding, event: &Value) -> Result<EventOutcome> {
         let vars = self.vars_from_event(&binding, event)?;
-        self.execute_workflow(&binding.workflow, event, vars)
+        self.execute_workflow(&binding.workflow, event, vars).await
     }
 
     /// Build the workflow-scoped variables map from an event according to the binding.
@@ -107,12 +766,12 @@ impl Runtime {
     }
 
     /// Execute all steps in a named workflow with the provided variables.
-    fn execute_workflow(
+    async fn execute_workflow(
         &mut self,
         workflow_name: &str,
         event: &Value,
         mut vars: HashMap<String, String>,
-    ) -> Result<()> {
+    ) -> Result<EventOutcome> {
         let steps = self
             .config
             .workflows
@@ -126,27 +785,80 @@ impl Runtime {
             steps = steps.len(),
             "Starting workflow"
         );
+        self.action_results.clear();
+        self.action_results_dropped = 0;
+        self.aborting = false;
+        let timeout_ms = self
+            .config
+            .workflow_meta
+            .get(workflow_name)
+            .and_then(|meta| meta.timeout_ms);
+
+        let mut current_step = 0usize;
+        let mut steps_executed = steps.len();
+        let run_steps = async {
+            for (idx, step) in steps.iter().enumerate() {
+                current_step = idx;
+                trace!(
+                    target: "notabot::runtime",
+                    %workflow_name, step_index = idx,
+                    "Executing step"
+                );
+                self.execute_action(step, event, &mut vars, 0)
+                    .instrument(step_span(idx))
+                    .await
+                    .with_context(|| {
+                        format!("Workflow '{}' failed at step {}", workflow_name, idx)
+                    })?;
+                if self.aborting {
+                    steps_executed = idx + 1;
+                    break;
+                }
+            }
+            Ok::<(), anyhow::Error>(())
+        }
+        .instrument(workflow_span(workflow_name));
+        let result = match timeout_ms {
+            Some(ms) => match tokio::time::timeout(Duration::from_millis(ms), run_steps).await {
+                Ok(result) => result,
+                Err(_) => Err(anyhow::anyhow!(
+                    "Workflow '{workflow_name}' timed out after {ms} ms at step {current_step}"
+                )),
+            },
+            None => run_steps.await,
+        };
+        // Keys held by `key_down` must not outlive the workflow, whichever way it ended.
+        self.executor.release_held_keys();
+        result?;
 
-        for (idx, step) in steps.iter().enumerate() {
-            trace!(
+        let aborted = std::mem::take(&mut self.aborting);
+        if aborted {
+            info!(
+                target: "notabot::runtime",
+                %workflow_name,
+                steps_executed,
+                "Workflow aborted"
+            );
+        } else {
+            info!(
                 target: "notabot::runtime",
-                %workflow_name, step_index = idx,
-                "Executing step"
+                %workflow_name,
+                "Workflow completed"
             );
-            self.execute_action(step, event, &mut vars, 0)
-                .with_context(|| format!("Workflow '{}' failed at step {}", workflow_name, idx))?;
         }
-
-        info!(
-            target: "notabot::runtime",
-            %workflow_name,
-            "Workflow completed"
-        );
-        Ok(())
+        Ok(EventOutcome {
+            workflow: workflow_name.to_string(),
+            steps_executed,
+            vars_final: vars,
+            rate_limited: false,
+            tag_filtered: false,
+            aborted,
+        })
     }
 
-    /// Execute a single action with recursion/sequence support.
-    fn execute_action(
+    /// Execute a single action with recursion/sequence support, then apply the
+    /// post-delay of input actions. Input actions are skipped while disarmed.
+    async fn execute_action(
         &mut self,
         action: &ActionDef,
         event: &Value,
@@ -156,17 +868,212 @@ impl Runtime {
         if depth > MAX_DEPTH {
             bail!("Maximum action nesting depth ({MAX_DEPTH}) exceeded (possible cycle)");
         }
+        if self.in_parallel && action.is_input() {
+            bail!(
+                "Input action '{}' cannot run inside `parallel`",
+                action.type_name()
+            );
+        }
+
+        if let Some(trace) = &mut self.trace {
+            trace.push(TraceEntry::new(action));
+        }
+
+        // Reserve the record first so containers precede their steps. It stays
+        // `Cancelled` if this future is dropped before the action finishes.
+        self.trim_action_results();
+        let slot = self.action_results_dropped + self.action_results.len();
+        self.action_results.push(ActionRecord {
+            action: action.type_name(),
+            depth,
+            result: ActionResult::Cancelled,
+        });
+
+        if action.is_input() && !self.arm.is_armed() {
+            debug!(target: "notabot::runtime", depth, "Input disarmed; skipping input action");
+            self.set_action_result(
+                slot,
+                ActionResult::Skipped {
+                    reason: "input disarmed".into(),
+                },
+            );
+            return Ok(());
+        }
+
+        // Boxed: dispatch recurses back into execute_action for nested actions
+        match Box::pin(self.dispatch_action(action, event, vars, depth)).await {
+            Ok(result) => self.set_action_result(slot, result),
+            Err(e) => {
+                self.set_action_result(
+                    slot,
+                    ActionResult::Failed {
+                        error: format!("{e:#}"),
+                    },
+                );
+                return Err(e);
+            }
+        }
+
+        if action.is_input()
+            && let Some(ms) = action.post_delay_ms().or(self.config.default_post_delay_ms)
+        {
+            self.executor.post_delay(ms).await?;
+        }
+        Ok(())
+    }
 
+    /// Dispatch a single action to its implementation and describe what it did.
+    async fn dispatch_action(
+        &mut self,
+        action: &ActionDef,
+        event: &Value,
+        vars: &mut HashMap<String, String>,
+        depth: usize,
+    ) -> Result<ActionResult> {
+        let done = |r: Result<()>| r.map(|()| ActionResult::Done);
         match action {
             ActionDef::Sequence { steps } => {
                 for (i, step) in steps.iter().enumerate() {
                     trace!(target: "notabot::runtime", depth, step_index = i, "Sequence step");
-                    self.execute_action(step, event, vars, depth + 1)?;
+                    self.execute_action(step, event, vars, depth + 1).await?;
+                    if self.aborting {
+                        break;
+                    }
+                }
+                Ok(ActionResult::Composite)
+            }
+
+            ActionDef::Parallel { steps } => {
+                let mut branches: Vec<_> =
+                    steps.iter().map(|_| (self.fork(), vars.clone())).collect();
+                let runs = branches.iter_mut().zip(steps).enumerate().map(
+                    |(i, ((runtime, branch_vars), step))| async move {
+                        trace!(target: "notabot::runtime", depth, step_index = i, "Parallel step");
+                        runtime
+                            .execute_action(step, event, branch_vars, depth + 1)
+                            .await
+                            .with_context(|| format!("parallel step {i} failed"))
+                    },
+                );
+                let result = try_join_all(runs).await.map(|_| ActionResult::Composite);
+
+                let before = vars.clone();
+                for (runtime, branch_vars) in branches {
+                    self.action_results.extend(runtime.action_results);
+                    if let (Some(trace), Some(forked)) = (&mut self.trace, runtime.trace) {
+                        trace.extend(forked);
+                    }
+                    self.last_fired.extend(runtime.last_fired);
+                    self.aborting |= runtime.aborting;
+                    if result.is_ok() {
+                        for (key, value) in branch_vars {
+                            if before.get(&key) != Some(&value) {
+                                vars.insert(key, value);
+                            }
+                        }
+                    }
+                }
+                result
+            }
+
+            ActionDef::Repeat { count, body } => {
+                let outer = vars.get(REPEAT_INDEX_VAR).cloned();
+                let mut result = Ok(());
+                let mut iterations = 0;
+                for i in 0..*count {
+                    trace!(target: "notabot::runtime", depth, iteration = i, "Repeat iteration");
+                    vars.insert(REPEAT_INDEX_VAR.to_string(), i.to_string());
+                    result = self.execute_action(body, event, vars, depth + 1).await;
+                    iterations = i + 1;
+                    if result.is_err() || self.aborting {
+                        break;
+                    }
+                }
+                match outer {
+                    Some(index) => vars.insert(REPEAT_INDEX_VAR.to_string(), index),
+                    None => vars.remove(REPEAT_INDEX_VAR),
+                };
+                result.map(|()| ActionResult::Looped { iterations })
+            }
+
+            ActionDef::While {
+                when,
+                equals,
+                body,
+                max_iterations,
+            } => {
+                let max = max_iterations.unwrap_or(DEFAULT_WHILE_MAX_ITERATIONS);
+                let mut iterations = 0;
+                while self.interp(when, vars, event) == self.interp(equals, vars, event) {
+                    if iterations == max {
+                        bail!("while loop still running after {max} iterations (max_iterations)");
+                    }
+                    trace!(target: "notabot::runtime", depth, iteration = iterations, "While iteration");
+                    self.execute_action(body, event, vars, depth + 1).await?;
+                    iterations += 1;
+                    if self.aborting {
+                        break;
+                    }
                 }
-                Ok(())
+                Ok(ActionResult::Looped { iterations })
             }
 
-            ActionDef::Ref { name } => {
+            ActionDef::Retry {
+                attempts,
+                delay_ms,
+                backoff,
+                body,
+            } => {
+                let attempts = (*attempts).max(1);
+                let outer = vars.get(ATTEMPT_VAR).cloned();
+                let mut delay = *delay_ms;
+                let mut attempt = 1;
+                let result = loop {
+                    vars.insert(ATTEMPT_VAR.to_string(), attempt.to_string());
+                    match self.execute_action(body, event, vars, depth + 1).await {
+                        Ok(()) => break Ok(ActionResult::Retried { attempts: attempt }),
+                        Err(e) if attempt == attempts => {
+                            break Err(e.context(format!("retry failed after {attempts} attempts")));
+                        }
+                        Err(e) => {
+                            warn!(
+                                target: "notabot::runtime",
+                                depth,
+                                attempt,
+                                delay_ms = delay,
+                                error = %format!("{e:#}"),
+                                "Retry attempt failed; retrying"
+                            );
+                        }
+                    }
+                    self.executor.sleep_ms(delay).await?;
+                    delay = (delay as f64 * backoff.unwrap_or(1.0)) as u64;
+                    attempt += 1;
+                };
+                match outer {
+                    Some(a) => vars.insert(ATTEMPT_VAR.to_string(), a),
+                    None => vars.remove(ATTEMPT_VAR),
+                };
+                result
+            }
+
+            ActionDef::Try { body, catch } => {
+                let Err(e) = self.execute_action(body, event, vars, depth + 1).await else {
+                    return Ok(ActionResult::Composite);
+                };
+                let error = format!("{e:#}");
+                warn!(target: "notabot::runtime", depth, %error, "Try body failed; running catch");
+                vars.insert(ERROR_VAR.to_string(), error.clone());
+                self.execute_action(catch, event, vars, depth + 1).await?;
+                Ok(ActionResult::Caught { error })
+            }
+
+            ActionDef::Ref {
+                name,
+                args,
+                export,
+                share_vars,
+            } => {
                 let referenced = self
                     .config
                     .actions
@@ -174,136 +1081,963 @@ impl Runtime {
                     .cloned()
                     .ok_or_else(|| anyhow::anyhow!("Referenced action '{}' not found", name))?;
                 trace!(target: "notabot::runtime", %name, depth, "Resolving Ref action");
-                self.execute_action(&referenced, event, vars, depth + 1)
+                if self.debounced(name) {
+                    return Ok(ActionResult::Skipped {
+                        reason: "debounced".into(),
+                    });
+                }
+                let args: Vec<(String, String)> = args
+                    .iter()
+                    .map(|(k, v)| (k.clone(), self.interp(v, vars, event)))
+                    .collect();
+                if *share_vars {
+                    let shadowed: Vec<(String, Option<String>)> = args
+                        .into_iter()
+                        .map(|(k, v)| {
+                            let prior = vars.insert(k.clone(), v);
+                            (k, prior)
+                        })
+                        .collect();
+                    let result = self
+                        .execute_action(&referenced, event, vars, depth + 1)
+                        .await;
+                    for (key, prior) in shadowed {
+                        match prior {
+                            Some(value) => vars.insert(key, value),
+                            None => vars.remove(&key),
+                        };
+                    }
+                    return result.map(|()| ActionResult::Composite);
+                }
+                let mut scope = vars.clone();
+                scope.extend(args);
+                let result = self
+                    .execute_action(&referenced, event, &mut scope, depth + 1)
+                    .await;
+                for key in export {
+                    match scope.remove(key) {
+                        Some(value) => {
+                            vars.insert(key.clone(), value);
+                        }
+                        None => {
+                            vars.remove(key);
+                        }
+                    }
+                }
+                result.map(|()| ActionResult::Composite)
             }
 
             // Mouse
-            ActionDef::MouseMove { x, y } => self.executor.mouse_move_to(*x, *y),
-            ActionDef::MouseClick { button, count } => self.executor.mouse_click(*button, *count),
-            ActionDef::MouseScroll { delta_x, delta_y } => {
-                self.executor.mouse_scroll(*delta_x, *delta_y)
+            ActionDef::MouseMove { x, y, .. } => done(self.executor.mouse_move_to(*x, *y)),
+            ActionDef::MouseMoveWindowRel {
+                title_contains,
+                x,
+                y,
+                ..
+            } => {
+                let title = self.interp(title_contains, vars, event);
+                done(self.executor.mouse_move_window_rel(&title, *x, *y))
+            }
+            ActionDef::MouseClick { button, count, .. } => {
+                done(self.executor.mouse_click(*button, *count))
+            }
+            ActionDef::MouseScroll {
+                delta_x,
+                delta_y,
+                steps,
+                step_delay_ms,
+                ..
+            } => done(
+                self.executor
+                    .mouse_scroll_steps(
+                        *delta_x,
+                        *delta_y,
+                        steps.unwrap_or(1),
+                        step_delay_ms.unwrap_or(0),
+                    )
+                    .await,
+            ),
+            ActionDef::MouseDrag {
+                from, to, button, ..
+            } => {
+                let from = (
+                    self.coord(&from.0, vars, event)?,
+                    self.coord(&from.1, vars, event)?,
+                );
+                let to = (
+                    self.coord(&to.0, vars, event)?,
+                    self.coord(&to.1, vars, event)?,
+                );
+                done(self.executor.mouse_drag(from, to, *button))
+            }
+            ActionDef::ClickGridCell {
+                origin_x,
+                origin_y,
+                cell_w,
+                cell_h,
+                col,
+                row,
+                button,
+                ..
+            } => {
+                let (x, y) = grid_cell_center(*origin_x, *origin_y, *cell_w, *cell_h, *col, *row);
+                debug!(target: "notabot::runtime", col, row, x, y, "Grid cell center computed");
+                self.executor.mouse_move_to(x, y)?;
+                done(self.executor.mouse_click(*button, None))
+            }
+            ActionDef::GetCursor { x_var, y_var } => {
+                let (x, y) = self.executor.cursor_location()?;
+                vars.insert(x_var.clone(), x.to_string());
+                vars.insert(y_var.clone(), y.to_string());
+                Ok(ActionResult::CursorLocated { x, y })
+            }
+            ActionDef::GetScreenSize {
+                width_var,
+                height_var,
+                monitor,
+            } => {
+                let (width, height) = self.executor.screen_size(monitor.unwrap_or(0))?;
+                vars.insert(width_var.clone(), width.to_string());
+                vars.insert(height_var.clone(), height.to_string());
+                Ok(ActionResult::ScreenSize { width, height })
             }
 
             // Keyboard
-            ActionDef::KeySeq { text } => {
-                let s = self.interp(text, vars);
-                self.executor.key_sequence(&s)
+            ActionDef::KeySeq { text, .. } => {
+                let s = self.interp(text, vars, event);
+                done(self.executor.key_sequence(&s))
             }
-            ActionDef::TypeText { text } => {
-                let s = self.interp(text, vars);
-                self.executor.type_text(&s)
+            ActionDef::KeyDown { key, .. } => done(self.executor.key_down(*key)),
+            ActionDef::KeyUp { key, .. } => done(self.executor.key_up(*key)),
+            ActionDef::TypeText {
+                text, per_char_ms, ..
+            } => {
+                let s = self.interp(text, vars, event);
+                match per_char_ms {
+                    Some(ms) => done(self.executor.type_text_per_char(&s, *ms).await),
+                    None => done(self.executor.type_text(&s)),
+                }
             }
 
             // Timing
-            ActionDef::SleepMs { ms } => self.executor.sleep_ms(*ms),
-            ActionDef::SleepRandMs { min, max } => self.executor.sleep_rand_ms(*min, *max),
-
-            // Window
-            ActionDef::FocusWindow { title_contains } => {
-                let title = self.interp(title_contains, vars);
-                let focused = self.executor.focus_window(&title)?;
-                if !focused {
-                    warn!(
-                        target: "notabot::runtime",
-                        %title,
-                        "focus_window reported no matching window"
-                    );
-                }
-                Ok(())
+            ActionDef::SleepMs { ms } => {
+                self.executor.sleep_ms(*ms).await?;
+                Ok(ActionResult::Slept { ms: *ms })
+            }
+            ActionDef::SleepRandMs { min, max } => {
+                let ms = self.executor.sleep_rand_ms(*min, *max).await?;
+                Ok(ActionResult::Slept { ms })
             }
 
-            // Logic & State
-            ActionDef::SetVar { name, value } => {
-                let k = self.interp(name, vars);
-                let v = self.interp(value, vars);
-                trace!(target: "notabot::runtime", key = %k, value = %v, "SetVar");
-                vars.insert(k, v);
-                Ok(())
+            ActionDef::ScheduleWorkflow {
+                name,
+                delay_ms,
+                vars: sched_vars,
+            } => {
+                let run = ScheduledRun {
+                    workflow: self.interp(name, vars, event),
+                    vars: sched_vars
+                        .iter()
+                        .map(|(k, v)| (k.clone(), self.interp(v, vars, event)))
+                        .collect(),
+                };
+                let workflow = run.workflow.clone();
+                self.schedule(run, *delay_ms)?;
+                Ok(ActionResult::Scheduled {
+                    workflow,
+                    delay_ms: *delay_ms,
+                })
             }
-            ActionDef::Conditional {
-                when,
+
+            ActionDef::CallWorkflow {
+                name,
+                vars: call_vars,
+                inherit_vars,
+            } => {
+                let steps = self
+                    .config
+                    .workflows
+                    .get(name)
+                    .cloned()
+                    .ok_or_else(|| anyhow::anyhow!("Called workflow '{}' not found", name))?;
+                trace!(target: "notabot::runtime", %name, depth, "Calling workflow");
+                let mut scope = if *inherit_vars {
+                    vars.clone()
+                } else {
+                    HashMap::new()
+                };
+                for (key, value) in call_vars {
+                    scope.insert(key.clone(), self.interp(value, vars, event));
+                }
+                let run_steps = async {
+                    for (idx, step) in steps.iter().enumerate() {
+                        self.execute_action(step, event, &mut scope, depth + 1)
+                            .instrument(step_span(idx))
+                            .await
+                            .with_context(|| {
+                                format!("Workflow '{}' failed at step {}", name, idx)
+                            })?;
+                        if self.aborting {
+                            break;
+                        }
+                    }
+                    Ok::<(), anyhow::Error>(())
+                };
+                run_steps.instrument(workflow_span(name)).await?;
+                // An abort ends the called workflow, not the caller
+                self.aborting = false;
+                Ok(ActionResult::Composite)
+            }
+
+            ActionDef::EmitEvent { event: fields } => {
+                let hops = event
+                    .get(EVENT_HOPS_FIELD)
+                    .and_then(Value::as_u64)
+                    .unwrap_or(0)
+                    + 1;
+                let max_hops = self.config.max_event_hops.unwrap_or(DEFAULT_MAX_EVENT_HOPS);
+                if hops > u64::from(max_hops) {
+                    bail!("Event chain is longer than max_event_hops ({max_hops}); not emitting");
+                }
+                let mut emitted = serde_json::Map::with_capacity(fields.len() + 1);
+                for (key, value) in fields {
+                    emitted.insert(key.clone(), self.interp_json(value, vars, event));
+                }
+                emitted.insert(EVENT_HOPS_FIELD.into(), hops.into());
+                let event_type = emitted
+                    .get("type")
+                    .and_then(Value::as_str)
+                    .map(str::to_string);
+                debug!(target: "notabot::runtime", ?event_type, hops, "Emitting event");
+                self.emit_tx.send(Value::Object(emitted)).map_err(|_| {
+                    anyhow::anyhow!("Cannot emit event: the event loop has stopped")
+                })?;
+                Ok(ActionResult::EventEmitted { event_type, hops })
+            }
+
+            ActionDef::Abort { message } => {
+                let message = message.as_ref().map(|m| self.interp(m, vars, event));
+                match &message {
+                    Some(message) => {
+                        info!(target: "notabot::runtime", %message, depth, "Workflow aborted by action")
+                    }
+                    None => info!(target: "notabot::runtime", depth, "Workflow aborted by action"),
+                }
+                self.aborting = true;
+                Ok(ActionResult::Aborted { message })
+            }
+
+            // Window
+            ActionDef::FocusWindow { title_contains } => {
+                let title = self.interp(title_contains, vars, event);
+                let focused = self.executor.focus_window(&title)?;
+                if !focused {
+                    warn!(
+                        target: "notabot::runtime",
+                        %title,
+                        "focus_window reported no matching window"
+                    );
+                }
+                Ok(ActionResult::WindowFocused { focused })
+            }
+            ActionDef::WaitForWindow {
+                title_contains,
+                timeout_ms,
+                poll_ms,
+            } => {
+                let title = self.interp(title_contains, vars, event);
+                done(
+                    self.wait_for_window(
+                        &title,
+                        poll_ms.unwrap_or(DEFAULT_WINDOW_POLL_MS),
+                        *timeout_ms,
+                    )
+                    .await,
+                )
+            }
+
+            // Logic & State
+            ActionDef::SetVar { name, value } => {
+                let k = self.interp(name, vars, event);
+                let v = self.interp(value, vars, event);
+                trace!(target: "notabot::runtime", key = %k, value = %v, "SetVar");
+                vars.insert(k.clone(), v.clone());
+                Ok(ActionResult::VarSet { name: k, value: v })
+            }
+            ActionDef::SetVarFromJson { name, path } => {
+                let k = self.interp(name, vars, event);
+                let path = self.interp(path, vars, event);
+                let v = match get_json_path(event, &path) {
+                    Some(v) => json_value_to_string(v),
+                    None => {
+                        warn!(
+                            target: "notabot::runtime",
+                            var = %k, path = %path,
+                            "Event field not found for set_var_from_json; setting empty string"
+                        );
+                        String::new()
+                    }
+                };
+                trace!(target: "notabot::runtime", key = %k, value = %v, "SetVarFromJson");
+                vars.insert(k.clone(), v.clone());
+                Ok(ActionResult::VarSet { name: k, value: v })
+            }
+            ActionDef::RegexExtract {
+                input,
+                pattern,
+                into_var,
+                group,
+            } => {
+                let input = self.interp(input, vars, event);
+                let re = self.regex(&self.interp(pattern, vars, event))?;
+                let group = group.unwrap_or(DEFAULT_REGEX_GROUP);
+                if group >= re.captures_len() {
+                    bail!("regex_extract pattern '{re}' has no capture group {group}");
+                }
+                let value = re
+                    .captures(&input)
+                    .and_then(|caps| caps.get(group))
+                    .map(|m| m.as_str().to_string())
+                    .unwrap_or_default();
+                trace!(target: "notabot::runtime", var = %into_var, %value, "RegexExtract");
+                vars.insert(into_var.clone(), value.clone());
+                Ok(ActionResult::VarSet {
+                    name: into_var.clone(),
+                    value,
+                })
+            }
+            ActionDef::Math {
+                into_var,
+                lhs,
+                op,
+                rhs,
+            } => {
+                let lhs = self.interp(lhs, vars, event);
+                let rhs = self.interp(rhs, vars, event);
+                let value = format_number(arithmetic(*op, &lhs, &rhs)?);
+                trace!(target: "notabot::runtime", var = %into_var, %value, "Math");
+                vars.insert(into_var.clone(), value.clone());
+                Ok(ActionResult::VarSet {
+                    name: into_var.clone(),
+                    value,
+                })
+            }
+            ActionDef::ClearVars { keep } => {
+                let before = vars.len();
+                vars.retain(|name, _| name.starts_with('_') || keep.contains(name));
+                let removed = before - vars.len();
+                debug!(
+                    target: "notabot::runtime",
+                    cleared = removed,
+                    kept = vars.len(),
+                    "Cleared workflow variables"
+                );
+                Ok(ActionResult::VarsCleared { removed })
+            }
+            ActionDef::Conditional {
+                when,
+                when_path,
                 equals,
+                equals_path,
+                op,
                 then,
                 else_,
             } => {
-                let lhs = self.interp(when, vars);
-                let rhs = self.interp(equals, vars);
+                let lhs = self.condition_operand(when, when_path.as_deref(), event, vars);
+                let rhs = self.condition_operand(equals, equals_path.as_deref(), event, vars);
                 debug!(
                     target: "notabot::runtime",
-                    when = %lhs, equals = %rhs, depth,
+                    when = %lhs.text, equals = %rhs.text, ?op, depth,
                     "Conditional evaluation"
                 );
-                if lhs == rhs {
-                    self.execute_action(then, event, vars, depth + 1)
+                let holds = condition_holds(*op, &lhs, &rhs)?;
+                let branch = if holds {
+                    self.execute_action(then, event, vars, depth + 1).await?;
+                    Branch::Then
                 } else if let Some(else_action) = else_ {
                     self.execute_action(else_action, event, vars, depth + 1)
+                        .await?;
+                    Branch::Else
                 } else {
-                    Ok(())
+                    Branch::Neither
+                };
+                Ok(ActionResult::Branch(branch))
+            }
+
+            ActionDef::ClipboardSet { text } => {
+                let text = self.interp(text, vars, event);
+                done(self.executor.clipboard_set(&text))
+            }
+            ActionDef::ClipboardGet { into_var } => match self.executor.clipboard_get()? {
+                Some(value) => {
+                    vars.insert(into_var.clone(), value.clone());
+                    Ok(ActionResult::VarSet {
+                        name: into_var.clone(),
+                        value,
+                    })
+                }
+                None => Ok(ActionResult::Done),
+            },
+
+            ActionDef::RunCommand {
+                program,
+                args,
+                capture_output_var,
+                ignore_exit_code,
+                timeout_ms,
+            } => {
+                let program = self.interp(program, vars, event);
+                let args: Vec<String> = args.iter().map(|a| self.interp(a, vars, event)).collect();
+                let timeout =
+                    Duration::from_millis(timeout_ms.unwrap_or(DEFAULT_RUN_COMMAND_TIMEOUT_MS));
+                let stdout = self
+                    .executor
+                    .run_command(&program, &args, *ignore_exit_code, timeout)
+                    .await?;
+                match (capture_output_var, stdout) {
+                    (Some(var), Some(out)) => {
+                        let value = out.trim().to_string();
+                        vars.insert(var.clone(), value.clone());
+                        Ok(ActionResult::VarSet {
+                            name: var.clone(),
+                            value,
+                        })
+                    }
+                    _ => Ok(ActionResult::Done),
                 }
             }
 
+            // Networking
+            ActionDef::TcpSend {
+                addr,
+                line,
+                expect_ack,
+            } => {
+                let addr = self.interp(addr, vars, event);
+                let line = self.interp(line, vars, event);
+                done(self.executor.tcp_send(&addr, &line, *expect_ack).await)
+            }
+
+            ActionDef::HttpPollUntil {
+                url,
+                path,
+                equals,
+                interval_ms,
+                timeout_ms,
+            } => {
+                let url = self.interp(url, vars, event);
+                let expected = self.interp(equals, vars, event);
+                done(
+                    self.http_poll_until(
+                        &url,
+                        path,
+                        &expected,
+                        interval_ms.unwrap_or(DEFAULT_HTTP_POLL_INTERVAL_MS),
+                        timeout_ms.unwrap_or(DEFAULT_HTTP_POLL_TIMEOUT_MS),
+                    )
+                    .await,
+                )
+            }
+
+            ActionDef::HttpRequest {
+                method,
+                url,
+                body,
+                headers,
+                into_var,
+                allow_error_status,
+            } => {
+                let method = self.interp(method, vars, event);
+                let url = self.interp(url, vars, event);
+                let body = body.as_ref().map(|b| self.interp(b, vars, event));
+                let headers = headers
+                    .iter()
+                    .map(|(k, v)| (k.clone(), self.interp(v, vars, event)))
+                    .collect();
+                let response = self
+                    .executor
+                    .http_request(
+                        &method,
+                        &url,
+                        &headers,
+                        body.as_deref(),
+                        *allow_error_status,
+                    )
+                    .await?;
+                match (into_var, response) {
+                    (Some(var), Some(value)) => {
+                        vars.insert(var.clone(), value.clone());
+                        Ok(ActionResult::VarSet {
+                            name: var.clone(),
+                            value,
+                        })
+                    }
+                    _ => Ok(ActionResult::Done),
+                }
+            }
             // Logging
             ActionDef::Log { level, message } => {
-                let msg = self.interp(message, vars);
+                let msg = self.interp(message, vars, event);
                 self.executor.log_message(*level, &msg);
-                Ok(())
+                Ok(ActionResult::Done)
+            }
+
+            ActionDef::Comment { text } => {
+                // Only render the text when it will be logged
+                if tracing::enabled!(target: "notabot::runtime", Level::TRACE) {
+                    let text = self.interp(text, vars, event);
+                    trace!(target: "notabot::runtime", %text, depth, "Comment");
+                }
+                Ok(ActionResult::Done)
+            }
+
+            ActionDef::DumpState { path } => {
+                let p = self.interp(path, vars, event);
+                let sorted_vars: std::collections::BTreeMap<_, _> = vars.iter().collect();
+                let state = serde_json::json!({
+                    "vars": sorted_vars,
+                    "globals": &self.config.globals,
+                });
+                done(self.executor.dump_state(&p, &state).await)
             }
 
             // Extensions (placeholders)
             ActionDef::OcrCheck {
                 region,
                 must_contain,
+                image,
+                then,
+                else_,
+            } => {
+                let text = self.interp(must_contain, vars, event);
+                let image = image.as_ref().map(|i| self.interp(i, vars, event));
+                let matched = self
+                    .executor
+                    .ocr_check(*region, &text, image.as_deref())
+                    .await?;
+                let value = matched.to_string();
+                vars.insert(OCR_MATCHED_VAR.to_string(), value.clone());
+                if then.is_none() && else_.is_none() {
+                    return Ok(ActionResult::VarSet {
+                        name: OCR_MATCHED_VAR.to_string(),
+                        value,
+                    });
+                }
+                let branch = match (matched, then, else_) {
+                    (true, Some(action), _) => {
+                        self.execute_action(action, event, vars, depth + 1).await?;
+                        Branch::Then
+                    }
+                    (false, _, Some(action)) => {
+                        self.execute_action(action, event, vars, depth + 1).await?;
+                        Branch::Else
+                    }
+                    _ => Branch::Neither,
+                };
+                Ok(ActionResult::Branch(branch))
+            }
+            ActionDef::PixelColorCheck {
+                x,
+                y,
+                expected,
+                tolerance,
+                then,
+                else_,
+            } => {
+                let expected = Rgb::parse_hex(&self.interp(expected, vars, event))?;
+                let matched =
+                    self.executor
+                        .pixel_color_check(*x, *y, expected, tolerance.unwrap_or(0))?;
+                let branch = if matched {
+                    self.execute_action(then, event, vars, depth + 1).await?;
+                    Branch::Then
+                } else if let Some(else_action) = else_ {
+                    self.execute_action(else_action, event, vars, depth + 1)
+                        .await?;
+                    Branch::Else
+                } else {
+                    Branch::Neither
+                };
+                Ok(ActionResult::Branch(branch))
+            }
+            ActionDef::CaptureScreen {
+                path,
+                region,
+                to_clipboard,
+                save_path_to,
             } => {
-                let text = self.interp(must_contain, vars);
-                let _ok = self.executor.ocr_check(*region, &text)?;
-                // In future, this could set a variable or influence control flow.
-                Ok(())
+                let p = self.interp(path, vars, event);
+                self.executor
+                    .capture_screen(&p, *region, *to_clipboard)
+                    .await?;
+                match save_path_to {
+                    Some(var) => {
+                        vars.insert(var.clone(), p.clone());
+                        Ok(ActionResult::VarSet {
+                            name: var.clone(),
+                            value: p,
+                        })
+                    }
+                    None => Ok(ActionResult::Done),
+                }
+            }
+        }
+    }
+
+    /// Poll `url` until the JSON field at `path` renders as `expected`, or time out.
+    async fn http_poll_until(
+        &mut self,
+        url: &str,
+        path: &str,
+        expected: &str,
+        interval_ms: u64,
+        timeout_ms: u64,
+    ) -> Result<()> {
+        if self.executor.is_dry_run() {
+            info!(target: "notabot::actions", %url, %path, %expected, "DRY-RUN http_poll_until");
+            return Ok(());
+        }
+        if !cfg!(feature = "http-client") {
+            bail!("http_poll_until requires the `http-client` feature");
+        }
+        let deadline = Instant::now() + Duration::from_millis(timeout_ms);
+        let mut last = String::from("no response");
+        for attempt in 1.. {
+            match self.executor.http_get_json(url).await {
+                Ok(body) => {
+                    let current = get_json_path(&body, path).map(json_value_to_string);
+                    if current.as_deref() == Some(expected) {
+                        debug!(target: "notabot::runtime", %url, %path, attempt, "http_poll_until matched");
+                        return Ok(());
+                    }
+                    last = match current {
+                        Some(v) => format!("'{v}'"),
+                        None => format!("field '{path}' missing"),
+                    };
+                }
+                Err(err) => last = format!("{err:#}"),
+            }
+            trace!(target: "notabot::runtime", %url, attempt, last = %last, "http_poll_until not yet");
+            if Instant::now() + Duration::from_millis(interval_ms) >= deadline {
+                break;
+            }
+            self.executor.sleep_ms(interval_ms).await?;
+        }
+        bail!(
+            "http_poll_until: '{path}' at {url} did not equal '{expected}' within {timeout_ms} ms (last: {last})"
+        )
+    }
+
+    /// Check for a window matching `title` every `poll_ms` until one exists, or time out.
+    async fn wait_for_window(&mut self, title: &str, poll_ms: u64, timeout_ms: u64) -> Result<()> {
+        if self.executor.is_dry_run() {
+            info!(target: "notabot::actions", %title, "DRY-RUN wait_for_window");
+            return Ok(());
+        }
+        let deadline = Instant::now() + Duration::from_millis(timeout_ms);
+        let mut last = String::from("no match");
+        for attempt in 1.. {
+            match self.executor.find_window(title) {
+                Ok(true) => {
+                    debug!(target: "notabot::runtime", %title, attempt, "wait_for_window found");
+                    return Ok(());
+                }
+                Ok(false) => last = String::from("no match"),
+                Err(err) => last = format!("{err:#}"),
+            }
+            trace!(target: "notabot::runtime", %title, attempt, last = %last, "wait_for_window not yet");
+            if Instant::now() + Duration::from_millis(poll_ms) >= deadline {
+                break;
+            }
+            self.executor.sleep_ms(poll_ms).await?;
+        }
+        bail!(
+            "wait_for_window: no window with a title containing '{title}' appeared within {timeout_ms} ms (last: {last})"
+        )
+    }
+
+    /// Arm a timer that emits `run` on the schedule channel after `delay_ms`.
+    fn schedule(&self, run: ScheduledRun, delay_ms: u64) -> Result<()> {
+        if !self.config.workflows.contains_key(&run.workflow) {
+            bail!("Cannot schedule unknown workflow '{}'", run.workflow);
+        }
+        let handle = tokio::runtime::Handle::try_current()
+            .context("schedule_workflow requires a running Tokio runtime")?;
+
+        let max = self
+            .config
+            .max_pending_schedules
+            .unwrap_or(DEFAULT_MAX_PENDING_SCHEDULES);
+        let pending = Arc::clone(&self.pending_schedules);
+        if pending
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| {
+                (n < max).then_some(n + 1)
+            })
+            .is_err()
+        {
+            bail!(
+                "Too many pending scheduled workflows (max {max}); refusing to schedule '{}'",
+                run.workflow
+            );
+        }
+
+        debug!(
+            target: "notabot::runtime",
+            workflow = %run.workflow, delay_ms,
+            "Scheduling workflow"
+        );
+        let tx = self.schedule_tx.clone();
+        handle.spawn(async move {
+            tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+            pending.fetch_sub(1, Ordering::SeqCst);
+            if tx.send(run).is_err() {
+                trace!(target: "notabot::runtime", "Schedule receiver dropped; discarding run");
             }
-            ActionDef::CaptureScreen { path, region } => {
-                let p = self.interp(path, vars);
-                self.executor.capture_screen(&p, *region)
+        });
+        Ok(())
+    }
+
+    /// Whether a `ref` to `name` falls inside its debounce window (and must be skipped).
+    /// Records the run time when it is allowed.
+    fn debounced(&mut self, name: &str) -> bool {
+        let Some(window) = self
+            .config
+            .action_meta
+            .get(name)
+            .and_then(|meta| meta.debounce_ms)
+        else {
+            return false;
+        };
+        let now = Instant::now();
+        if let Some(last) = self.last_fired.get(name)
+            && now.duration_since(*last) < Duration::from_millis(window)
+        {
+            info!(
+                target: "notabot::runtime",
+                action = %name,
+                debounce_ms = window,
+                "Debounced action skipped"
+            );
+            return true;
+        }
+        self.last_fired.insert(name.to_string(), now);
+        false
+    }
+
+    /// Resolve one side of a `conditional`: the event path when given, else the
+    /// interpolated string.
+    fn condition_operand(
+        &self,
+        template: &str,
+        path: Option<&str>,
+        event: &Value,
+        vars: &HashMap<String, String>,
+    ) -> Operand {
+        let typed = match path {
+            Some(path) => get_json_path(event, path).cloned(),
+            None if template.contains("{{") => self
+                .template(template)
+                .typed_value(&self.config.globals, event),
+            None => None,
+        };
+        let text = match (&typed, path) {
+            (Some(value), _) => json_value_to_string(value),
+            (None, Some(_)) => String::new(),
+            (None, None) => self.interp(template, vars, event),
+        };
+        Operand { text, typed }
+    }
+
+    /// Resolve a coordinate, interpolating and parsing it when given as a template.
+    fn coord(&self, coord: &Coord, vars: &HashMap<String, String>, event: &Value) -> Result<i32> {
+        match coord {
+            Coord::Value(v) => Ok(*v),
+            Coord::Template(t) => {
+                let rendered = self.interp(t, vars, event);
+                rendered.trim().parse().with_context(|| {
+                    format!("Coordinate '{t}' rendered to '{rendered}', which is not an integer")
+                })
             }
         }
     }
 
-    /// Interpolate a string with the current variables and config globals.
-    fn interp(&self, s: &str, vars: &HashMap<String, String>) -> String {
-        interpolation::interpolate_string(s, vars, &self.config.globals)
+    /// Interpolate a string with the current variables, event and config globals.
+    /// Strings without tokens are returned as-is; others are parsed once and cached.
+    fn interp(&self, s: &str, vars: &HashMap<String, String>, event: &Value) -> String {
+        if !s.contains("{{") {
+            return s.to_string();
+        }
+        self.template(s)
+            .render_with_event(vars, &self.config.globals, event)
+    }
+
+    /// Interpolate every string in `value` (recursively), like `interpolate_json` but
+    /// also resolving `{{$path}}` against `event`.
+    fn interp_json(&self, value: &Value, vars: &HashMap<String, String>, event: &Value) -> Value {
+        match value {
+            Value::String(s) => Value::String(self.interp(s, vars, event)),
+            Value::Array(items) => Value::Array(
+                items
+                    .iter()
+                    .map(|item| self.interp_json(item, vars, event))
+                    .collect(),
+            ),
+            Value::Object(fields) => Value::Object(
+                fields
+                    .iter()
+                    .map(|(key, value)| (key.clone(), self.interp_json(value, vars, event)))
+                    .collect(),
+            ),
+            _ => value.clone(),
+        }
+    }
+
+    /// The compiled regex for `pattern`, from the cache when possible.
+    fn regex(&self, pattern: &str) -> Result<Regex> {
+        let mut cache = self.regexes.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(re) = cache.get(pattern) {
+            return Ok(re.clone());
+        }
+        let re = Regex::new(pattern).with_context(|| format!("Invalid regex '{pattern}'"))?;
+        if cache.len() >= MAX_CACHED_REGEXES {
+            cache.clear();
+        }
+        cache.insert(pattern.to_string(), re.clone());
+        Ok(re)
+    }
+
+    /// The parsed template for `s`, from the cache when possible.
+    fn template(&self, s: &str) -> Arc<Template> {
+        let mut cache = self.templates.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(t) = cache.get(s) {
+            return Arc::clone(t);
+        }
+        if cache.len() >= MAX_CACHED_TEMPLATES {
+            cache.clear();
+        }
+        let t = Arc::new(Template::parse(s));
+        cache.insert(s.to_string(), Arc::clone(&t));
+        t
+    }
+}
+
+/// One side of a `conditional`: its text, plus the JSON value when it was read
+/// directly from a global or event field (see `Template::typed_value`).
+struct Operand {
+    text: String,
+    typed: Option<Value>,
+}
+
+impl Operand {
+    fn is_number(&self) -> bool {
+        matches!(self.typed, Some(Value::Number(_)))
     }
 }
 
-/// Convert a JSON value to a user-friendly string:
-/// - Strings are returned as-is.
-/// - Numbers/bools are rendered via to_string().
-/// - Arrays/objects are serialized as compact JSON.
-fn json_value_to_string(v: &Value) -> String {
-    match v {
-        Value::String(s) => s.clone(),
-        other => other.to_string(),
+/// Evaluate a `conditional`. Without `op` the sides are compared as strings, unless
+/// one of them is a typed number: then both are compared as numbers when they parse.
+fn condition_holds(op: Option<CompareOp>, lhs: &Operand, rhs: &Operand) -> Result<bool> {
+    match op {
+        Some(op) => compare(op, &lhs.text, &rhs.text),
+        None if lhs.is_number() || rhs.is_number() => compare(CompareOp::Eq, &lhs.text, &rhs.text),
+        None => Ok(lhs.text == rhs.text),
     }
 }
 
-/// Get a JSON value by a dotted path (e.g., "order.side").
-fn get_json_path<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
-    if path.is_empty() {
-        return Some(value);
+/// Evaluate `lhs op rhs`: numerically when both sides parse as numbers, else as strings
+/// (`eq`/`ne` only).
+fn compare(op: CompareOp, lhs: &str, rhs: &str) -> Result<bool> {
+    if let (Ok(l), Ok(r)) = (lhs.trim().parse::<f64>(), rhs.trim().parse::<f64>()) {
+        return Ok(match op {
+            CompareOp::Eq => l == r,
+            CompareOp::Ne => l != r,
+            CompareOp::Lt => l < r,
+            CompareOp::Le => l <= r,
+            CompareOp::Gt => l > r,
+            CompareOp::Ge => l >= r,
+        });
     }
-    let mut current = value;
-    for seg in path.split('.') {
-        match current {
-            Value::Object(map) => {
-                current = map.get(seg)?;
-            }
-            _ => return None,
+    match op {
+        CompareOp::Eq => Ok(lhs == rhs),
+        CompareOp::Ne => Ok(lhs != rhs),
+        _ => bail!("conditional {op:?} needs numbers, got '{lhs}' and '{rhs}'"),
+    }
+}
+
+/// Evaluate a `math` action on two numeric strings.
+fn arithmetic(op: MathOp, lhs: &str, rhs: &str) -> Result<f64> {
+    let parse = |side: &str| {
+        side.trim()
+            .parse::<f64>()
+            .with_context(|| format!("math operand '{side}' is not a number"))
+    };
+    let (l, r) = (parse(lhs)?, parse(rhs)?);
+    Ok(match op {
+        MathOp::Add => l + r,
+        MathOp::Sub => l - r,
+        MathOp::Mul => l * r,
+        MathOp::Div | MathOp::Rem if r == 0.0 => {
+            bail!("math: division by zero ('{lhs}' divided by '{rhs}')")
         }
+        MathOp::Div => l / r,
+        MathOp::Rem => l % r,
+    })
+}
+
+/// Render a number, without a fractional part when it is a whole number.
+fn format_number(n: f64) -> String {
+    // Integers up to 2^53 are exact in f64
+    if n.fract() == 0.0 && n.abs() < 9_007_199_254_740_992.0 {
+        format!("{}", n as i64)
+    } else {
+        n.to_string()
     }
-    Some(current)
+}
+
+/// Center of cell (`col`, `row`) in a uniform grid whose top-left corner is at the origin.
+/// Saturates instead of overflowing for out-of-range inputs.
+fn grid_cell_center(
+    origin_x: i32,
+    origin_y: i32,
+    cell_w: u32,
+    cell_h: u32,
+    col: u32,
+    row: u32,
+) -> (i32, i32) {
+    let axis = |origin: i32, size: u32, index: u32| {
+        let offset = i64::from(size) * i64::from(index) + i64::from(size / 2);
+        (i64::from(origin) + offset).clamp(i64::from(i32::MIN), i64::from(i32::MAX)) as i32
+    };
+    (axis(origin_x, cell_w, col), axis(origin_y, cell_h, row))
+}
+
+/// Expose every top-level field of an object event as a workflow variable.
+fn vars_from_top_level_fields(event: &Value) -> HashMap<String, String> {
+    event
+        .as_object()
+        .map(|map| {
+            map.iter()
+                .map(|(k, v)| (k.clone(), json_value_to_string(v)))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Span around the steps of a workflow; its field is attached to every event inside
+/// (shown in the JSON log format).
+fn workflow_span(workflow_name: &str) -> Span {
+    info_span!(target: "notabot::runtime", "workflow", %workflow_name)
+}
+
+/// Span around one top-level step of a workflow (see `workflow_span`).
+fn step_span(step_index: usize) -> Span {
+    info_span!(target: "notabot::runtime", "step", step_index)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::LogLevel;
+    use crate::config::{ActionMeta, LogLevel, WorkflowMeta};
     use serde_json::json;
 
     #[test]
@@ -328,6 +2062,7 @@ mod tests {
                 ("age".into(), "user.age".into()),
                 ("missing".into(), "not.there".into()),
             ]),
+            schema: None,
         };
         let event = json!({"type":"x","user":{"name":"Zied","age": 33}});
 
@@ -347,37 +2082,2122 @@ mod tests {
         let mut vars = HashMap::new();
         vars.insert("user".into(), "Alice".into());
 
-        let out = rt.interp("Hi {{user}} from {{@app}}", &vars);
+        let event = json!({ "order": { "side": "buy" } });
+        let out = rt.interp("Hi {{user}} from {{@app}}", &vars, &event);
         assert_eq!(out, "Hi Alice from Notabot");
+
+        // Event fields are read directly; unknown paths stay intact
+        let out = rt.interp(
+            "{{$order.side|upper}} {{$order.qty}} {{$order.qty:1}}",
+            &vars,
+            &event,
+        );
+        assert_eq!(out, "BUY {{$order.qty}} 1");
+    }
+
+    #[test]
+    fn test_interp_caches_parsed_templates() {
+        let rt = Runtime::new(Config::default(), true);
+        let mut vars = HashMap::new();
+        vars.insert("user".into(), "Alice".into());
+
+        let event = Value::Null;
+
+        assert_eq!(rt.interp("plain", &vars, &event), "plain");
+        assert_eq!(rt.interp("Hi {{user}}", &vars, &event), "Hi Alice");
+        vars.insert("user".into(), "Bob".into());
+        assert_eq!(rt.interp("Hi {{user}}", &vars, &event), "Hi Bob");
+
+        // Only templates with tokens are cached, once each
+        assert_eq!(rt.templates.lock().unwrap().len(), 1);
     }
 
     #[test]
-    fn test_workflow_runs_empty_sequence() {
+    fn test_effective_config_json_round_trips() {
+        let cfg = crate::config::load_from_str(
+            r#"{
+                "sources": [{ "type": "tcp", "bind": "127.0.0.1:6000" }],
+                "actions": { "hello": { "type": "type_text", "text": "Hi {{name}}" } },
+                "workflows": { "greet": [{ "type": "ref", "name": "hello" }] },
+                "events": { "greet": { "workflow": "greet", "vars_map": { "name": "who" } } },
+                "globals": { "app": "Notabot" },
+                "dry_run_time_scale": 0.5
+            }"#,
+        )
+        .unwrap();
+        let rt = Runtime::new(cfg.clone(), true);
+
+        let dumped = rt.effective_config_json();
+        let reloaded: Config = serde_json::from_value(dumped).unwrap();
+        assert_eq!(reloaded, cfg);
+    }
+
+    #[tokio::test]
+    async fn test_workflow_runs_empty_sequence() {
         let mut cfg = Config::default();
         cfg.workflows.insert("empty".into(), vec![]);
         let mut rt = Runtime::new(cfg, true);
         rt.execute_workflow("empty", &Value::Null, HashMap::new())
+            .await
             .unwrap();
     }
 
-    #[test]
-    fn test_conditional_equal_branch() {
+    #[tokio::test]
+    async fn test_run_event_detailed_reports_outcome() {
         let mut cfg = Config::default();
         cfg.workflows.insert(
-            "wf".into(),
-            vec![ActionDef::Conditional {
-                when: "{{x}}".into(),
-                equals: "yes".into(),
-                then: Box::new(ActionDef::Log {
+            "greet".into(),
+            vec![
+                ActionDef::SetVar {
+                    name: "greeting".into(),
+                    value: "Hi {{who}}".into(),
+                },
+                ActionDef::Log {
                     level: LogLevel::Info,
-                    message: "OK".into(),
-                }),
-                else_: None,
+                    message: "{{greeting}}".into(),
+                },
+            ],
+        );
+        cfg.events.insert(
+            "hello".into(),
+            EventBinding {
+                workflow: "greet".into(),
+                vars_map: HashMap::from([("who".into(), "name".into())]),
+                schema: None,
+            },
+        );
+        let mut rt = Runtime::new(cfg, true);
+
+        let outcome = rt
+            .run_event_detailed(&json!({"type": "hello", "name": "Bob"}))
+            .await
+            .unwrap();
+        assert_eq!(outcome.workflow, "greet");
+        assert_eq!(outcome.steps_executed, 2);
+        assert_eq!(
+            outcome.vars_final.get("greeting").map(String::as_str),
+            Some("Hi Bob")
+        );
+    }
+
+    fn batch_config() -> Config {
+        let mut cfg = Config::default();
+        for name in ["first", "second"] {
+            cfg.workflows.insert(
+                name.into(),
+                vec![ActionDef::DumpState {
+                    path: format!("{{{{dir}}}}/{name}.json"),
+                }],
+            );
+            cfg.events.insert(
+                name.into(),
+                EventBinding {
+                    workflow: name.into(),
+                    vars_map: HashMap::from([("dir".into(), "dir".into())]),
+                    schema: None,
+                },
+            );
+        }
+        cfg
+    }
+
+    #[tokio::test]
+    async fn test_batch_envelope_runs_each_event_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let d = dir.path().to_str().unwrap();
+        let mut rt = Runtime::new(batch_config(), false);
+
+        rt.run_event(&json!({"batch": [
+            {"type": "first", "dir": d},
+            {"type": "second", "dir": d},
+        ]}))
+        .await
+        .unwrap();
+        assert!(dir.path().join("first.json").exists());
+        assert!(dir.path().join("second.json").exists());
+    }
+
+    #[tokio::test]
+    async fn test_batch_envelope_stops_or_continues_on_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let d = dir.path().to_str().unwrap();
+        let batch = json!({"items": [{"type": "nope"}, {"type": "second", "dir": d}]});
+
+        let cfg = Config {
+            batch_key: Some("items".into()),
+            ..batch_config()
+        };
+        let mut rt = Runtime::new(cfg.clone(), false);
+        assert!(rt.run_event(&batch).await.is_err());
+        assert!(!dir.path().join("second.json").exists());
+
+        let mut rt = Runtime::new(
+            Config {
+                batch_continue_on_error: true,
+                ..cfg
+            },
+            false,
+        );
+        rt.run_event(&batch).await.unwrap();
+        assert!(dir.path().join("second.json").exists());
+    }
+
+    #[test]
+    fn test_check_input_ready_reports_friendly_init_error() {
+        fn failing_init(
+            _: &enigo::Settings,
+        ) -> std::result::Result<enigo::Enigo, enigo::NewConError> {
+            Err(enigo::NewConError::NoPermission)
+        }
+
+        let mut rt = Runtime::new(Config::default(), false);
+        rt.executor.set_enigo_init(failing_init);
+        let err = rt.check_input_ready().unwrap_err().to_string();
+        assert!(err.contains("permission"), "{err}");
+        assert!(err.contains("--dry-run"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_post_delay_applies_to_input_actions_only() {
+        use std::time::Instant;
+
+        let mut cfg = Config {
+            dry_run_time_scale: 1.0,
+            default_post_delay_ms: Some(40),
+            ..Default::default()
+        };
+        cfg.workflows.insert(
+            "input".into(),
+            vec![ActionDef::MouseMove {
+                x: 1,
+                y: 2,
+                post_delay_ms: Some(120),
+            }],
+        );
+        cfg.workflows.insert(
+            "log".into(),
+            vec![ActionDef::Log {
+                level: LogLevel::Info,
+                message: "no delay".into(),
             }],
         );
         let mut rt = Runtime::new(cfg, true);
-        let mut vars = HashMap::new();
-        vars.insert("x".into(), "yes".into());
-        rt.execute_workflow("wf", &Value::Null, vars).unwrap();
+
+        let start = Instant::now();
+        rt.run_workflow_by_name("input", HashMap::new())
+            .await
+            .unwrap();
+        let input_elapsed = start.elapsed();
+        assert!(
+            input_elapsed >= Duration::from_millis(120),
+            "{input_elapsed:?}"
+        );
+
+        let start = Instant::now();
+        rt.run_workflow_by_name("log", HashMap::new())
+            .await
+            .unwrap();
+        assert!(start.elapsed() < Duration::from_millis(40));
+    }
+
+    #[tokio::test]
+    async fn test_disarmed_input_actions_do_not_reach_enigo() {
+        fn failing_init(_: &enigo::Settings) -> Result<enigo::Enigo, enigo::NewConError> {
+            Err(enigo::NewConError::EstablishCon("forced failure"))
+        }
+
+        let mut cfg = Config {
+            require_arm: true,
+            ..Default::default()
+        };
+        cfg.workflows.insert(
+            "click".into(),
+            vec![ActionDef::MouseClick {
+                button: crate::config::MouseButton::Left,
+                count: None,
+                post_delay_ms: None,
+            }],
+        );
+        // Real (non-dry-run) executor whose Enigo can never be created
+        let mut rt = Runtime::new(cfg, false);
+        rt.executor.set_enigo_init(failing_init);
+
+        let switch = rt.arm_switch();
+        assert!(!switch.is_armed());
+        rt.run_workflow_by_name("click", HashMap::new())
+            .await
+            .unwrap();
+
+        switch.arm();
+        let err = rt
+            .run_workflow_by_name("click", HashMap::new())
+            .await
+            .unwrap_err();
+        assert!(format!("{err:#}").contains("forced failure"), "{err:#}");
+    }
+
+    #[tokio::test]
+    async fn test_mouse_drag_resolves_literal_and_template_coords() {
+        use crate::config::MouseButton;
+        use crate::testing::{CapturedAction, capturing_runtime};
+
+        let mut cfg = Config::default();
+        cfg.workflows.insert(
+            "drag".into(),
+            vec![ActionDef::MouseDrag {
+                from: (Coord::Value(10), Coord::Template("{{y}}".into())),
+                to: (Coord::Template(" {{x}} ".into()), Coord::Value(-5)),
+                button: MouseButton::Right,
+                post_delay_ms: None,
+            }],
+        );
+        let (mut rt, capture) = capturing_runtime(cfg);
+
+        let vars = HashMap::from([
+            ("x".to_string(), "300".to_string()),
+            ("y".to_string(), "40".to_string()),
+        ]);
+        rt.run_workflow_by_name("drag", vars).await.unwrap();
+        assert_eq!(
+            capture.take(),
+            vec![CapturedAction::MouseDrag {
+                from: (10, 40),
+                to: (300, -5),
+                button: MouseButton::Right,
+            }]
+        );
+
+        let vars = HashMap::from([("x".to_string(), "wide".to_string())]);
+        let err = rt.run_workflow_by_name("drag", vars).await.unwrap_err();
+        assert!(format!("{err:#}").contains("not an integer"), "{err:#}");
+        assert!(capture.actions().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_key_down_up_hold_modifier_around_clicks() {
+        use crate::config::{Key, MouseButton};
+        use crate::testing::{CapturedAction, capturing_runtime};
+
+        let cfg: Config = serde_json::from_value(json!({
+            "workflows": {
+                "shift_click": [
+                    { "type": "key_down", "key": "shift" },
+                    { "type": "mouse_click", "button": "left", "count": 2 },
+                    { "type": "key_up", "key": "Shift" }
+                ]
+            }
+        }))
+        .unwrap();
+        let (mut rt, capture) = capturing_runtime(cfg);
+        rt.run_workflow_by_name("shift_click", HashMap::new())
+            .await
+            .unwrap();
+        assert_eq!(
+            capture.take(),
+            vec![
+                CapturedAction::KeyDown(Key::Shift),
+                CapturedAction::MouseClick {
+                    button: MouseButton::Left,
+                    count: 2,
+                },
+                CapturedAction::KeyUp(Key::Shift),
+            ]
+        );
+
+        let err = serde_json::from_value::<ActionDef>(json!({"type": "key_down", "key": "hyper"}))
+            .unwrap_err();
+        assert!(err.to_string().contains("unknown key 'hyper'"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_held_keys_are_released_when_workflow_fails() {
+        use crate::config::Key;
+        use crate::testing::{CapturedAction, capturing_runtime};
+
+        let cfg: Config = serde_json::from_value(json!({
+            "workflows": {
+                "broken": [
+                    { "type": "key_down", "key": "ctrl" },
+                    { "type": "key_down", "key": "shift" },
+                    { "type": "call_workflow", "name": "missing" }
+                ]
+            }
+        }))
+        .unwrap();
+        let (mut rt, capture) = capturing_runtime(cfg);
+        rt.run_workflow_by_name("broken", HashMap::new())
+            .await
+            .unwrap_err();
+        assert_eq!(
+            capture.take(),
+            vec![
+                CapturedAction::KeyDown(Key::Control),
+                CapturedAction::KeyDown(Key::Shift),
+                CapturedAction::KeyUp(Key::Shift),
+                CapturedAction::KeyUp(Key::Control),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ocr_check_sets_matched_var_for_conditional() {
+        let cfg: Config = serde_json::from_value(json!({
+            "workflows": {
+                "check": [
+                    { "type": "ocr_check", "must_contain": "Success" },
+                    {
+                        "type": "conditional",
+                        "when": "{{ocr_matched}}",
+                        "equals": "true",
+                        "then": { "type": "set_var", "name": "seen", "value": "yes" }
+                    }
+                ]
+            }
+        }))
+        .unwrap();
+        // Dry-run always reports a match
+        let mut rt = Runtime::new(cfg, true);
+        rt.run_workflow_by_name("check", HashMap::new())
+            .await
+            .unwrap();
+        let results: Vec<_> = rt
+            .last_action_results()
+            .iter()
+            .map(|r| r.result.clone())
+            .collect();
+        assert_eq!(
+            results,
+            vec![
+                ActionResult::VarSet {
+                    name: "ocr_matched".into(),
+                    value: "true".into(),
+                },
+                ActionResult::Branch(Branch::Then),
+                ActionResult::VarSet {
+                    name: "seen".into(),
+                    value: "yes".into(),
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ocr_check_runs_then_branch_on_match() {
+        let cfg: Config = serde_json::from_value(json!({
+            "workflows": {
+                "check": [{
+                    "type": "ocr_check",
+                    "must_contain": "Success",
+                    "then": { "type": "set_var", "name": "seen", "value": "yes" },
+                    "else": { "type": "set_var", "name": "seen", "value": "no" }
+                }]
+            }
+        }))
+        .unwrap();
+        let mut rt = Runtime::new(cfg, true);
+        rt.run_workflow_by_name("check", HashMap::new())
+            .await
+            .unwrap();
+        let results = rt.last_action_results();
+        assert_eq!(results[0].result, ActionResult::Branch(Branch::Then));
+        assert_eq!(
+            results[1].result,
+            ActionResult::VarSet {
+                name: "seen".into(),
+                value: "yes".into(),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_repeat_runs_body_with_index_and_nests() {
+        use crate::testing::{CapturedAction, capturing_runtime};
+
+        let cfg: Config = serde_json::from_value(json!({
+            "workflows": {
+                "grid": [{
+                    "type": "repeat",
+                    "count": 2,
+                    "body": { "type": "sequence", "steps": [
+                        { "type": "set_var", "name": "row", "value": "{{repeat_index}}" },
+                        { "type": "repeat", "count": 2, "body":
+                            { "type": "type_text", "text": "{{row}}.{{repeat_index}}" } },
+                        { "type": "type_text", "text": "end {{repeat_index}}" }
+                    ]}
+                }, { "type": "type_text", "text": "after {{repeat_index}}" }]
+            }
+        }))
+        .unwrap();
+        let (mut rt, capture) = capturing_runtime(cfg);
+        rt.run_workflow_by_name("grid", HashMap::new())
+            .await
+            .unwrap();
+
+        let typed: Vec<_> = capture
+            .take()
+            .into_iter()
+            .map(|a| match a {
+                CapturedAction::TypeText(t) => t,
+                other => panic!("unexpected {other:?}"),
+            })
+            .collect();
+        assert_eq!(
+            typed,
+            [
+                "0.0",
+                "0.1",
+                "end 0",
+                "1.0",
+                "1.1",
+                "end 1",
+                "after {{repeat_index}}"
+            ]
+        );
+        assert_eq!(
+            rt.last_action_results()[0].result,
+            ActionResult::Looped { iterations: 2 }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_parallel_overlaps_steps_and_merges_vars() {
+        let cfg: Config = serde_json::from_value(json!({
+            "workflows": {
+                "fan_out": [{
+                    "type": "parallel",
+                    "steps": [
+                        { "type": "sequence", "steps": [
+                            { "type": "sleep_ms", "ms": 100 },
+                            { "type": "set_var", "name": "a", "value": "1" }
+                        ]},
+                        { "type": "sequence", "steps": [
+                            { "type": "sleep_ms", "ms": 100 },
+                            { "type": "set_var", "name": "b", "value": "{{seed}}" }
+                        ]}
+                    ]
+                }],
+                "typing": [{
+                    "type": "parallel",
+                    "steps": [{ "type": "type_text", "text": "no" }]
+                }]
+            },
+            "events": { "fan_out": { "workflow": "fan_out", "vars_map": { "seed": "seed" } } }
+        }))
+        .unwrap();
+        let mut rt = Runtime::new(cfg, false);
+
+        let start = Instant::now();
+        let outcome = rt
+            .run_event_detailed(&json!({ "type": "fan_out", "seed": "2" }))
+            .await
+            .unwrap();
+        assert!(
+            start.elapsed() < Duration::from_millis(190),
+            "steps ran serially"
+        );
+        assert_eq!(outcome.vars_final["a"], "1");
+        assert_eq!(outcome.vars_final["b"], "2");
+        // The container comes first, then each step's records
+        let actions: Vec<_> = rt.last_action_results().iter().map(|r| r.action).collect();
+        assert_eq!(
+            actions,
+            [
+                "parallel", "sequence", "sleep_ms", "set_var", "sequence", "sleep_ms", "set_var"
+            ]
+        );
+
+        // Input actions never reach the (shared) input device
+        let err = rt
+            .run_workflow_by_name("typing", HashMap::new())
+            .await
+            .unwrap_err();
+        assert!(
+            format!("{err:#}").contains("cannot run inside `parallel`"),
+            "{err:#}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_while_loops_until_var_changes_and_is_bounded() {
+        let cfg: Config = serde_json::from_value(json!({
+            "workflows": {
+                "count_up": [
+                    { "type": "set_var", "name": "n", "value": "" },
+                    { "type": "while", "when": "{{n}}", "equals": "{{n}}", "max_iterations": 5,
+                      "body": { "type": "set_var", "name": "n", "value": "{{n}}x" } }
+                ],
+                "wait_done": [
+                    { "type": "set_var", "name": "status", "value": "pending" },
+                    { "type": "while", "when": "{{status}}", "equals": "pending",
+                      "body": { "type": "repeat", "count": 1, "body":
+                        { "type": "set_var", "name": "status", "value": "done" } } }
+                ]
+            }
+        }))
+        .unwrap();
+        let mut rt = Runtime::new(cfg, true);
+
+        rt.run_workflow_by_name("wait_done", HashMap::new())
+            .await
+            .unwrap();
+        let results = rt.last_action_results();
+        assert_eq!(results[1].result, ActionResult::Looped { iterations: 1 });
+
+        // Always-true condition hits the bound and fails the workflow
+        let err = rt
+            .run_workflow_by_name("count_up", HashMap::new())
+            .await
+            .unwrap_err();
+        assert!(format!("{err:#}").contains("after 5 iterations"), "{err:#}");
+    }
+
+    #[tokio::test]
+    async fn test_retry_backs_off_until_body_succeeds() {
+        use crate::testing::{CapturedAction, capturing_runtime};
+
+        let cfg: Config = serde_json::from_value(json!({
+            "workflows": {
+                "flaky": [{
+                    "type": "retry",
+                    "attempts": 4,
+                    "delay_ms": 20,
+                    "backoff": 2.0,
+                    "body": {
+                        "type": "conditional", "when": "{{attempt}}", "op": "lt", "equals": "3",
+                        "then": { "type": "ref", "name": "missing" },
+                        "else": { "type": "set_var", "name": "done_on", "value": "{{attempt}}" }
+                    }
+                }],
+                "broken": [{
+                    "type": "retry", "attempts": 2, "delay_ms": "5ms",
+                    "body": { "type": "ref", "name": "missing" }
+                }]
+            },
+            "events": { "flaky": { "workflow": "flaky" } }
+        }))
+        .unwrap();
+        let (mut rt, capture) = capturing_runtime(cfg);
+
+        let outcome = rt
+            .run_event_detailed(&json!({ "type": "flaky" }))
+            .await
+            .unwrap();
+        assert_eq!(outcome.vars_final["done_on"], "3");
+        assert!(!outcome.vars_final.contains_key("attempt"));
+        assert_eq!(
+            rt.last_action_results()[0].result,
+            ActionResult::Retried { attempts: 3 }
+        );
+        assert_eq!(
+            capture.take(),
+            [
+                CapturedAction::Sleep { ms: 20 },
+                CapturedAction::Sleep { ms: 40 }
+            ]
+        );
+
+        let err = rt
+            .run_workflow_by_name("broken", HashMap::new())
+            .await
+            .unwrap_err();
+        let msg = format!("{err:#}");
+        assert!(msg.contains("retry failed after 2 attempts"), "{msg}");
+        assert!(msg.contains("'missing' not found"), "{msg}");
+    }
+
+    #[tokio::test]
+    async fn test_try_runs_catch_with_error_and_recovers() {
+        let cfg: Config = serde_json::from_value(json!({
+            "workflows": {
+                "recover": [{
+                    "type": "try",
+                    "body": { "type": "sequence", "steps": [
+                        { "type": "set_var", "name": "stage", "value": "body" },
+                        { "type": "ref", "name": "missing" }
+                    ]},
+                    "catch": { "type": "set_var", "name": "caught", "value": "{{stage}}: {{error}}" }
+                }, { "type": "set_var", "name": "after", "value": "yes" }],
+                "rethrow": [{
+                    "type": "try",
+                    "body": { "type": "ref", "name": "missing" },
+                    "catch": { "type": "ref", "name": "also_missing" }
+                }]
+            },
+            "events": { "recover": { "workflow": "recover" } }
+        }))
+        .unwrap();
+        let mut rt = Runtime::new(cfg, true);
+
+        let outcome = rt
+            .run_event_detailed(&json!({ "type": "recover" }))
+            .await
+            .unwrap();
+        assert_eq!(
+            outcome.vars_final["caught"],
+            "body: Referenced action 'missing' not found"
+        );
+        assert_eq!(outcome.vars_final["after"], "yes");
+        assert_eq!(
+            rt.last_action_results()[0].result,
+            ActionResult::Caught {
+                error: "Referenced action 'missing' not found".into()
+            }
+        );
+
+        // A failing catch fails the workflow with its own error
+        let err = rt
+            .run_workflow_by_name("rethrow", HashMap::new())
+            .await
+            .unwrap_err();
+        assert!(format!("{err:#}").contains("also_missing"), "{err:#}");
+    }
+
+    #[test]
+    fn test_compare_numeric_and_string_fallback() {
+        assert!(compare(CompareOp::Lt, "2", "10").unwrap());
+        assert!(compare(CompareOp::Ge, " 3 ", "3.0").unwrap());
+        assert!(compare(CompareOp::Eq, "1.0", "1").unwrap());
+        assert!(compare(CompareOp::Ne, "buy", "sell").unwrap());
+        assert!(compare(CompareOp::Eq, "buy", "buy").unwrap());
+        assert!(compare(CompareOp::Gt, "abc", "1").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_conditional_op_branches_on_retries() {
+        let cfg: Config = serde_json::from_value(json!({
+            "workflows": {
+                "retry": [{
+                    "type": "conditional", "when": "{{retries}}", "op": "lt", "equals": "3",
+                    "then": { "type": "set_var", "name": "again", "value": "yes" },
+                    "else": { "type": "set_var", "name": "again", "value": "no" }
+                }]
+            }
+        }))
+        .unwrap();
+        let mut rt = Runtime::new(cfg, true);
+        for (retries, expected) in [("2", Branch::Then), ("10", Branch::Else)] {
+            let vars = HashMap::from([("retries".to_string(), retries.to_string())]);
+            rt.run_workflow_by_name("retry", vars).await.unwrap();
+            assert_eq!(
+                rt.last_action_results()[0].result,
+                ActionResult::Branch(expected)
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_window_times_out_without_match() {
+        let cfg: Config = serde_json::from_value(json!({
+            "workflows": { "wait": [{
+                "type": "wait_for_window", "title_contains": "{{app}}",
+                "timeout_ms": "60ms", "poll_ms": 10
+            }]}
+        }))
+        .unwrap();
+        let vars = HashMap::from([("app".to_string(), "no-such-window-3f9c".to_string())]);
+
+        let mut dry = Runtime::new(cfg.clone(), true);
+        dry.run_workflow_by_name("wait", vars.clone())
+            .await
+            .unwrap();
+
+        let mut rt = Runtime::new(cfg, false);
+        let start = Instant::now();
+        let err = rt.run_workflow_by_name("wait", vars).await.unwrap_err();
+        assert!(
+            format!("{err:#}").contains("'no-such-window-3f9c' appeared within 60 ms"),
+            "{err:#}"
+        );
+        assert!(start.elapsed() >= Duration::from_millis(40));
+    }
+
+    #[tokio::test]
+    async fn test_pixel_color_check_takes_then_branch_in_dry_run() {
+        let cfg: Config = serde_json::from_value(json!({
+            "workflows": { "probe": [{
+                "type": "pixel_color_check", "x": 5, "y": 5, "expected": "{{color}}",
+                "tolerance": 8,
+                "then": { "type": "set_var", "name": "seen", "value": "then" },
+                "else": { "type": "set_var", "name": "seen", "value": "else" }
+            }]},
+            "events": { "probe": { "workflow": "probe", "vars_map": { "color": "color" } } }
+        }))
+        .unwrap();
+        let mut rt = Runtime::new(cfg, true);
+        let outcome = rt
+            .run_event_detailed(&json!({ "type": "probe", "color": "#00ff00" }))
+            .await
+            .unwrap();
+        assert_eq!(outcome.vars_final["seen"], "then");
+        assert_eq!(
+            rt.last_action_results()[0].result,
+            ActionResult::Branch(Branch::Then)
+        );
+
+        // A templated color is only checked when it is rendered
+        let err = rt
+            .run_event_detailed(&json!({ "type": "probe", "color": "green" }))
+            .await
+            .unwrap_err();
+        assert!(
+            format!("{err:#}").contains("Invalid color 'green'"),
+            "{err:#}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_abort_stops_workflow_without_error_or_catch() {
+        let cfg: Config = serde_json::from_value(json!({
+            "workflows": { "guarded": [
+                { "type": "repeat", "count": 5, "body": { "type": "sequence", "steps": [
+                    { "type": "set_var", "name": "last", "value": "{{repeat_index}}" },
+                    { "type": "conditional", "when": "{{repeat_index}}", "equals": "2",
+                      "then": { "type": "try",
+                        "body": { "type": "abort", "message": "stopping at {{repeat_index}}" },
+                        "catch": { "type": "set_var", "name": "caught", "value": "yes" } } },
+                    { "type": "set_var", "name": "after", "value": "{{repeat_index}}" }
+                ]}},
+                { "type": "set_var", "name": "tail", "value": "ran" }
+            ]},
+            "events": { "guarded": { "workflow": "guarded" } }
+        }))
+        .unwrap();
+        let mut rt = Runtime::new(cfg, true);
+        let outcome = rt
+            .run_event_detailed(&json!({ "type": "guarded" }))
+            .await
+            .unwrap();
+
+        assert!(outcome.aborted);
+        assert_eq!(outcome.steps_executed, 1);
+        assert_eq!(outcome.vars_final["last"], "2");
+        assert_eq!(outcome.vars_final["after"], "1");
+        assert!(!outcome.vars_final.contains_key("caught"));
+        assert!(!outcome.vars_final.contains_key("tail"));
+        let results = rt.last_action_results();
+        assert_eq!(results[0].result, ActionResult::Looped { iterations: 3 });
+        assert!(results.iter().any(|r| r.result
+            == ActionResult::Aborted {
+                message: Some("stopping at 2".into())
+            }));
+
+        // The next run starts fresh
+        let outcome = rt
+            .run_event_detailed(&json!({ "type": "guarded" }))
+            .await
+            .unwrap();
+        assert!(outcome.aborted);
+        assert_eq!(outcome.vars_final["last"], "2");
+    }
+
+    #[tokio::test]
+    async fn test_default_workflow_handles_unbound_event_types() {
+        let mut cfg: Config = serde_json::from_value(json!({
+            "workflows": {
+                "known": [ { "type": "set_var", "name": "via", "value": "{{who}}" } ],
+                "catch_all": [ { "type": "set_var", "name": "via", "value": "{{$type}}/{{$payload.id}}" } ]
+            },
+            "events": { "known": { "workflow": "known", "vars_map": { "who": "payload.id" } } },
+            "default_workflow": "catch_all"
+        }))
+        .unwrap();
+        let mut rt = Runtime::new(cfg.clone(), true);
+
+        let known = json!({ "type": "known", "payload": { "id": 7 } });
+        let outcome = rt.run_event_detailed(&known).await.unwrap();
+        assert_eq!(outcome.workflow, "known");
+        assert_eq!(outcome.vars_final["via"], "7");
+
+        let other = json!({ "type": "other", "payload": { "id": 8 } });
+        let outcome = rt.run_event_detailed(&other).await.unwrap();
+        assert_eq!(outcome.workflow, "catch_all");
+        assert_eq!(outcome.vars_final["via"], "other/8");
+
+        cfg.default_workflow = None;
+        let err = Runtime::new(cfg, true)
+            .run_event_detailed(&other)
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("No event binding found for type 'other'")
+        );
+    }
+
+    /// Log sink for `tracing_subscriber` shared with the test.
+    #[derive(Clone, Default)]
+    struct LogBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl std::io::Write for LogBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_comment_and_noop_do_nothing() {
+        let cfg: Config = serde_json::from_value(json!({
+            "workflows": { "annotated": [
+                { "type": "comment", "text": "Step 1: greet {{who}}" },
+                { "type": "noop" },
+                { "type": "set_var", "name": "done", "value": "yes" }
+            ]},
+            "events": { "annotated": { "workflow": "annotated" } }
+        }))
+        .unwrap();
+        assert_eq!(
+            cfg.workflows["annotated"][1],
+            ActionDef::Comment {
+                text: String::new()
+            }
+        );
+        let mut rt = Runtime::new(cfg, true);
+        let outcome = rt
+            .run_event_detailed(&json!({ "type": "annotated" }))
+            .await
+            .unwrap();
+        assert_eq!(outcome.steps_executed, 3);
+        assert_eq!(outcome.vars_final.len(), 1);
+        let results = rt.last_action_results();
+        assert_eq!(results[0].action, "comment");
+        assert_eq!(results[1].result, ActionResult::Done);
+    }
+
+    #[tokio::test]
+    async fn test_emit_event_interpolates_and_counts_hops() {
+        let cfg: Config = serde_json::from_value(json!({
+            "workflows": { "relay": [
+                { "type": "set_var", "name": "status", "value": "done" },
+                { "type": "emit_event", "event": {
+                    "type": "order.{{status}}", "ids": ["{{$id}}", 2], "meta": { "by": "{{@app}}" } } }
+            ]},
+            "events": { "order": { "workflow": "relay" } },
+            "globals": { "app": "notabot" },
+            "max_event_hops": 2
+        }))
+        .unwrap();
+        let mut rt = Runtime::new(cfg, true);
+        let mut emitted = rt.take_emitted_event_receiver().unwrap();
+
+        rt.run_event(&json!({ "type": "order", "id": "A1" }))
+            .await
+            .unwrap();
+        assert_eq!(
+            emitted.try_recv().unwrap(),
+            json!({ "type": "order.done", "ids": ["A1", 2], "meta": { "by": "notabot" }, "_hops": 1 })
+        );
+        assert_eq!(
+            rt.last_action_results()[1].result,
+            ActionResult::EventEmitted {
+                event_type: Some("order.done".into()),
+                hops: 1
+            }
+        );
+
+        let err = rt
+            .run_event(&json!({ "type": "order", "id": "A1", "_hops": 2 }))
+            .await
+            .unwrap_err();
+        assert!(format!("{err:#}").contains("longer than max_event_hops (2)"));
+        assert!(emitted.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_strip_event_hops_from_source_events_and_batches() {
+        let rt = Runtime::new(Config::default(), true);
+        let mut event = json!({ "type": "order", "_hops": 99 });
+        rt.strip_event_hops(&mut event);
+        assert_eq!(event, json!({ "type": "order" }));
+
+        let mut batch =
+            json!({ "batch": [{ "type": "a", "_hops": 7 }, { "type": "b" }], "_hops": 1 });
+        rt.strip_event_hops(&mut batch);
+        assert_eq!(
+            batch,
+            json!({ "batch": [{ "type": "a" }, { "type": "b" }] })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_trace_records_executed_actions_across_runs() {
+        let cfg: Config = serde_json::from_value(json!({
+            "workflows": { "click": [
+                { "type": "mouse_move", "x": 5, "y": 6 },
+                { "type": "repeat", "count": 2, "body": { "type": "mouse_click", "button": "left" } }
+            ]},
+            "events": { "click": { "workflow": "click" } }
+        }))
+        .unwrap();
+        let mut rt = Runtime::new(cfg, true);
+        rt.run_event(&json!({ "type": "click" })).await.unwrap();
+        assert!(rt.take_trace().is_empty());
+
+        rt.set_trace(true);
+        rt.run_event(&json!({ "type": "click" })).await.unwrap();
+        rt.run_event(&json!({ "type": "click" })).await.unwrap();
+        let trace = rt.take_trace();
+        let actions: Vec<&str> = trace.iter().map(|e| e.action.as_str()).collect();
+        assert_eq!(
+            actions,
+            ["mouse_move", "repeat", "mouse_click", "mouse_click"].repeat(2)
+        );
+        assert_eq!(trace[0].params["x"], 5);
+        assert_eq!(trace[0].params.get("type"), None);
+        assert!(humantime::parse_rfc3339(&trace[0].timestamp).is_ok());
+
+        // Entries round-trip through JSON, and taking the trace empties it
+        let json = serde_json::to_string(&trace).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Vec<TraceEntry>>(&json).unwrap(),
+            trace
+        );
+        assert!(rt.take_trace().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_json_logs_carry_workflow_and_step_fields() {
+        let logs = LogBuffer::default();
+        let writer = logs.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .json()
+            .with_writer(move || writer.clone())
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let cfg: Config = serde_json::from_value(json!({
+            "workflows": { "greet": [
+                { "type": "sleep_ms", "ms": 0 },
+                { "type": "log", "level": "info", "message": "hello" }
+            ]},
+            "events": { "greet": { "workflow": "greet" } }
+        }))
+        .unwrap();
+        let mut rt = Runtime::new(cfg, true);
+        rt.run_event(&json!({ "type": "greet" })).await.unwrap();
+
+        let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        let line: Value = logs
+            .lines()
+            .map(|l| serde_json::from_str::<Value>(l).unwrap())
+            .find(|l| l["fields"]["message"] == "hello")
+            .unwrap_or_else(|| panic!("no 'hello' line in {logs}"));
+        assert_eq!(line["span"]["step_index"], 1);
+        assert_eq!(line["spans"][0]["workflow_name"], "greet");
+    }
+
+    #[tokio::test]
+    async fn test_wildcard_bindings_prefer_exact_then_longest_prefix() {
+        let bind = |workflow: &str| json!({ "workflow": workflow });
+        let cfg: Config = serde_json::from_value(json!({
+            "workflows": { "exact": [], "order_any": [], "o_any": [], "any_cancelled": [] },
+            "events": {
+                "order.created": bind("exact"),
+                "order.*": bind("order_any"),
+                "o*": bind("o_any"),
+                "*.cancelled": bind("any_cancelled")
+            }
+        }))
+        .unwrap();
+        let mut rt = Runtime::new(cfg, true);
+        for (event_type, workflow) in [
+            ("order.created", "exact"),
+            ("order.cancelled", "order_any"),
+            ("offer.cancelled", "o_any"),
+            ("trade.cancelled", "any_cancelled"),
+        ] {
+            let outcome = rt
+                .run_event_detailed(&json!({ "type": event_type }))
+                .await
+                .unwrap();
+            assert_eq!(outcome.workflow, workflow, "{event_type}");
+        }
+        assert!(
+            rt.run_event_detailed(&json!({ "type": "trade" }))
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_event_schema_rejects_malformed_events() {
+        let cfg: Config = serde_json::from_value(json!({
+            "workflows": { "order": [
+                { "type": "set_var", "name": "placed", "value": "{{$qty}} x {{$sku}}" }
+            ]},
+            "events": { "order": { "workflow": "order", "schema": {
+                "type": "object",
+                "required": ["sku", "qty"],
+                "properties": { "sku": { "type": "string" }, "qty": { "type": "integer", "minimum": 1 } }
+            }}}
+        }))
+        .unwrap();
+        let mut rt = Runtime::new(cfg, true);
+
+        let outcome = rt
+            .run_event_detailed(&json!({ "type": "order", "sku": "A-1", "qty": 2 }))
+            .await
+            .unwrap();
+        assert_eq!(outcome.vars_final["placed"], "2 x A-1");
+
+        let err = rt
+            .run_event_detailed(&json!({ "type": "order", "qty": 0 }))
+            .await
+            .unwrap_err();
+        let msg = format!("{err:#}");
+        assert!(
+            msg.starts_with("Rejected event of type 'order': Event does not match its schema"),
+            "{msg}"
+        );
+        assert!(msg.contains("/: \"sku\" is a required property"), "{msg}");
+        assert!(
+            msg.contains("/qty: 0 is less than the minimum of 1"),
+            "{msg}"
+        );
+        assert_eq!(rt.event_schemas.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_call_workflow_runs_steps_in_own_scope() {
+        let cfg: Config = serde_json::from_value(json!({
+            "workflows": {
+                "greet": [
+                    { "type": "set_var", "name": "greeting", "value": "hi {{who}}{{outer:?}}" },
+                    { "type": "abort" },
+                    { "type": "set_var", "name": "unreached", "value": "x" }
+                ],
+                "main": [
+                    { "type": "set_var", "name": "outer", "value": "!" },
+                    { "type": "call_workflow", "name": "greet", "vars": { "who": "{{$user}}" } },
+                    { "type": "call_workflow", "name": "greet", "inherit_vars": true,
+                      "vars": { "who": "again" } },
+                    { "type": "set_var", "name": "tail", "value": "ran" }
+                ]
+            },
+            "events": { "main": { "workflow": "main" } }
+        }))
+        .unwrap();
+        let mut rt = Runtime::new(cfg, true);
+        let outcome = rt
+            .run_event_detailed(&json!({ "type": "main", "user": "ann" }))
+            .await
+            .unwrap();
+
+        // The abort only ended the called workflow, and nothing leaked back
+        assert!(!outcome.aborted);
+        assert_eq!(outcome.steps_executed, 4);
+        assert_eq!(outcome.vars_final["tail"], "ran");
+        assert!(!outcome.vars_final.contains_key("greeting"));
+        let greetings: Vec<String> = rt
+            .last_action_results()
+            .iter()
+            .filter_map(|r| match &r.result {
+                ActionResult::VarSet { name, value } if name == "greeting" => Some(value.clone()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(greetings, ["hi ann?", "hi again!"]);
+    }
+
+    #[tokio::test]
+    async fn test_get_cursor_stores_dry_run_position() {
+        let cfg: Config = serde_json::from_value(json!({
+            "workflows": { "remember": [
+                { "type": "get_cursor", "x_var": "cx", "y_var": "cy" },
+                { "type": "set_var", "name": "back_to", "value": "{{cx}},{{cy}}" }
+            ]},
+            "events": { "remember": { "workflow": "remember" } }
+        }))
+        .unwrap();
+        let mut rt = Runtime::new(cfg, true);
+        let outcome = rt
+            .run_event_detailed(&json!({ "type": "remember" }))
+            .await
+            .unwrap();
+
+        assert_eq!(outcome.vars_final["back_to"], "0,0");
+        assert_eq!(
+            rt.last_action_results()[0].result,
+            ActionResult::CursorLocated { x: 0, y: 0 }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_screen_size_populates_vars_in_dry_run() {
+        let cfg: Config = serde_json::from_value(json!({
+            "workflows": {
+                "center": [
+                    { "type": "get_screen_size", "width_var": "w", "height_var": "h" },
+                    { "type": "math", "into_var": "cx", "lhs": "{{w}}", "op": "/", "rhs": "2" }
+                ],
+                "second": [
+                    { "type": "get_screen_size", "width_var": "w", "height_var": "h", "monitor": 1 }
+                ]
+            },
+            "events": { "center": { "workflow": "center" } }
+        }))
+        .unwrap();
+        let mut rt = Runtime::new(cfg, true);
+        let outcome = rt
+            .run_event_detailed(&json!({ "type": "center" }))
+            .await
+            .unwrap();
+
+        assert_eq!(outcome.vars_final["w"], "1920");
+        assert_eq!(outcome.vars_final["h"], "1080");
+        assert_eq!(outcome.vars_final["cx"], "960");
+        assert!(
+            rt.run_workflow_by_name("second", HashMap::new())
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_set_var_from_json_reads_event_fields() {
+        let cfg: Config = serde_json::from_value(json!({
+            "workflows": { "extract": [
+                { "type": "set_var", "name": "field", "value": "side" },
+                { "type": "set_var_from_json", "name": "side", "path": "order.{{field}}" },
+                { "type": "set_var_from_json", "name": "order", "path": "order" },
+                { "type": "set_var_from_json", "name": "qty", "path": "order.qty" },
+                { "type": "set_var_from_json", "name": "gone", "path": "order.missing" }
+            ]},
+            "events": { "extract": { "workflow": "extract" } }
+        }))
+        .unwrap();
+        let mut rt = Runtime::new(cfg, true);
+        let outcome = rt
+            .run_event_detailed(&json!({
+                "type": "extract",
+                "order": { "side": "buy", "qty": 3 }
+            }))
+            .await
+            .unwrap();
+
+        assert_eq!(outcome.vars_final["side"], "buy");
+        assert_eq!(outcome.vars_final["order"], r#"{"qty":3,"side":"buy"}"#);
+        assert_eq!(outcome.vars_final["qty"], "3");
+        assert_eq!(outcome.vars_final["gone"], "");
+        assert_eq!(
+            rt.last_action_results()[1].result,
+            ActionResult::VarSet {
+                name: "side".into(),
+                value: "buy".into()
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_regex_extract_selects_groups_and_clears_on_no_match() {
+        let cfg: Config = serde_json::from_value(json!({
+            "workflows": { "parse": [
+                { "type": "regex_extract", "input": "{{note}}", "pattern": "order #(\\d+)-(\\w+)",
+                  "into_var": "id" },
+                { "type": "regex_extract", "input": "{{note}}", "pattern": "order #(\\d+)-(\\w+)",
+                  "into_var": "region", "group": 2 },
+                { "type": "regex_extract", "input": "{{note}}", "pattern": "#\\d+",
+                  "into_var": "whole", "group": 0 },
+                { "type": "regex_extract", "input": "{{note}}", "pattern": "refund (\\d+)",
+                  "into_var": "refund" }
+            ], "bad_group": [
+                { "type": "regex_extract", "input": "x", "pattern": "{{p}}", "into_var": "v", "group": 3 }
+            ]},
+            "events": { "parse": { "workflow": "parse", "vars_map": { "note": "note" } } }
+        }))
+        .unwrap();
+        let mut rt = Runtime::new(cfg, true);
+        let outcome = rt
+            .run_event_detailed(&json!({ "type": "parse", "note": "Re: order #4521-emea shipped" }))
+            .await
+            .unwrap();
+        assert_eq!(outcome.vars_final["id"], "4521");
+        assert_eq!(outcome.vars_final["region"], "emea");
+        assert_eq!(outcome.vars_final["whole"], "#4521");
+        assert_eq!(outcome.vars_final["refund"], "");
+        // Same pattern source, compiled once
+        assert_eq!(rt.regexes.lock().unwrap().len(), 3);
+
+        let vars = HashMap::from([("p".to_string(), "(a)".to_string())]);
+        let err = rt
+            .run_workflow_by_name("bad_group", vars)
+            .await
+            .unwrap_err();
+        assert!(format!("{err:#}").contains("no capture group 3"), "{err:#}");
+    }
+
+    #[test]
+    fn test_arithmetic_and_number_formatting() {
+        assert_eq!(
+            format_number(arithmetic(MathOp::Add, "2", " 1 ").unwrap()),
+            "3"
+        );
+        assert_eq!(
+            format_number(arithmetic(MathOp::Sub, "1", "1.5").unwrap()),
+            "-0.5"
+        );
+        assert_eq!(
+            format_number(arithmetic(MathOp::Mul, "2.5", "4").unwrap()),
+            "10"
+        );
+        assert_eq!(
+            format_number(arithmetic(MathOp::Div, "7", "2").unwrap()),
+            "3.5"
+        );
+        assert_eq!(
+            format_number(arithmetic(MathOp::Rem, "-7", "3").unwrap()),
+            "-1"
+        );
+        assert_eq!(format_number(-0.0), "0");
+        assert_eq!(format_number(1e20), "100000000000000000000");
+
+        let err = arithmetic(MathOp::Div, "1", "0").unwrap_err();
+        assert!(err.to_string().contains("division by zero"), "{err:#}");
+        assert!(arithmetic(MathOp::Rem, "1", "0.0").is_err());
+        let err = arithmetic(MathOp::Add, "{{x}}", "1").unwrap_err();
+        assert!(err.to_string().contains("not a number"), "{err:#}");
+    }
+
+    #[tokio::test]
+    async fn test_math_increments_loop_counter() {
+        let cfg: Config = serde_json::from_value(json!({
+            "workflows": { "count": [
+                { "type": "set_var", "name": "x", "value": "0" },
+                { "type": "repeat", "count": 4, "body":
+                    { "type": "math", "into_var": "x", "lhs": "{{x}}", "op": "+", "rhs": "1" } },
+                { "type": "math", "into_var": "half", "lhs": "{{x}}", "op": "/", "rhs": "8" }
+            ]},
+            "events": { "count": { "workflow": "count" } }
+        }))
+        .unwrap();
+        let mut rt = Runtime::new(cfg, true);
+        let outcome = rt
+            .run_event_detailed(&json!({ "type": "count" }))
+            .await
+            .unwrap();
+        assert_eq!(outcome.vars_final["x"], "4");
+        assert_eq!(outcome.vars_final["half"], "0.5");
+
+        let bad_op = serde_json::from_value::<ActionDef>(
+            json!({ "type": "math", "into_var": "x", "lhs": "1", "op": "^", "rhs": "2" }),
+        );
+        assert!(bad_op.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_conditional_compares_typed_numbers_numerically() {
+        let cfg: Config = serde_json::from_value(json!({
+            "globals": { "port": 8080, "version": "1.0", "net": { "ready": true } },
+            "workflows": { "check": [
+                { "type": "conditional", "when": "{{@port}}", "equals": "8080.0",
+                  "then": { "type": "log", "level": "info", "message": "typed global" } },
+                { "type": "conditional", "when": "port {{@port}}", "equals": "port 8080.0",
+                  "then": { "type": "log", "level": "info", "message": "text" } },
+                { "type": "conditional", "when": "{{@version}}", "equals": "1",
+                  "then": { "type": "log", "level": "info", "message": "string global" } },
+                { "type": "conditional", "when_path": "count", "equals": "{{@port}}",
+                  "then": { "type": "log", "level": "info", "message": "path vs global" } },
+                { "type": "conditional", "when": "{{$count}}", "equals": "8.08e3",
+                  "then": { "type": "log", "level": "info", "message": "event token" } },
+                { "type": "conditional", "when": "{{@net.ready}}", "equals": "true",
+                  "then": { "type": "log", "level": "info", "message": "bool" } }
+            ]},
+            "events": { "check": { "workflow": "check" } }
+        }))
+        .unwrap();
+        let mut rt = Runtime::new(cfg, true);
+        rt.run_event(&json!({ "type": "check", "count": 8080 }))
+            .await
+            .unwrap();
+
+        let branches: Vec<_> = rt
+            .last_action_results()
+            .iter()
+            .filter(|r| r.depth == 0)
+            .map(|r| r.result.clone())
+            .collect();
+        let then = ActionResult::Branch(Branch::Then);
+        let neither = ActionResult::Branch(Branch::Neither);
+        assert_eq!(
+            branches,
+            [
+                then.clone(),
+                neither.clone(),
+                neither,
+                then.clone(),
+                then.clone(),
+                then
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_run_command_captures_output_and_checks_exit_code() {
+        let cfg: Config = serde_json::from_value(json!({
+            "workflows": {
+                "echo": [{ "type": "run_command", "program": "echo",
+                           "args": ["hello", "{{who}}"], "capture_output_var": "out" }],
+                "fail": [{ "type": "run_command", "program": "false" }],
+                "fail_ok": [{ "type": "run_command", "program": "false", "ignore_exit_code": true }]
+            }
+        }))
+        .unwrap();
+        let mut rt = Runtime::new(cfg, false);
+
+        let vars = HashMap::from([("who".to_string(), "world".to_string())]);
+        rt.run_workflow_by_name("echo", vars).await.unwrap();
+        assert_eq!(
+            rt.last_action_results()[0].result,
+            ActionResult::VarSet {
+                name: "out".into(),
+                value: "hello world".into(),
+            }
+        );
+
+        assert!(
+            rt.run_workflow_by_name("fail", HashMap::new())
+                .await
+                .is_err()
+        );
+        rt.run_workflow_by_name("fail_ok", HashMap::new())
+            .await
+            .unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_run_command_times_out_without_blocking_other_tasks() {
+        let cfg: Config = serde_json::from_value(json!({
+            "workflows": {
+                "hang": [{ "type": "run_command", "program": "sleep", "args": ["5"],
+                           "timeout_ms": 100 }]
+            }
+        }))
+        .unwrap();
+        let mut rt = Runtime::new(cfg, false);
+
+        let ticker = tokio::spawn(async {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            Instant::now()
+        });
+        let start = Instant::now();
+        let err = rt
+            .run_workflow_by_name("hang", HashMap::new())
+            .await
+            .unwrap_err();
+        assert!(
+            format!("{err:#}").contains("did not exit within"),
+            "{err:#}"
+        );
+        assert!(start.elapsed() < Duration::from_secs(2));
+        // The single-threaded test runtime kept running other tasks meanwhile
+        assert!(ticker.await.unwrap() - start < Duration::from_millis(90));
+    }
+
+    #[tokio::test]
+    async fn test_clipboard_actions_log_in_dry_run() {
+        use crate::testing::{CapturedAction, capturing_runtime};
+
+        let cfg: Config = serde_json::from_value(json!({
+            "workflows": {
+                "paste": [
+                    { "type": "clipboard_set", "text": "Hello {{who}}" },
+                    { "type": "clipboard_get", "into_var": "copied" }
+                ]
+            }
+        }))
+        .unwrap();
+        let (mut rt, capture) = capturing_runtime(cfg);
+        let vars = HashMap::from([("who".to_string(), "Ada".to_string())]);
+        rt.run_workflow_by_name("paste", vars).await.unwrap();
+        assert_eq!(
+            capture.take(),
+            vec![
+                CapturedAction::ClipboardSet("Hello Ada".into()),
+                CapturedAction::ClipboardGet,
+            ]
+        );
+    }
+
+    #[cfg(not(feature = "clipboard"))]
+    #[tokio::test]
+    async fn test_clipboard_actions_need_feature() {
+        let cfg: Config = serde_json::from_value(json!({
+            "workflows": { "set": [{ "type": "clipboard_set", "text": "x" }] }
+        }))
+        .unwrap();
+        let mut rt = Runtime::new(cfg, false);
+        let err = rt
+            .run_workflow_by_name("set", HashMap::new())
+            .await
+            .unwrap_err();
+        assert!(
+            format!("{err:#}").contains("`clipboard` feature"),
+            "{err:#}"
+        );
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_sleep_yields_to_other_tasks() {
+        let cfg: Config = serde_json::from_value(json!({
+            "workflows": { "nap": [{ "type": "sleep_ms", "ms": 50 }] }
+        }))
+        .unwrap();
+        let mut rt = Runtime::new(cfg, false);
+        let ticker = tokio::spawn(async {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            Instant::now()
+        });
+        let start = Instant::now();
+        rt.run_workflow_by_name("nap", HashMap::new())
+            .await
+            .unwrap();
+        let finished = Instant::now();
+        // On a single-threaded runtime the ticker only completes if the sleep yielded
+        let ticked = ticker.await.unwrap();
+        assert!(ticked < finished, "sleep_ms blocked the worker thread");
+        assert!(finished - start >= Duration::from_millis(50));
+    }
+
+    #[tokio::test]
+    async fn test_action_results_keep_only_the_latest_records() {
+        let count = MAX_ACTION_RECORDS * 2 + 5;
+        let cfg: Config = serde_json::from_value(json!({
+            "workflows": {
+                "long": [
+                    { "type": "repeat", "count": count, "body":
+                        { "type": "set_var", "name": "i", "value": "{{repeat_index}}" } }
+                ]
+            }
+        }))
+        .unwrap();
+        let mut rt = Runtime::new(cfg, true);
+        rt.run_workflow_by_name("long", HashMap::new())
+            .await
+            .unwrap();
+
+        let results = rt.last_action_results();
+        assert_eq!(results.len(), MAX_ACTION_RECORDS);
+        assert!(rt.action_results.len() < 2 * MAX_ACTION_RECORDS);
+        assert_eq!(
+            results.last().unwrap().result,
+            ActionResult::VarSet {
+                name: "i".into(),
+                value: (count - 1).to_string(),
+            }
+        );
+        assert!(results.iter().all(|r| r.action == "set_var"));
+    }
+
+    #[tokio::test]
+    async fn test_workflow_timeout_names_workflow_and_step() {
+        let cfg: Config = serde_json::from_value(json!({
+            "workflows": {
+                "stuck": [
+                    { "type": "log", "level": "info", "message": "starting" },
+                    { "type": "sleep_ms", "ms": "10s" },
+                    { "type": "log", "level": "info", "message": "never reached" }
+                ],
+                "quick": [{ "type": "sleep_ms", "ms": 10 }]
+            },
+            "workflow_meta": {
+                "stuck": { "timeout_ms": 50 },
+                "quick": { "timeout_ms": "1s" }
+            }
+        }))
+        .unwrap();
+        let mut rt = Runtime::new(cfg, false);
+
+        let start = Instant::now();
+        let err = rt
+            .run_workflow_by_name("stuck", HashMap::new())
+            .await
+            .unwrap_err();
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert_eq!(
+            err.to_string(),
+            "Workflow 'stuck' timed out after 50 ms at step 1"
+        );
+        let results = rt.last_action_results();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].result, ActionResult::Done);
+        assert_eq!(results[1].result, ActionResult::Cancelled);
+
+        rt.run_workflow_by_name("quick", HashMap::new())
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_replace_config_applies_to_next_event() {
+        let load = |msg: &str| -> Config {
+            serde_json::from_value(json!({
+                "workflows": { "wf": [{ "type": "set_var", "name": "msg", "value": msg }] },
+                "events": { "e": { "workflow": "wf" } }
+            }))
+            .unwrap()
+        };
+        let mut rt = Runtime::new(load("old"), true);
+        let before = rt.run_event_detailed(&json!({"type": "e"})).await.unwrap();
+        assert_eq!(before.vars_final["msg"], "old");
+
+        rt.config_update_sender().send(load("new")).unwrap();
+        let mut updates = rt.take_config_update_receiver().unwrap();
+        assert!(rt.take_config_update_receiver().is_none());
+        rt.replace_config(updates.recv().await.unwrap());
+        let after = rt.run_event_detailed(&json!({"type": "e"})).await.unwrap();
+        assert_eq!(after.vars_final["msg"], "new");
+    }
+
+    #[tokio::test]
+    async fn test_dump_state_writes_vars_and_globals() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested/state.json");
+
+        let mut cfg = Config::default();
+        cfg.globals.insert("app".into(), json!("Notabot"));
+        cfg.workflows.insert(
+            "wf".into(),
+            vec![
+                ActionDef::SetVar {
+                    name: "order_id".into(),
+                    value: "A-42".into(),
+                },
+                ActionDef::DumpState {
+                    path: path.to_string_lossy().into_owned(),
+                },
+            ],
+        );
+        let mut rt = Runtime::new(cfg, false);
+        rt.run_workflow_by_name("wf", HashMap::new()).await.unwrap();
+
+        let written: Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(written["vars"]["order_id"], "A-42");
+        assert_eq!(written["globals"]["app"], "Notabot");
+    }
+
+    #[tokio::test]
+    async fn test_rate_limits_drop_excess_events_per_type() {
+        let mut cfg = Config::default();
+        cfg.workflows.insert("wf".into(), vec![]);
+        for ty in ["chatty", "quiet"] {
+            cfg.events.insert(
+                ty.into(),
+                EventBinding {
+                    workflow: "wf".into(),
+                    vars_map: HashMap::new(),
+                    schema: None,
+                },
+            );
+        }
+        cfg.rate_limits.insert("chatty".into(), 3);
+        let mut rt = Runtime::new(cfg, true);
+
+        let mut ran = HashMap::<&str, usize>::new();
+        for _ in 0..10 {
+            for ty in ["chatty", "quiet"] {
+                let outcome = rt.run_event_detailed(&json!({ "type": ty })).await.unwrap();
+                if !outcome.rate_limited {
+                    *ran.entry(ty).or_default() += 1;
+                }
+            }
+        }
+        assert_eq!(ran["chatty"], 3);
+        assert_eq!(ran["quiet"], 10);
+    }
+
+    #[test]
+    fn test_parallel_forks_share_rate_limit_buckets() {
+        let mut cfg = Config::default();
+        cfg.rate_limits.insert("chatty".into(), 2);
+        let rt = Runtime::new(cfg, true);
+        let fork = rt.fork();
+
+        assert!(fork.rate_limit_allows("chatty"));
+        assert!(rt.rate_limit_allows("chatty"));
+        assert!(!fork.rate_limit_allows("chatty"));
+        assert!(!rt.rate_limit_allows("chatty"));
+    }
+
+    fn inline_workflow_config(allow: bool) -> Config {
+        let mut cfg = Config {
+            allow_inline_workflow: allow,
+            ..Default::default()
+        };
+        cfg.workflows.insert(
+            "adhoc".into(),
+            vec![ActionDef::SetVar {
+                name: "seen".into(),
+                value: "{{text}}".into(),
+            }],
+        );
+        cfg.workflows.insert("bound".into(), vec![]);
+        cfg.events.insert(
+            "ping".into(),
+            EventBinding {
+                workflow: "bound".into(),
+                vars_map: HashMap::new(),
+                schema: None,
+            },
+        );
+        cfg
+    }
+
+    #[tokio::test]
+    async fn test_inline_workflow_runs_named_workflow_with_all_fields() {
+        let mut rt = Runtime::new(inline_workflow_config(true), true);
+        let outcome = rt
+            .run_event_detailed(&json!({"_workflow": "adhoc", "text": "hi", "n": 2}))
+            .await
+            .unwrap();
+        assert_eq!(outcome.workflow, "adhoc");
+        assert_eq!(
+            outcome.vars_final.get("seen").map(String::as_str),
+            Some("hi")
+        );
+        assert_eq!(outcome.vars_final.get("n").map(String::as_str), Some("2"));
+    }
+
+    #[tokio::test]
+    async fn test_inline_workflow_honors_tag_filter_and_rate_limit() {
+        let mut cfg = inline_workflow_config(true);
+        cfg.rate_limits.insert("ping".into(), 1);
+        let mut rt = Runtime::new(cfg, true);
+
+        let event = json!({"type": "ping", "_workflow": "adhoc", "text": "hi"});
+        let first = rt.run_event_detailed(&event).await.unwrap();
+        assert!(!first.rate_limited);
+        assert_eq!(first.vars_final["seen"], "hi");
+        let second = rt.run_event_detailed(&event).await.unwrap();
+        assert!(second.rate_limited);
+        assert_eq!(second.steps_executed, 0);
+
+        rt.set_tag_filter(Some(vec!["nightly".into()]));
+        let outcome = rt
+            .run_event_detailed(&json!({"_workflow": "adhoc", "text": "hi"}))
+            .await
+            .unwrap();
+        assert!(outcome.tag_filtered);
+        assert!(outcome.vars_final.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_inline_workflow_ignored_when_disabled() {
+        let mut rt = Runtime::new(inline_workflow_config(false), true);
+        let outcome = rt
+            .run_event_detailed(&json!({"type": "ping", "_workflow": "adhoc"}))
+            .await
+            .unwrap();
+        assert_eq!(outcome.workflow, "bound");
+        assert!(rt.run_event(&json!({"_workflow": "adhoc"})).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_schedule_workflow_runs_after_delay_with_vars() {
+        let mut cfg = Config::default();
+        cfg.workflows.insert(
+            "kickoff".into(),
+            vec![ActionDef::ScheduleWorkflow {
+                name: "later".into(),
+                delay_ms: 50,
+                vars: [("who".to_string(), "{{user}}".to_string())].into(),
+            }],
+        );
+        cfg.workflows.insert(
+            "later".into(),
+            vec![ActionDef::Log {
+                level: LogLevel::Info,
+                message: "Hello {{who}}".into(),
+            }],
+        );
+        let mut rt = Runtime::new(cfg, true);
+        let mut scheduled = rt.take_schedule_receiver().unwrap();
+
+        let started = std::time::Instant::now();
+        let vars = HashMap::from([("user".to_string(), "Alice".to_string())]);
+        rt.run_workflow_by_name("kickoff", vars).await.unwrap();
+        assert_eq!(rt.pending_schedules(), 1);
+
+        let run = scheduled.recv().await.unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(50));
+        assert_eq!(run.workflow, "later");
+        assert_eq!(run.vars.get("who").map(String::as_str), Some("Alice"));
+        assert_eq!(rt.pending_schedules(), 0);
+        rt.run_workflow_by_name(&run.workflow, run.vars)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_schedule_workflow_respects_pending_cap() {
+        let mut cfg = Config {
+            max_pending_schedules: Some(1),
+            ..Default::default()
+        };
+        let step = ActionDef::ScheduleWorkflow {
+            name: "later".into(),
+            delay_ms: 1_000,
+            vars: Default::default(),
+        };
+        cfg.workflows
+            .insert("kickoff".into(), vec![step.clone(), step]);
+        cfg.workflows.insert("later".into(), vec![]);
+        let mut rt = Runtime::new(cfg, true);
+
+        let err = rt
+            .run_workflow_by_name("kickoff", HashMap::new())
+            .await
+            .unwrap_err();
+        assert!(format!("{err:#}").contains("Too many pending"), "{err:#}");
+        assert_eq!(rt.pending_schedules(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_conditional_equal_branch() {
+        let mut cfg = Config::default();
+        cfg.workflows.insert(
+            "wf".into(),
+            vec![ActionDef::Conditional {
+                when: "{{x}}".into(),
+                when_path: None,
+                equals: "yes".into(),
+                equals_path: None,
+                op: None,
+                then: Box::new(ActionDef::Log {
+                    level: LogLevel::Info,
+                    message: "OK".into(),
+                }),
+                else_: None,
+            }],
+        );
+        let mut rt = Runtime::new(cfg, true);
+        let mut vars = HashMap::new();
+        vars.insert("x".into(), "yes".into());
+        rt.execute_workflow("wf", &Value::Null, vars).await.unwrap();
+    }
+
+    fn path_conditional(
+        when: &str,
+        when_path: Option<&str>,
+        equals: &str,
+        equals_path: Option<&str>,
+    ) -> Config {
+        let set = |value: &str| {
+            Box::new(ActionDef::SetVar {
+                name: "branch".into(),
+                value: value.into(),
+            })
+        };
+        let mut cfg = Config::default();
+        cfg.workflows.insert(
+            "wf".into(),
+            vec![ActionDef::Conditional {
+                when: when.into(),
+                when_path: when_path.map(Into::into),
+                equals: equals.into(),
+                equals_path: equals_path.map(Into::into),
+                op: None,
+                then: set("then"),
+                else_: Some(set("else")),
+            }],
+        );
+        cfg
+    }
+
+    async fn branch_taken(cfg: Config, event: Value) -> String {
+        let mut rt = Runtime::new(cfg, true);
+        let outcome = rt
+            .execute_workflow("wf", &event, HashMap::new())
+            .await
+            .unwrap();
+        outcome.vars_final["branch"].clone()
+    }
+
+    #[tokio::test]
+    async fn test_conditional_reports_branch_taken() {
+        let mut cfg = Config::default();
+        cfg.workflows.insert(
+            "check".into(),
+            vec![ActionDef::Conditional {
+                when: "{{side}}".into(),
+                when_path: None,
+                equals: "buy".into(),
+                equals_path: None,
+                op: None,
+                then: Box::new(ActionDef::SetVar {
+                    name: "branch".into(),
+                    value: "then".into(),
+                }),
+                else_: Some(Box::new(ActionDef::SleepMs { ms: 0 })),
+            }],
+        );
+        let mut rt = Runtime::new(cfg, true);
+
+        let vars = HashMap::from([("side".to_string(), "buy".to_string())]);
+        rt.run_workflow_by_name("check", vars).await.unwrap();
+        assert_eq!(
+            rt.last_action_results(),
+            &[
+                ActionRecord {
+                    action: "conditional",
+                    depth: 0,
+                    result: ActionResult::Branch(Branch::Then),
+                },
+                ActionRecord {
+                    action: "set_var",
+                    depth: 1,
+                    result: ActionResult::VarSet {
+                        name: "branch".into(),
+                        value: "then".into(),
+                    },
+                },
+            ]
+        );
+
+        let vars = HashMap::from([("side".to_string(), "sell".to_string())]);
+        rt.run_workflow_by_name("check", vars).await.unwrap();
+        let results = rt.last_action_results();
+        assert_eq!(results[0].result, ActionResult::Branch(Branch::Else));
+        assert_eq!(results[1].result, ActionResult::Slept { ms: 0 });
+        assert_eq!(results.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_conditional_compares_two_event_paths() {
+        let cfg = path_conditional("", Some("order.side"), "", Some("expected"));
+        assert_eq!(
+            branch_taken(
+                cfg.clone(),
+                json!({"order": {"side": "buy"}, "expected": "buy"})
+            )
+            .await,
+            "then"
+        );
+        assert_eq!(
+            branch_taken(cfg, json!({"order": {"side": "sell"}, "expected": "buy"})).await,
+            "else"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_conditional_path_against_literal_and_precedence() {
+        // `when_path` wins over the `when` string on the same side.
+        let cfg = path_conditional("sell", Some("qty"), "3", None);
+        assert_eq!(branch_taken(cfg.clone(), json!({"qty": 3})).await, "then");
+        assert_eq!(branch_taken(cfg, json!({"qty": 4})).await, "else");
+
+        // A missing path compares as an empty string.
+        let cfg = path_conditional("", Some("absent"), "", None);
+        assert_eq!(branch_taken(cfg, json!({})).await, "then");
+    }
+
+    #[test]
+    fn test_grid_cell_center() {
+        // 7-column calendar at (100, 200) with 40x30 cells: (3, 2) -> (100+120+20, 200+60+15)
+        assert_eq!(grid_cell_center(100, 200, 40, 30, 3, 2), (240, 275));
+        assert_eq!(grid_cell_center(0, 0, 10, 10, 0, 0), (5, 5));
+        assert_eq!(
+            grid_cell_center(i32::MAX, 0, u32::MAX, 1, 5, 0),
+            (i32::MAX, 0)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_capture_screen_saves_path_to_var() {
+        let mut cfg = Config::default();
+        cfg.workflows.insert(
+            "shot".into(),
+            vec![ActionDef::CaptureScreen {
+                path: "shots/{{id}}.png".into(),
+                region: None,
+                to_clipboard: false,
+                save_path_to: Some("last_shot".into()),
+            }],
+        );
+        let mut rt = Runtime::new(cfg, true);
+        let vars = HashMap::from([("id".to_string(), "7".to_string())]);
+        let outcome = rt
+            .execute_workflow("shot", &Value::Null, vars)
+            .await
+            .unwrap();
+        assert_eq!(outcome.vars_final["last_shot"], "shots/7.png");
+    }
+
+    #[tokio::test]
+    async fn test_tag_filter_skips_untagged_workflows() {
+        let dir = tempfile::tempdir().unwrap();
+        let d = dir.path().to_str().unwrap();
+        let mut cfg = batch_config();
+        cfg.workflow_meta.insert(
+            "first".into(),
+            WorkflowMeta {
+                tags: vec!["smoke".into()],
+                ..Default::default()
+            },
+        );
+        let mut rt = Runtime::new(cfg, false);
+        rt.set_tag_filter(Some(vec!["smoke".into()]));
+
+        let tagged = rt
+            .run_event_detailed(&json!({"type": "first", "dir": d}))
+            .await
+            .unwrap();
+        assert!(!tagged.tag_filtered);
+        let untagged = rt
+            .run_event_detailed(&json!({"type": "second", "dir": d}))
+            .await
+            .unwrap();
+        assert!(untagged.tag_filtered);
+        assert_eq!(untagged.steps_executed, 0);
+        assert!(dir.path().join("first.json").exists());
+        assert!(!dir.path().join("second.json").exists());
+
+        rt.set_tag_filter(None);
+        rt.run_event(&json!({"type": "second", "dir": d}))
+            .await
+            .unwrap();
+        assert!(dir.path().join("second.json").exists());
+    }
+
+    #[tokio::test]
+    async fn test_ref_scopes_vars_unless_exported() {
+        let mut cfg = Config::default();
+        cfg.actions.insert(
+            "set_both".into(),
+            ActionDef::Sequence {
+                steps: vec![
+                    ActionDef::SetVar {
+                        name: "inner".into(),
+                        value: "seen {{outer}}".into(),
+                    },
+                    ActionDef::SetVar {
+                        name: "result".into(),
+                        value: "42".into(),
+                    },
+                ],
+            },
+        );
+        let call = |export: &[&str], share_vars| ActionDef::Ref {
+            name: "set_both".into(),
+            args: Default::default(),
+            export: export.iter().map(|s| s.to_string()).collect(),
+            share_vars,
+        };
+        cfg.workflows
+            .insert("scoped".into(), vec![call(&["result"], false)]);
+        cfg.workflows.insert("shared".into(), vec![call(&[], true)]);
+        let mut rt = Runtime::new(cfg, true);
+        let vars = HashMap::from([("outer".to_string(), "x".to_string())]);
+
+        let scoped = rt
+            .execute_workflow("scoped", &Value::Null, vars.clone())
+            .await
+            .unwrap();
+        assert_eq!(scoped.vars_final.get("inner"), None);
+        assert_eq!(scoped.vars_final["result"], "42");
+        assert_eq!(scoped.vars_final["outer"], "x");
+
+        let shared = rt
+            .execute_workflow("shared", &Value::Null, vars)
+            .await
+            .unwrap();
+        assert_eq!(shared.vars_final["inner"], "seen x");
+    }
+
+    #[tokio::test]
+    async fn test_ref_args_parameterize_named_action() {
+        let cfg: Config = serde_json::from_value(json!({
+            "actions": {
+                "greet": { "type": "set_var", "name": "greeting_{{who}}", "value": "{{salute}} {{who}}" }
+            },
+            "workflows": { "wf": [
+                { "type": "ref", "name": "greet", "args": { "who": "ada", "salute": "Hi" },
+                  "export": ["greeting_ada"] },
+                { "type": "ref", "name": "greet", "args": { "who": "{{next}}", "salute": "Hello" },
+                  "share_vars": true }
+            ]}
+        }))
+        .unwrap();
+        let mut rt = Runtime::new(cfg, true);
+        let vars = HashMap::from([
+            ("who".to_string(), "caller".to_string()),
+            ("next".to_string(), "linus".to_string()),
+        ]);
+        let outcome = rt.execute_workflow("wf", &Value::Null, vars).await.unwrap();
+
+        assert_eq!(outcome.vars_final["greeting_ada"], "Hi ada");
+        assert_eq!(outcome.vars_final["greeting_linus"], "Hello linus");
+        // Args are scoped to the call, even with `share_vars`
+        assert_eq!(outcome.vars_final["who"], "caller");
+        assert_eq!(outcome.vars_final.get("salute"), None);
+    }
+
+    #[tokio::test]
+    async fn test_clear_vars_keeps_listed_and_reserved() {
+        let mut cfg = Config::default();
+        cfg.workflows.insert(
+            "wf".into(),
+            vec![
+                ActionDef::SetVar {
+                    name: "stale".into(),
+                    value: "1".into(),
+                },
+                ActionDef::ClearVars {
+                    keep: vec!["id".into()],
+                },
+            ],
+        );
+        let mut rt = Runtime::new(cfg, true);
+        let vars = HashMap::from([
+            ("id".to_string(), "7".to_string()),
+            ("_workflow".to_string(), "wf".to_string()),
+        ]);
+        let outcome = rt.execute_workflow("wf", &Value::Null, vars).await.unwrap();
+        assert_eq!(outcome.vars_final.get("stale"), None);
+        assert_eq!(outcome.vars_final["id"], "7");
+        assert_eq!(outcome.vars_final["_workflow"], "wf");
+    }
+
+    #[tokio::test]
+    async fn test_debounced_ref_runs_once_within_window() {
+        let mut cfg = Config::default();
+        cfg.actions.insert(
+            "submit".into(),
+            ActionDef::SetVar {
+                name: "submits".into(),
+                value: "{{submits}}x".into(),
+            },
+        );
+        cfg.action_meta.insert(
+            "submit".into(),
+            ActionMeta {
+                debounce_ms: Some(60_000),
+            },
+        );
+        let submit = ActionDef::Ref {
+            name: "submit".into(),
+            args: Default::default(),
+            export: vec!["submits".into()],
+            share_vars: false,
+        };
+        cfg.workflows
+            .insert("wf".into(), vec![submit.clone(), submit.clone(), submit]);
+        let mut rt = Runtime::new(cfg, true);
+        let vars = HashMap::from([("submits".to_string(), String::new())]);
+
+        let outcome = rt
+            .execute_workflow("wf", &Value::Null, vars.clone())
+            .await
+            .unwrap();
+        assert_eq!(outcome.vars_final["submits"], "x");
+        // The window spans the whole runtime, not just one workflow run.
+        let outcome = rt.execute_workflow("wf", &Value::Null, vars).await.unwrap();
+        assert_eq!(outcome.vars_final["submits"], "");
     }
 }
@@ -0,0 +1,110 @@
+//! Idle watchdog ("dead-man's switch") for event sources.
+//!
+//! The event loop resets the watchdog whenever an event is processed; if the
+//! configured timeout elapses first, `IdleWatchdog::expired` completes with the
+//! recovery workflow name and the timer re-arms itself.
+
+use std::time::Duration;
+use tokio::time::{Instant, sleep_until};
+use tracing::warn;
+
+use crate::config::IdleWatchdogConfig;
+
+/// Reset-on-event timer backing `Config::idle_watchdog`.
+#[derive(Debug, Clone)]
+pub struct IdleWatchdog {
+    timeout: Duration,
+    workflow: String,
+    deadline: Instant,
+}
+
+impl IdleWatchdog {
+    /// Create an armed watchdog from its configuration.
+    pub fn new(cfg: &IdleWatchdogConfig) -> Self {
+        let timeout = Duration::from_millis(cfg.timeout_ms);
+        Self {
+            timeout,
+            workflow: cfg.workflow.clone(),
+            deadline: Instant::now() + timeout,
+        }
+    }
+
+    /// Workflow run when the watchdog fires.
+    pub fn workflow(&self) -> &str {
+        &self.workflow
+    }
+
+    /// Re-arm the timer (call after each processed event).
+    pub fn reset(&mut self) {
+        self.deadline = Instant::now() + self.timeout;
+    }
+
+    /// Wait until the timeout elapses without a reset, then re-arm and return
+    /// the workflow to run. Cancel-safe: dropping the future keeps the deadline.
+    pub async fn expired(&mut self) -> String {
+        sleep_until(self.deadline).await;
+        warn!(
+            target: "notabot::runtime",
+            timeout_ms = self.timeout.as_millis() as u64,
+            workflow = %self.workflow,
+            "No events within the idle timeout; running watchdog workflow"
+        );
+        self.reset();
+        self.workflow.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ActionDef, Config};
+    use crate::executor::Runtime;
+    use std::collections::HashMap;
+
+    fn watchdog(timeout_ms: u64) -> IdleWatchdog {
+        IdleWatchdog::new(&IdleWatchdogConfig {
+            timeout_ms,
+            workflow: "recover".into(),
+        })
+    }
+
+    #[tokio::test]
+    async fn fires_without_events_and_runs_workflow() {
+        let dir = tempfile::tempdir().unwrap();
+        let marker = dir.path().join("recovered.json");
+        let mut cfg = Config::default();
+        cfg.workflows.insert(
+            "recover".into(),
+            vec![ActionDef::DumpState {
+                path: marker.to_string_lossy().into_owned(),
+            }],
+        );
+        let mut rt = Runtime::new(cfg, false);
+
+        let mut wd = watchdog(20);
+        let workflow = tokio::time::timeout(Duration::from_secs(2), wd.expired())
+            .await
+            .expect("watchdog should fire");
+        rt.run_workflow_by_name(&workflow, HashMap::new())
+            .await
+            .unwrap();
+        assert!(marker.exists());
+
+        // Re-armed: fires again after another quiet period.
+        tokio::time::timeout(Duration::from_secs(2), wd.expired())
+            .await
+            .expect("watchdog should re-arm");
+    }
+
+    #[tokio::test]
+    async fn reset_postpones_firing() {
+        let mut wd = watchdog(200);
+        tokio::time::sleep(Duration::from_millis(120)).await;
+        wd.reset();
+        assert!(
+            tokio::time::timeout(Duration::from_millis(120), wd.expired())
+                .await
+                .is_err()
+        );
+    }
+}
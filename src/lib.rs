@@ -9,18 +9,27 @@
 //! - `executor`: Action definitions and runtime execution engine.
 //! - `sources`: Event sources (file, directory, TCP, stdin).
 //! - `utils`: Utilities such as interpolation and (optional) window helpers.
+//! - `app`: The event loop (`run`) with two-stage shutdown, shared by the CLI.
+//! - `testing` (feature `testing`): in-memory source and capturing executor for workflow tests.
 //!
 //! Use `notabot::prelude::*` to bring commonly used items into scope quickly.
 
+/// Public module: event loop and shutdown handling.
+pub mod app;
 /// Public module: configuration (models, loader, schema helpers).
 pub mod config;
 /// Public module: execution engine (actions and runtime).
 pub mod executor;
-/// Public module: event sources (file, directory, tcp, stdin).
+/// Public module: event sources (file, directory, tcp, udp, http, websocket, stdin).
 pub mod sources;
+/// Public module: helpers for end-to-end workflow tests (feature `testing`).
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 /// Public module: utilities (interpolation, window helpers, etc.).
 pub mod utils;
 
+pub use app::{RunExit, run};
+
 /// Crate-level constants for consumers that want to inspect package metadata at runtime.
 pub const PKG_NAME: &str = env!("CARGO_PKG_NAME");
 pub const PKG_VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -31,30 +40,77 @@ pub const fn version() -> &'static str {
     PKG_VERSION
 }
 
+/// Output format of the tracing subscriber installed by `init_tracing`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Human-readable lines (default).
+    #[default]
+    Text,
+    /// One JSON object per line, including the fields of the enclosing spans
+    /// (e.g. `workflow_name`, `step_index`), for log collectors.
+    Json,
+}
+
+impl std::str::FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            other => Err(format!(
+                "unknown log format '{other}' (expected text or json)"
+            )),
+        }
+    }
+}
+
 /// Initialize tracing (logging) with a reasonable default.
 /// - Honors the `RUST_LOG` environment variable if set.
 /// - Falls back to `info` level.
+/// - Writes JSON lines when `NOTABOT_LOG_FORMAT=json`, text otherwise.
 ///
 /// Safe to call multiple times; subsequent calls are no-ops.
 pub fn init_tracing() {
+    init_tracing_with(None, None);
+}
+
+/// Initialize tracing like `init_tracing`, with an explicit `level` and `format`
+/// taking precedence over `RUST_LOG` and `NOTABOT_LOG_FORMAT` when given.
+pub fn init_tracing_with(level: Option<tracing::Level>, format: Option<LogFormat>) {
     use tracing::Level;
     use tracing_subscriber::fmt;
 
     // Parse RUST_LOG as a simple level (trace|debug|info|warn|error)
-    let level = std::env::var("RUST_LOG")
-        .ok()
-        .and_then(|s| match s.to_lowercase().as_str() {
-            "trace" => Some(Level::TRACE),
-            "debug" => Some(Level::DEBUG),
-            "info" => Some(Level::INFO),
-            "warn" | "warning" => Some(Level::WARN),
-            "error" => Some(Level::ERROR),
-            _ => None,
-        })
+    let level = level
+        .or_else(|| std::env::var("RUST_LOG").ok().and_then(|s| parse_level(&s)))
         .unwrap_or(Level::INFO);
+    let format = format
+        .or_else(|| {
+            std::env::var("NOTABOT_LOG_FORMAT")
+                .ok()
+                .and_then(|s| s.parse().ok())
+        })
+        .unwrap_or_default();
 
     // Ignore the error if the global subscriber was already set.
-    let _ = fmt().with_max_level(level).try_init();
+    let _ = match format {
+        LogFormat::Text => fmt().with_max_level(level).try_init(),
+        LogFormat::Json => fmt().json().with_max_level(level).try_init(),
+    };
+}
+
+/// Parse a simple level name (trace|debug|info|warn|error), case-insensitively.
+pub fn parse_level(s: &str) -> Option<tracing::Level> {
+    use tracing::Level;
+    match s.to_lowercase().as_str() {
+        "trace" => Some(Level::TRACE),
+        "debug" => Some(Level::DEBUG),
+        "info" => Some(Level::INFO),
+        "warn" | "warning" => Some(Level::WARN),
+        "error" => Some(Level::ERROR),
+        _ => None,
+    }
 }
 
 /// A convenient set of exports for most consumers.
@@ -1,14 +1,19 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
+use anyhow::Context;
 use clap::Parser;
-use serde_json::Value;
 use tokio::sync::mpsc;
 use tracing::{debug, error, info, warn};
 
 use notabot::config as cfg;
 use notabot::executor::Runtime;
+use notabot::executor::arming::{ArmWait, DEFAULT_ARM_KEY, ensure_console, wait_for_arm_key};
 use notabot::sources;
 
+/// How long a forced exit waits for the event loop before terminating the process.
+const FORCE_EXIT_GRACE: Duration = Duration::from_millis(500);
+
 /// Notabot CLI
 #[derive(Debug, Parser)]
 #[command(
@@ -18,42 +23,74 @@ use notabot::sources;
 )]
 struct Args {
     /// Path to the JSON configuration file
-    #[arg(short = 'c', long = "config", default_value = "config/default.json")]
+    #[arg(short = 'c', long = "config", default_value = "config/default.json", global = true)]
     config: PathBuf,
 
     /// Enable dry-run mode (log actions instead of simulating input)
-    #[arg(long = "dry-run")]
+    #[arg(long = "dry-run", global = true)]
     dry_run: bool,
 
     /// Set log level (e.g., trace, debug, info, warn, error). Overrides RUST_LOG.
-    #[arg(long = "log-level")]
+    #[arg(long = "log-level", global = true)]
     log_level: Option<String>,
 
+    /// Log output format: text (default) or json. Overrides NOTABOT_LOG_FORMAT.
+    #[arg(long = "log-format", global = true)]
+    log_format: Option<notabot::LogFormat>,
+
     /// Print the JSON Schema for the configuration and exit
     #[arg(long = "print-schema")]
     print_schema: bool,
+
+    /// Print TypeScript declarations for the configuration and exit
+    #[arg(long = "print-ts")]
+    print_ts: bool,
+
+    /// Print the effective (loaded and validated) configuration as JSON and exit
+    #[arg(long = "dump-config")]
+    dump_config: bool,
+
+    /// Reload the configuration when the file changes (invalid edits are ignored)
+    #[arg(long = "watch-config")]
+    watch_config: bool,
+
+    /// Record every executed action and write them as a JSON array to this file on exit
+    #[arg(long = "trace-out", global = true)]
+    trace_out: Option<PathBuf>,
+
+    /// Only activate event bindings whose workflow has one of these tags
+    /// (comma-separated; see `workflow_meta`)
+    #[arg(long = "only-tags", value_delimiter = ',')]
+    only_tags: Option<Vec<String>>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum Command {
+    /// Run one workflow by name and exit, without starting any event source
+    RunWorkflow {
+        /// Name of the workflow (a key of `workflows`)
+        name: String,
+
+        /// Workflow variable as KEY=VALUE (repeatable). Everything after the first `=`
+        /// is the value, which may be empty or contain `=`; the key may not be empty.
+        #[arg(long = "var", value_name = "KEY=VALUE", value_parser = parse_var)]
+        vars: Vec<(String, String)>,
+    },
 }
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let args = Args::parse();
 
-    // Honor --log-level by setting RUST_LOG before initializing tracing.
-    if let Some(level) = &args.log_level {
-        let level = match level.to_lowercase().as_str() {
-            "trace" => tracing::Level::TRACE,
-            "debug" => tracing::Level::DEBUG,
-            "info" => tracing::Level::INFO,
-            "warn" | "warning" => tracing::Level::WARN,
-            "error" => tracing::Level::ERROR,
-            _ => tracing::Level::INFO,
-        };
-        let _ = tracing_subscriber::fmt().with_max_level(level).try_init();
-    }
-
-    if args.log_level.is_none() {
-        notabot::init_tracing();
-    }
+    // --log-level and --log-format override RUST_LOG and NOTABOT_LOG_FORMAT.
+    let level = args
+        .log_level
+        .as_deref()
+        .map(|level| notabot::parse_level(level).unwrap_or(tracing::Level::INFO));
+    notabot::init_tracing_with(level, args.log_format);
     info!(
         version = notabot::PKG_VERSION,
         config = %args.config.display(),
@@ -68,40 +105,159 @@ async fn main() -> anyhow::Result<()> {
         return Ok(());
     }
 
-    // Load configuration
-    let config = cfg::load_from_path_async(&args.config).await?;
+    if args.print_ts {
+        print!("{}", cfg::generate_typescript());
+        return Ok(());
+    }
+
+    // Load configuration (built-in defaults if the file does not exist)
+    let config = cfg::load_from_path_or_default_async(&args.config).await?;
     debug!(target: "notabot", "Configuration loaded successfully");
 
+    // Optionally warn when the config file is edited while running (unless reloading it)
+    let _drift_check = match config.config_drift_check_ms {
+        Some(ms) if args.config.exists() && !args.watch_config => Some(
+            cfg::drift::ConfigDriftWatcher::new(&args.config)?.spawn(Duration::from_millis(ms)),
+        ),
+        _ => None,
+    };
+
     // Create the runtime (owns the config)
     let mut runtime = Runtime::new(config, args.dry_run);
 
+    if args.dump_config {
+        let json = serde_json::to_string_pretty(&runtime.effective_config_json())?;
+        println!("{json}");
+        return Ok(());
+    }
+
+    // Optionally hot-reload the config file; changes apply between workflows
+    let _config_watcher = if args.watch_config {
+        Some(cfg::watch::ConfigWatcher::spawn(
+            &args.config,
+            runtime.config_update_sender(),
+        )?)
+    } else {
+        None
+    };
+
+    if let Some(tags) = &args.only_tags {
+        info!(tags = ?tags, "Restricting event bindings to tagged workflows");
+    }
+    runtime.set_tag_filter(args.only_tags.clone());
+    runtime.set_trace(args.trace_out.is_some());
+
+    // Fail fast (with an actionable message) if input simulation is unavailable
+    runtime.check_input_ready()?;
+
+    if let Some(Command::RunWorkflow { name, vars }) = args.command {
+        if runtime.config().require_arm {
+            warn!("require_arm is set: input actions are skipped by run-workflow");
+        }
+        info!(workflow = %name, "Running workflow once");
+        let result = runtime
+            .run_workflow_by_name(&name, vars.into_iter().collect())
+            .await;
+        if let Some(path) = &args.trace_out {
+            write_trace(path, &mut runtime)?;
+        }
+        result?;
+        if runtime.pending_schedules() > 0 {
+            warn!(
+                pending = runtime.pending_schedules(),
+                "Exiting with scheduled workflows that will not run"
+            );
+        }
+        if let Some(emitted) = runtime.take_emitted_event_receiver()
+            && !emitted.is_empty()
+        {
+            warn!(
+                pending = emitted.len(),
+                "Exiting with emitted events that will not be handled"
+            );
+        }
+        return Ok(());
+    }
+
     // Build and spawn event sources based on config
-    let sources = sources::build_sources_from_config(runtime.config());
+    let sources = sources::build_sources_from_config(runtime.config())?;
     if sources.is_empty() {
         warn!("No event sources configured. The runtime will wait for Ctrl+C and then exit.");
     }
 
-    // Channel for events produced by sources
-    let (tx, mut rx) = mpsc::channel::<Value>(256);
-    let _handles = sources::spawn_all_sources(&sources, tx);
-
-    // Main loop: handle events or Ctrl+C
-    tokio::select! {
-        _ = async {
-            while let Some(event) = rx.recv().await {
-                match runtime.run_event(&event) {
-                    Ok(()) => { /* ok */ }
-                    Err(err) => {
-                        error!(error = %err, event = %event, "Failed to handle event");
-                    }
-                }
+    // Forward every Ctrl+C to the event loop: the first drains, the second forces exit.
+    // If a blocking workflow keeps the loop from reacting, exit anyway after a grace period.
+    let (signal_tx, signal_rx) = mpsc::unbounded_channel();
+
+    // Safe mode: keep input disabled until the arm key is pressed in this console.
+    // The console is in raw mode meanwhile, so Ctrl+C is forwarded from here.
+    if runtime.config().require_arm {
+        ensure_console()?;
+        let key = runtime.config().arm_key.unwrap_or(DEFAULT_ARM_KEY);
+        let switch = runtime.arm_switch();
+        let signal_tx = signal_tx.clone();
+        warn!("Input is DISARMED: press {key} in this console to enable real input");
+        tokio::task::spawn_blocking(move || match wait_for_arm_key(&switch, key) {
+            Ok(ArmWait::Armed) => {}
+            Ok(ArmWait::Interrupted) => {
+                info!("Received Ctrl+C before arming, draining queued events");
+                let _ = signal_tx.send(());
+            }
+            Err(e) => {
+                error!(error = %format!("{e:#}"), "Cannot wait for the arm key; input stays disarmed")
+            }
+        });
+    }
+
+    tokio::spawn(async move {
+        let mut count = 0u32;
+        while tokio::signal::ctrl_c().await.is_ok() {
+            count += 1;
+            if count == 1 {
+                info!("Received Ctrl+C, draining queued events (press Ctrl+C again to force quit)");
+            } else {
+                warn!("Received second Ctrl+C, forcing exit");
+            }
+            if signal_tx.send(()).is_err() {
+                break;
+            }
+            if count >= 2 {
+                tokio::time::sleep(FORCE_EXIT_GRACE).await;
+                warn!("Event loop did not stop in time; exiting immediately");
+                std::process::exit(130);
             }
-        } => {}
-        _ = tokio::signal::ctrl_c() => {
-            info!("Received Ctrl+C, shutting down");
         }
+    });
+
+    let exit = notabot::run(&mut runtime, &sources, signal_rx).await?;
+    debug!(target: "notabot", ?exit, "Event loop finished");
+    if let Some(path) = &args.trace_out {
+        write_trace(path, &mut runtime)?;
+    }
+    if exit == notabot::RunExit::Forced {
+        runtime.release_held_keys();
+        info!("Notabot exited (forced)");
+        std::process::exit(130);
     }
 
     info!("Notabot exited");
     Ok(())
 }
+
+/// Parse a `--var KEY=VALUE` pair, splitting at the first `=`.
+fn parse_var(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(format!("expected KEY=VALUE, got '{s}'")),
+    }
+}
+
+/// Write the actions recorded by the runtime to `path` as pretty-printed JSON.
+fn write_trace(path: &Path, runtime: &mut Runtime) -> anyhow::Result<()> {
+    let trace = runtime.take_trace();
+    let json = serde_json::to_string_pretty(&trace)?;
+    std::fs::write(path, json)
+        .with_context(|| format!("Failed to write action trace to {}", path.display()))?;
+    info!(path = %path.display(), actions = trace.len(), "Wrote action trace");
+    Ok(())
+}
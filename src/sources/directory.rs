@@ -1,20 +1,33 @@
 use std::{
-    collections::{HashSet, VecDeque},
+    collections::{HashMap, HashSet, VecDeque},
     fs,
     path::{Path, PathBuf},
-    time::Duration,
+    sync::Arc,
+    time::{Duration, Instant, SystemTime},
 };
 
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
 use serde_json::Value;
-use tokio::{fs as afs, sync::mpsc::Sender, task::JoinHandle, time::interval};
+use tokio::{
+    fs as afs,
+    sync::mpsc::{Sender, UnboundedReceiver, unbounded_channel},
+    task::JoinHandle,
+    time::interval,
+};
+use tokio_util::sync::CancellationToken;
 use tracing::{error, info, trace, warn};
 
-use super::EventSource;
+use super::{EventSource, SourceMetrics};
+use crate::utils::pattern::simple_pattern_match;
+
+/// In watch mode, the directory is still rescanned this often to catch files whose
+/// notifications were missed (e.g. event queue overflow).
+const WATCH_RESCAN_INTERVAL: Duration = Duration::from_secs(30);
 
-/// Directory-based event source (polling).
+/// Directory-based event source (polling, or filesystem notifications with `watch`).
 ///
 /// Behavior:
-/// - Recursively (optional) scans a directory at a fixed cadence (400ms default inside implementation)
+/// - Recursively (optional) scans a directory every `poll_ms` (default 400ms, minimum 10ms),
 ///   adding new files that match an optional simple pattern (supports `*` wildcards).
 /// - Processes at most one file per tick (FIFO) to smooth bursts:
 ///     1. Reads file contents (async).
@@ -29,44 +42,105 @@ use super::EventSource;
 /// Error Handling:
 /// - All I/O / parse errors are logged and the loop continues.
 /// - Channel closure (send error) terminates the task gracefully.
+/// - Cancelling the shutdown token ends the task at the next tick; a file being
+///   dispatched is finished first (so it is never sent without being deleted).
 ///
 /// Pattern Matching:
 /// - Simple glob-like matching with `*` as "match any (possibly empty) substring".
 ///   Multiple `*` allowed. (E.g. `event_*.json`, `*order*`, `*.json`)
+/// - Files are filtered by a `FileFilter`: a file is processed when it matches any
+///   include pattern (or there are none) AND matches no exclude pattern.
+///
+/// Watching:
+/// - With `with_watch(true)`, new and modified files are enqueued as soon as a `notify`
+///   event arrives instead of on the next directory scan; the same filter applies. The
+///   directory is scanned at start and every `WATCH_RESCAN_INTERVAL` as a safety net.
+/// - If the watcher cannot be created, the source logs a warning and polls instead.
+/// - Dispatch is unchanged: one file per tick, deleted after a successful dispatch.
+///
+/// Stability:
+/// - With `require_stable_ms`, a dequeued file whose size/mtime changed within that
+///   window is put back at the end of the queue instead of being read, so producers
+///   writing incrementally are never caught mid-write.
+///
+/// Metrics:
+/// - Outcomes are counted in the source's `SourceMetrics`: events sent, parse and read
+///   errors, skipped files (empty, or requeued because not yet stable), deletions.
 ///
 /// Future Enhancements:
-/// - Optional `notify`-based watcher (platform dependent) instead of polling.
-/// - Rate limits / metrics (processed, failed, skipped, retried).
-/// - Configurable poll interval.
+/// - Rate limits.
 ///
 /// Safety:
 /// - Never panics inside the task; designed for long-running robustness.
 #[derive(Debug, Clone)]
 pub struct DirectorySource {
     path: String,
-    pattern: Option<String>,
+    filter: FileFilter,
     recursive: bool,
     poll_ms: u64,
+    require_stable_ms: Option<u64>,
+    watch: bool,
+    metrics: Arc<SourceMetrics>,
+}
+
+/// Include/exclude file name filter: "included AND NOT excluded".
+///
+/// - An empty include list includes every file.
+/// - Any matching exclude pattern rejects the file, even if it is included.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileFilter {
+    include: Vec<String>,
+    exclude: Vec<String>,
+}
+
+impl FileFilter {
+    /// Build a filter from include and exclude patterns (`*` wildcards).
+    pub fn new(include: Vec<String>, exclude: Vec<String>) -> Self {
+        Self { include, exclude }
+    }
+
+    /// Whether `file_name` passes the filter.
+    pub fn matches(&self, file_name: &str) -> bool {
+        let included = self.include.is_empty()
+            || self
+                .include
+                .iter()
+                .any(|p| simple_pattern_match(file_name, p));
+        included
+            && !self
+                .exclude
+                .iter()
+                .any(|p| simple_pattern_match(file_name, p))
+    }
 }
 
 impl DirectorySource {
     /// Create a new `DirectorySource`.
     ///
-    /// `poll_ms` currently fixed internally to 400ms to keep signature stable;
-    /// expose via config if needed later. For now we keep the parameter off the public
-    /// API to avoid premature complexity—modify here if you want configurability.
-    pub fn new(path: String, pattern: Option<String>, recursive: bool) -> Self {
+    /// - `poll_ms`: optional scan/dispatch interval (defaults to 400ms; minimum 10ms).
+    pub fn new(path: String, filter: FileFilter, recursive: bool, poll_ms: Option<u64>) -> Self {
         Self {
             path,
-            pattern,
+            filter,
             recursive,
-            poll_ms: 400,
+            poll_ms: poll_ms.unwrap_or(400).max(10),
+            require_stable_ms: None,
+            watch: false,
+            metrics: Arc::default(),
         }
     }
 
-    // fn make_interval(&self) -> Interval {
-    //     interval(Duration::from_millis(self.poll_ms))
-    // }
+    /// Use filesystem notifications to find new files (see "Watching" above).
+    pub fn with_watch(mut self, watch: bool) -> Self {
+        self.watch = watch;
+        self
+    }
+
+    /// Only process files whose size/mtime stayed unchanged for `ms` (None disables).
+    pub fn with_require_stable_ms(mut self, ms: Option<u64>) -> Self {
+        self.require_stable_ms = ms;
+        self
+    }
 }
 
 impl EventSource for DirectorySource {
@@ -74,37 +148,90 @@ impl EventSource for DirectorySource {
         "directory"
     }
 
-    fn start(&self, sender: Sender<Value>) -> JoinHandle<()> {
+    fn metrics(&self) -> Option<Arc<SourceMetrics>> {
+        Some(self.metrics.clone())
+    }
+
+    fn start(&self, sender: Sender<Value>, shutdown: CancellationToken) -> JoinHandle<()> {
         let root = self.path.clone();
-        let pattern = self.pattern.clone();
+        let filter = self.filter.clone();
         let recursive = self.recursive;
         let poll_ms = self.poll_ms;
+        let require_stable_ms = self.require_stable_ms;
+        let watch = self.watch;
+        let metrics = self.metrics.clone();
 
         tokio::spawn(async move {
+            // Keep the watcher alive for the lifetime of the task
+            let watcher = if watch {
+                start_watcher(Path::new(&root), recursive)
+            } else {
+                None
+            };
+            let (_watcher, mut notified) = match watcher {
+                Some((w, rx)) => (Some(w), Some(rx)),
+                None => (None, None),
+            };
             info!(
                 target: "notabot::sources",
-                path = %root, ?pattern, recursive, poll_ms,
-                "DirectorySource task started (polling)"
+                path = %root, ?filter, recursive, poll_ms, ?require_stable_ms,
+                mode = if notified.is_some() { "watching" } else { "polling" },
+                "DirectorySource task started"
             );
 
             let mut queue: VecDeque<PathBuf> = VecDeque::new();
             let mut queued: HashSet<PathBuf> = HashSet::new();
+            let mut stability =
+                require_stable_ms.map(|ms| StabilityTracker::new(Duration::from_millis(ms)));
             let mut ticker = interval(Duration::from_millis(poll_ms));
+            let mut last_scan: Option<Instant> = None;
 
             loop {
-                ticker.tick().await;
+                match notified.as_mut() {
+                    Some(rx) => tokio::select! {
+                        _ = shutdown.cancelled() => break,
+                        _ = ticker.tick() => {}
+                        Some(path) = rx.recv() => {
+                            enqueue_file(path, &filter, &mut queue, &mut queued);
+                            while let Ok(path) = rx.try_recv() {
+                                enqueue_file(path, &filter, &mut queue, &mut queued);
+                            }
+                        }
+                    },
+                    None => tokio::select! {
+                        _ = shutdown.cancelled() => break,
+                        _ = ticker.tick() => {}
+                    },
+                }
 
-                // Discover new candidate files
-                discover_files(
-                    Path::new(&root),
-                    recursive,
-                    &pattern,
-                    &mut queue,
-                    &mut queued,
-                );
+                // Discover new candidate files (every tick when polling)
+                let scan_due = notified.is_none()
+                    || last_scan.is_none_or(|t| t.elapsed() >= WATCH_RESCAN_INTERVAL);
+                if scan_due {
+                    discover_files(
+                        Path::new(&root),
+                        recursive,
+                        &filter,
+                        &mut queue,
+                        &mut queued,
+                    );
+                    last_scan = Some(Instant::now());
+                }
 
                 // Process at most one file per tick for smoother throughput
                 if let Some(path) = queue.pop_front() {
+                    if let Some(tracker) = stability.as_mut()
+                        && !tracker.is_stable(&path, file_signature(&path), Instant::now())
+                    {
+                        trace!(
+                            target: "notabot::sources",
+                            file = %path.display(),
+                            "File not stable yet; requeued"
+                        );
+                        metrics.record_skipped();
+                        queue.push_back(path);
+                        continue;
+                    }
                     queued.remove(&path);
 
                     match afs::read_to_string(&path).await {
@@ -116,6 +243,7 @@ impl EventSource for DirectorySource {
                                     file = %path.display(),
                                     "Skipping empty file"
                                 );
+                                metrics.record_skipped();
                                 continue;
                             }
                             match serde_json::from_str::<Value>(trimmed) {
@@ -129,21 +257,27 @@ impl EventSource for DirectorySource {
                                         );
                                         break;
                                     }
+                                    metrics.record_event_sent();
                                     info!(
                                         target: "notabot::sources",
                                         file = %path.display(),
                                         "Dispatched event from directory file"
                                     );
-                                    if let Err(e) = afs::remove_file(&path).await {
-                                        warn!(
-                                            target: "notabot::sources",
-                                            file = %path.display(),
-                                            error = %e,
-                                            "Failed to delete file after dispatch"
-                                        );
+                                    match afs::remove_file(&path).await {
+                                        Ok(()) => metrics.record_file_deleted(),
+                                        Err(e) => {
+                                            metrics.record_delete_error();
+                                            warn!(
+                                                target: "notabot::sources",
+                                                file = %path.display(),
+                                                error = %e,
+                                                "Failed to delete file after dispatch"
+                                            );
+                                        }
                                     }
                                 }
                                 Err(e) => {
+                                    metrics.record_parse_error();
                                     warn!(
                                         target: "notabot::sources",
                                         file = %path.display(),
@@ -154,6 +288,7 @@ impl EventSource for DirectorySource {
                             }
                         }
                         Err(e) => {
+                            metrics.record_read_error();
                             warn!(
                                 target: "notabot::sources",
                                 file = %path.display(),
@@ -164,15 +299,118 @@ impl EventSource for DirectorySource {
                     }
                 }
             }
+
+            trace!(target: "notabot::sources", path = %root, "DirectorySource task ended");
         })
     }
 }
 
-/// Recursively (optional) discover files and enqueue new ones that match the pattern.
+/// Size and modification time of a file, used to detect ongoing writes.
+type FileSignature = Option<(u64, Option<SystemTime>)>;
+
+fn file_signature(path: &Path) -> FileSignature {
+    let meta = fs::metadata(path).ok()?;
+    Some((meta.len(), meta.modified().ok()))
+}
+
+/// Remembers when each file's signature last changed.
+#[derive(Debug)]
+struct StabilityTracker {
+    window: Duration,
+    seen: HashMap<PathBuf, (FileSignature, Instant)>,
+}
+
+impl StabilityTracker {
+    fn new(window: Duration) -> Self {
+        Self {
+            window,
+            seen: HashMap::new(),
+        }
+    }
+
+    /// Whether `path` has kept `sig` for at least the window as of `now`.
+    /// A new or changed signature restarts the window. Stable files are forgotten.
+    fn is_stable(&mut self, path: &Path, sig: FileSignature, now: Instant) -> bool {
+        match self.seen.get(path) {
+            Some((last_sig, since))
+                if *last_sig == sig && now.duration_since(*since) >= self.window =>
+            {
+                self.seen.remove(path);
+                true
+            }
+            Some((last_sig, _)) if *last_sig == sig => false,
+            _ => {
+                self.seen.insert(path.to_path_buf(), (sig, now));
+                false
+            }
+        }
+    }
+}
+
+/// Watch `root` with `notify`, forwarding paths of created/modified files.
+/// Returns `None` (after logging) when watching is unavailable.
+fn start_watcher(
+    root: &Path,
+    recursive: bool,
+) -> Option<(RecommendedWatcher, UnboundedReceiver<PathBuf>)> {
+    let (tx, rx) = unbounded_channel();
+    let handler = move |res: notify::Result<notify::Event>| match res {
+        Ok(event) if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) => {
+            for path in event.paths {
+                let _ = tx.send(path);
+            }
+        }
+        Ok(_) => {}
+        Err(e) => warn!(target: "notabot::sources", error = %e, "Directory watch error"),
+    };
+    let mode = if recursive {
+        RecursiveMode::Recursive
+    } else {
+        RecursiveMode::NonRecursive
+    };
+    let result = notify::recommended_watcher(handler).and_then(|mut w| {
+        w.watch(root, mode)?;
+        Ok(w)
+    });
+    match result {
+        Ok(watcher) => Some((watcher, rx)),
+        Err(e) => {
+            warn!(
+                target: "notabot::sources",
+                path = %root.display(),
+                error = %e,
+                "Cannot watch directory; falling back to polling"
+            );
+            None
+        }
+    }
+}
+
+/// Enqueue `path` if it is a file that passes the filter and is not queued yet.
+fn enqueue_file(
+    path: PathBuf,
+    filter: &FileFilter,
+    queue: &mut VecDeque<PathBuf>,
+    queued: &mut HashSet<PathBuf>,
+) {
+    if !path.is_file() || queued.contains(&path) {
+        return;
+    }
+    let Some(file_name) = path.file_name().and_then(|s| s.to_str()) else {
+        return;
+    };
+    if !filter.matches(file_name) {
+        return;
+    }
+    queue.push_back(path.clone());
+    queued.insert(path);
+}
+
+/// Recursively (optional) discover files and enqueue new ones that pass the filter.
 fn discover_files(
     root: &Path,
     recursive: bool,
-    pattern: &Option<String>,
+    filter: &FileFilter,
     queue: &mut VecDeque<PathBuf>,
     queued: &mut HashSet<PathBuf>,
 ) {
@@ -185,119 +423,157 @@ fn discover_files(
 
         if path.is_dir() {
             if recursive {
-                discover_files(&path, true, pattern, queue, queued);
+                discover_files(&path, true, filter, queue, queued);
             }
             continue;
         }
 
-        if !path.is_file() {
-            continue;
-        }
-
-        let file_name = match path.file_name().and_then(|s| s.to_str()) {
-            Some(f) => f,
-            None => continue,
-        };
-
-        if let Some(p) = pattern {
-            if !simple_pattern_match(file_name, p) {
-                continue;
-            }
-        }
-
-        if queued.contains(&path) {
-            continue;
-        }
-
-        queue.push_back(path.clone());
-        queued.insert(path);
+        enqueue_file(path, filter, queue, queued);
     }
 }
 
-/// Very small glob-like matcher supporting `*` wildcards (match any substring).
-/// Multiple `*` supported. Case-sensitive.
-fn simple_pattern_match(text: &str, pattern: &str) -> bool {
-    if pattern == "*" {
-        return true;
-    }
-    if !pattern.contains('*') {
-        return text == pattern;
-    }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let mut parts: Vec<&str> = pattern.split('*').collect();
-    if parts.is_empty() {
-        return true;
+    fn patterns(p: &[&str]) -> Vec<String> {
+        p.iter().map(|s| s.to_string()).collect()
     }
 
-    let starts_with_star = pattern.starts_with('*');
-    let ends_with_star = pattern.ends_with('*');
-
-    // Trim leading/trailing empties from boundary stars
-    if starts_with_star {
-        if let Some(first) = parts.first() {
-            if first.is_empty() {
-                parts.remove(0);
-            }
-        }
+    #[test]
+    fn test_file_filter_include_only() {
+        let f = FileFilter::new(patterns(&["*.json", "event_*"]), vec![]);
+        assert!(f.matches("a.json"));
+        assert!(f.matches("event_1.txt"));
+        assert!(!f.matches("notes.txt"));
+        assert!(FileFilter::default().matches("anything"));
     }
-    if ends_with_star {
-        if let Some(last) = parts.last() {
-            if last.is_empty() {
-                parts.pop();
-            }
-        }
+
+    #[test]
+    fn test_file_filter_exclude_only() {
+        let f = FileFilter::new(vec![], patterns(&["*.tmp", "STOP"]));
+        assert!(f.matches("a.json"));
+        assert!(!f.matches("a.json.tmp"));
+        assert!(!f.matches("STOP"));
     }
 
-    let mut remainder = text;
+    #[test]
+    fn test_file_filter_include_and_exclude() {
+        let f = FileFilter::new(patterns(&["*.json"]), patterns(&["~*", "sentinel.json"]));
+        assert!(f.matches("order.json"));
+        assert!(!f.matches("~order.json"));
+        assert!(!f.matches("sentinel.json"));
+        assert!(!f.matches("order.txt"));
+    }
 
-    // First segment (prefix) if no leading star
-    if !starts_with_star {
-        if let Some(first) = parts.first() {
-            if !remainder.starts_with(first) {
-                return false;
-            }
-            remainder = &remainder[first.len()..];
-            parts.remove(0);
-        }
+    #[test]
+    fn stability_tracker_waits_for_unchanged_signature() {
+        let mut t = StabilityTracker::new(Duration::from_millis(300));
+        let p = Path::new("growing.json");
+        let t0 = Instant::now();
+        let ms = Duration::from_millis;
+
+        assert!(!t.is_stable(p, Some((10, None)), t0));
+        // Grew before the window elapsed: restart.
+        assert!(!t.is_stable(p, Some((20, None)), t0 + ms(200)));
+        assert!(!t.is_stable(p, Some((20, None)), t0 + ms(400)));
+        assert!(t.is_stable(p, Some((20, None)), t0 + ms(500)));
     }
 
-    // Intermediate segments
-    while parts.len() > 1 {
-        let seg = parts.remove(0);
-        if let Some(pos) = remainder.find(seg) {
-            remainder = &remainder[pos + seg.len()..];
-        } else {
-            return false;
-        }
+    #[tokio::test]
+    async fn growing_file_is_processed_after_it_stabilizes() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("event.json");
+        std::fs::write(&file, r#"{"type": "a"}"#).unwrap();
+
+        let src = DirectorySource::new(
+            dir.path().to_string_lossy().into_owned(),
+            FileFilter::default(),
+            false,
+            None,
+        )
+        .with_require_stable_ms(Some(300));
+        let (tx, mut rx) = tokio::sync::mpsc::channel(4);
+        let handle = src.start(tx, CancellationToken::new());
+
+        // Keep writing while the source is watching: the first version must never be read.
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        std::fs::write(&file, r#"{"type": "complete"}"#).unwrap();
+
+        let event = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(event["type"], "complete");
+        handle.abort();
     }
 
-    // Last segment
-    if let Some(last) = parts.first() {
-        if !ends_with_star {
-            remainder.ends_with(last)
-        } else {
-            remainder.contains(last)
-        }
-    } else {
-        // No segments -> pattern was all stars
-        starts_with_star || ends_with_star || remainder.is_empty()
+    #[tokio::test]
+    async fn watch_mode_picks_up_new_matching_files() {
+        let dir = tempfile::tempdir().unwrap();
+        // A long poll interval shows that pick-up does not wait for a scan
+        let src = DirectorySource::new(
+            dir.path().to_string_lossy().into_owned(),
+            FileFilter::new(vec!["*.json".into()], vec![]),
+            false,
+            Some(60_000),
+        )
+        .with_watch(true);
+        let (tx, mut rx) = tokio::sync::mpsc::channel(4);
+        let handle = src.start(tx, CancellationToken::new());
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        std::fs::write(dir.path().join("ignored.txt"), r#"{"type": "txt"}"#).unwrap();
+        let file = dir.path().join("event.json");
+        std::fs::write(&file, r#"{"type": "watched"}"#).unwrap();
+
+        let event = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(event["type"], "watched");
+        // The file is deleted right after dispatch
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while file.exists() {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("dispatched file is deleted");
+        handle.abort();
+        assert!(dir.path().join("ignored.txt").exists());
+        let metrics = src.metrics().unwrap().snapshot();
+        assert_eq!(metrics.events_sent, 1);
+        assert_eq!(metrics.files_deleted, 1);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[tokio::test]
+    async fn cancelling_the_shutdown_token_ends_the_task() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = DirectorySource::new(
+            dir.path().to_string_lossy().into_owned(),
+            FileFilter::default(),
+            false,
+            Some(60_000),
+        )
+        .with_watch(true);
+        let (tx, _rx) = tokio::sync::mpsc::channel(4);
+        let shutdown = CancellationToken::new();
+        let handle = src.start(tx, shutdown.clone());
+
+        shutdown.cancel();
+        tokio::time::timeout(Duration::from_secs(5), handle)
+            .await
+            .expect("source stops after cancellation")
+            .unwrap();
+    }
 
     #[test]
-    fn test_simple_pattern_match() {
-        assert!(simple_pattern_match("event_123.json", "event_*.json"));
-        assert!(simple_pattern_match("data.txt", "*.txt"));
-        assert!(simple_pattern_match("abc", "abc"));
-        assert!(!simple_pattern_match("abc", "abcd"));
-        assert!(simple_pattern_match("abcd", "a*d"));
-        assert!(simple_pattern_match("axyzd", "a*z*d"));
-        assert!(!simple_pattern_match("abcd", "a*z*c"));
-        assert!(simple_pattern_match("anything", "*"));
+    fn poll_interval_defaults_and_is_clamped() {
+        let make = |ms| DirectorySource::new("d".into(), FileFilter::default(), false, ms);
+        assert_eq!(make(None).poll_ms, 400);
+        assert_eq!(make(Some(50)).poll_ms, 50);
+        assert_eq!(make(Some(1)).poll_ms, 10); // enforced minimum
     }
 
     #[test]
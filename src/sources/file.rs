@@ -5,27 +5,38 @@
 //! Behavior:
 //! - If `delete_on_success = true`: every non-empty successful parse dispatches an event
 //!   and the file is deleted (so the next event requires recreating the file).
-//! - If `delete_on_success = false`: the file is dispatched only when its
-//!   (length, mtime_seconds) signature changes to avoid duplicate events.
+//! - If `delete_on_success = false`: the file is dispatched only when it changed, to
+//!   avoid duplicate events. With `dedup = signature` (default) a change is a new
+//!   (length, mtime_seconds) signature, which is cheap but misses same-length edits
+//!   within one second. With `dedup = hash` the file is read every poll and a change is
+//!   a new hash of the trimmed contents.
 //! - Empty / whitespace-only files are ignored.
-//! - Invalid JSON content is logged (warn) and retried on the next poll without deletion.
+//! - Invalid JSON content is logged (warn) and retried without deletion. Repeated failures
+//!   on an unchanged file back off: the retry delay doubles from the poll interval up to
+//!   `MAX_PARSE_BACKOFF`. A successful parse, or a change of the file as seen by the
+//!   dedup mode (a new signature, or a new content hash with `dedup = hash`), resets it.
 //!
 //! Cancellation / Exit:
 //! - The task ends early if the receiver side of the channel is closed (sending fails).
+//! - The task ends at the next poll once the shutdown token is cancelled; a file being
+//!   dispatched is finished first (including its deletion).
 //!
 //! Robustness:
 //! - All operational errors (I/O, JSON parse) are logged; the loop continues.
+//! - Outcomes (sent, parse/read errors, empty files, deletions) are counted in the
+//!   source's `SourceMetrics`.
 //! - Missing file is silent (to avoid log spam) until it appears.
 //!
 //! Possible future enhancements:
-//! - Hash content instead of (len, mtime) for more precise change detection.
 //! - Support batching if file contains a JSON array.
-//! - Exponential back-off for repeated parse failures.
 //!
 //! This module is intentionally independent and only relies on the public trait
 //! `EventSource` defined in `mod.rs`.
 
+use std::collections::hash_map::DefaultHasher;
 use std::fs;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
 use std::time::{Duration, SystemTime};
 
 use serde_json::Value;
@@ -33,11 +44,68 @@ use tokio::{
     fs as afs,
     sync::mpsc::Sender,
     task::JoinHandle,
-    time::{Instant, sleep},
+    time::{Instant, sleep_until},
 };
+use tokio_util::sync::CancellationToken;
 use tracing::{error, info, trace, warn};
 
-use super::EventSource;
+use super::{EventSource, SourceMetrics};
+use crate::config::models::FileDedup;
+
+/// Longest delay between retries of a file that keeps failing to parse.
+const MAX_PARSE_BACKOFF: Duration = Duration::from_secs(10);
+
+/// Version of a file that failed to parse, as identified by the dedup mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChangeKey {
+    Signature((u64, u64)),
+    Hash(u64),
+}
+
+/// Doubling retry delay for a file that keeps failing to parse.
+struct ParseBackoff {
+    interval: Duration,
+    failed: Option<ChangeKey>,
+    delay: Duration,
+    retry_at: Instant,
+}
+
+impl ParseBackoff {
+    fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            failed: None,
+            delay: interval,
+            retry_at: Instant::now(),
+        }
+    }
+
+    /// Whether to skip this poll: the failed version is unchanged and not yet due.
+    /// A different version resets the backoff.
+    fn waiting(&mut self, key: ChangeKey) -> bool {
+        match self.failed {
+            Some(failed) if failed == key => Instant::now() < self.retry_at,
+            Some(_) => {
+                self.reset();
+                false
+            }
+            None => false,
+        }
+    }
+
+    /// Record a parse failure of `key`; returns the delay before the next retry.
+    fn fail(&mut self, key: ChangeKey) -> Duration {
+        self.delay = (self.delay * 2).min(MAX_PARSE_BACKOFF);
+        self.retry_at = Instant::now() + self.delay;
+        self.failed = Some(key);
+        self.delay
+    }
+
+    fn reset(&mut self) {
+        self.failed = None;
+        self.delay = self.interval;
+    }
+}
 
 /// Source that polls a single file for JSON events.
 #[derive(Debug, Clone)]
@@ -45,6 +113,8 @@ pub struct FileSource {
     path: String,
     poll_ms: u64,
     delete_on_success: bool,
+    dedup: FileDedup,
+    metrics: Arc<SourceMetrics>,
 }
 
 impl FileSource {
@@ -59,9 +129,17 @@ impl FileSource {
             path,
             poll_ms: poll_ms.unwrap_or(100).max(10),
             delete_on_success: delete_on_success.unwrap_or(false),
+            dedup: FileDedup::Signature,
+            metrics: Arc::default(),
         }
     }
 
+    /// How unchanged files are recognized when not deleting (see module docs).
+    pub fn with_dedup(mut self, dedup: FileDedup) -> Self {
+        self.dedup = dedup;
+        self
+    }
+
     /// Internal helper to compute a coarse signature (length, mtime seconds).
     fn file_signature(meta: &fs::Metadata) -> (u64, u64) {
         let len = meta.len();
@@ -73,6 +151,13 @@ impl FileSource {
             .unwrap_or(0);
         (len, mtime)
     }
+
+    /// Hash of the (trimmed) file contents, for `FileDedup::Hash`.
+    fn content_hash(content: &str) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        content.hash(&mut hasher);
+        hasher.finish()
+    }
 }
 
 impl EventSource for FileSource {
@@ -80,27 +165,35 @@ impl EventSource for FileSource {
         "file"
     }
 
-    fn start(&self, sender: Sender<Value>) -> JoinHandle<()> {
+    fn metrics(&self) -> Option<Arc<SourceMetrics>> {
+        Some(self.metrics.clone())
+    }
+
+    fn start(&self, sender: Sender<Value>, shutdown: CancellationToken) -> JoinHandle<()> {
         let path = self.path.clone();
         let poll_ms = self.poll_ms;
         let delete_on_success = self.delete_on_success;
+        let dedup = self.dedup;
+        let metrics = self.metrics.clone();
 
         tokio::spawn(async move {
             info!(
                 target: "notabot::sources",
-                %path, poll_ms, delete_on_success,
+                %path, poll_ms, delete_on_success, ?dedup,
                 "FileSource task started"
             );
 
             let mut last_sig: Option<(u64, u64)> = None;
+            let mut last_hash: Option<u64> = None;
             let interval = Duration::from_millis(poll_ms);
             let mut next_tick = Instant::now();
+            let mut backoff = ParseBackoff::new(interval);
 
             loop {
                 // Poll timing (manual loop instead of interval for drift control)
-                let now = Instant::now();
-                if now < next_tick {
-                    sleep(next_tick - now).await;
+                tokio::select! {
+                    _ = shutdown.cancelled() => break,
+                    _ = sleep_until(next_tick) => {}
                 }
                 next_tick += interval;
 
@@ -123,7 +216,8 @@ impl EventSource for FileSource {
 
                 // Signature-based dedup (only for non-delete mode)
                 let sig = Self::file_signature(&meta);
-                if !delete_on_success && last_sig == Some(sig) {
+                let dedup_sig = !delete_on_success && dedup == FileDedup::Signature;
+                if dedup_sig && last_sig == Some(sig) {
                     trace!(
                         target: "notabot::sources",
                         %path,
@@ -132,6 +226,12 @@ impl EventSource for FileSource {
                     continue;
                 }
 
+                // Parse-failure backoff: wait unless the file changed since it failed
+                // (with hash dedup, the content is checked once it has been read)
+                if dedup == FileDedup::Signature && backoff.waiting(ChangeKey::Signature(sig)) {
+                    continue;
+                }
+
                 // Read file (async)
                 match afs::read_to_string(&path).await {
                     Ok(content) => {
@@ -142,10 +242,30 @@ impl EventSource for FileSource {
                                 %path,
                                 "File is empty/whitespace; ignoring"
                             );
+                            metrics.record_skipped();
+                            continue;
+                        }
+                        // Content-based dedup (only for non-delete mode)
+                        let hash = Self::content_hash(trimmed);
+                        let dedup_hash = !delete_on_success && dedup == FileDedup::Hash;
+                        if dedup_hash && last_hash == Some(hash) {
+                            trace!(
+                                target: "notabot::sources",
+                                %path,
+                                "File content unchanged; skipping"
+                            );
+                            continue;
+                        }
+                        let key = match dedup {
+                            FileDedup::Signature => ChangeKey::Signature(sig),
+                            FileDedup::Hash => ChangeKey::Hash(hash),
+                        };
+                        if dedup == FileDedup::Hash && backoff.waiting(key) {
                             continue;
                         }
                         match serde_json::from_str::<Value>(trimmed) {
                             Ok(value) => {
+                                backoff.reset();
                                 if let Err(e) = sender.send(value).await {
                                     error!(
                                         target: "notabot::sources",
@@ -154,6 +274,7 @@ impl EventSource for FileSource {
                                     );
                                     break;
                                 }
+                                metrics.record_event_sent();
 
                                 info!(
                                     target: "notabot::sources",
@@ -162,27 +283,36 @@ impl EventSource for FileSource {
                                 );
 
                                 if delete_on_success {
-                                    if let Err(e) = afs::remove_file(&path).await {
-                                        warn!(
-                                            target: "notabot::sources",
-                                            %path, error=%e,
-                                            "Failed to delete file after dispatch"
-                                        );
+                                    match afs::remove_file(&path).await {
+                                        Ok(()) => metrics.record_file_deleted(),
+                                        Err(e) => {
+                                            metrics.record_delete_error();
+                                            warn!(
+                                                target: "notabot::sources",
+                                                %path, error=%e,
+                                                "Failed to delete file after dispatch"
+                                            );
+                                        }
                                     }
                                 } else {
                                     last_sig = Some(sig);
+                                    last_hash = Some(hash);
                                 }
                             }
                             Err(e) => {
+                                metrics.record_parse_error();
+                                let retry_delay = backoff.fail(key);
                                 warn!(
                                     target: "notabot::sources",
                                     %path, error=%e,
+                                    retry_in_ms = retry_delay.as_millis() as u64,
                                     "Failed to parse JSON; will retry"
                                 );
                             }
                         }
                     }
                     Err(e) => {
+                        metrics.record_read_error();
                         warn!(
                             target: "notabot::sources",
                             %path, error=%e,
@@ -218,4 +348,81 @@ mod tests {
         );
         assert!(FileSource::new("y".into(), Some(1), None).poll_ms >= 10); // enforced minimum
     }
+
+    #[tokio::test]
+    async fn hash_dedup_detects_same_length_edits_within_a_second() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("event.json");
+        std::fs::write(&file, r#"{"v":1}"#).unwrap();
+
+        let src = FileSource::new(file.to_string_lossy().into_owned(), Some(10), None)
+            .with_dedup(FileDedup::Hash);
+        let (tx, mut rx) = tokio::sync::mpsc::channel(4);
+        let handle = src.start(tx, CancellationToken::new());
+        let next = async |rx: &mut tokio::sync::mpsc::Receiver<Value>| {
+            tokio::time::timeout(Duration::from_secs(5), rx.recv())
+                .await
+                .unwrap()
+                .unwrap()
+        };
+
+        assert_eq!(next(&mut rx).await["v"], 1);
+        // Same length, and (almost certainly) the same mtime second
+        std::fs::write(&file, r#"{"v":2}"#).unwrap();
+        assert_eq!(next(&mut rx).await["v"], 2);
+        // Unchanged contents are not dispatched again
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(rx.try_recv().is_err());
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn parse_failures_back_off_until_the_file_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("event.json");
+        std::fs::write(&file, r#"{"v": "#).unwrap();
+
+        let src = FileSource::new(file.to_string_lossy().into_owned(), Some(10), None);
+        let (tx, mut rx) = tokio::sync::mpsc::channel(4);
+        let handle = src.start(tx, CancellationToken::new());
+
+        // Polling every 10ms would fail ~30 times; backing off (20, 40, 80, 160ms) far less
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        let failures = src.metrics().unwrap().snapshot().parse_errors;
+        assert!((1..=6).contains(&failures), "{failures} parse failures");
+
+        // Fixing the file changes its signature, so it is picked up without waiting
+        std::fs::write(&file, r#"{"v": 1, "fixed": true}"#).unwrap();
+        let event = tokio::time::timeout(Duration::from_millis(150), rx.recv())
+            .await
+            .expect("a changed file skips the backoff")
+            .unwrap();
+        assert_eq!(event["fixed"], true);
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn hash_dedup_resets_the_backoff_on_same_length_fixes() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("event.json");
+        std::fs::write(&file, r#"{"v": 1,"#).unwrap();
+
+        let src = FileSource::new(file.to_string_lossy().into_owned(), Some(10), None)
+            .with_dedup(FileDedup::Hash);
+        let (tx, mut rx) = tokio::sync::mpsc::channel(4);
+        let handle = src.start(tx, CancellationToken::new());
+
+        // Let the delay grow well past the poll interval
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert!(src.metrics().unwrap().snapshot().parse_errors >= 1);
+
+        // Same length, and (almost certainly) the same mtime second: only the hash changed
+        std::fs::write(&file, r#"{"v": 2}"#).unwrap();
+        let event = tokio::time::timeout(Duration::from_millis(150), rx.recv())
+            .await
+            .expect("a changed hash skips the backoff")
+            .unwrap();
+        assert_eq!(event["v"], 2);
+        handle.abort();
+    }
 }
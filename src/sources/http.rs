@@ -0,0 +1,198 @@
+use axum::{
+    Json, Router,
+    body::Bytes,
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::post,
+};
+use serde_json::{Value, json};
+use tokio::{net::TcpListener, sync::mpsc::Sender, task::JoinHandle};
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, trace, warn};
+
+use super::{EventSource, send_events};
+
+/// HTTP webhook event source (`http` cargo feature).
+///
+/// Protocol:
+/// - `POST <path>` with a JSON body (one value, typically an object) queues it as an event.
+/// - Replies `200 {"status":"ok"}` once the event is queued, or
+///   `400 {"status":"error","error":"..."}` if the body is not valid JSON.
+/// - With `batch = true`, a body holding a JSON array is split into one event per element
+///   (in order) and still gets a single `200`. Other values are sent unchanged.
+/// - Other paths answer `404` and other methods on `path` answer `405`.
+///
+/// Behavior & Robustness:
+/// - Requests are served concurrently; channel backpressure is respected
+///   (`sender.send(...).await`), so a slow runtime delays the responses.
+/// - If the event channel is closed, requests get `503` and the event is dropped.
+/// - Cancelling the shutdown token stops the server gracefully: in-flight requests
+///   complete, new connections are refused.
+///
+/// Security Notes:
+/// - No authentication and no TLS (intended for local / trusted network use, or behind
+///   a reverse proxy that handles both).
+#[derive(Debug, Clone)]
+pub struct HttpSource {
+    bind: String,
+    path: String,
+    batch: bool,
+}
+
+/// Shared state of the request handler.
+#[derive(Clone)]
+struct HandlerState {
+    sender: Sender<Value>,
+    batch: bool,
+}
+
+impl HttpSource {
+    /// Create a new HTTP source.
+    ///
+    /// * `bind` - Address (e.g. "127.0.0.1:8080")
+    /// * `path` - Route accepting POSTed events (e.g. "/events")
+    pub fn new(bind: impl Into<String>, path: impl Into<String>) -> Self {
+        Self {
+            bind: bind.into(),
+            path: path.into(),
+            batch: false,
+        }
+    }
+
+    /// Split JSON array bodies into one event per element (see "Protocol" above).
+    pub fn with_batch(mut self, batch: bool) -> Self {
+        self.batch = batch;
+        self
+    }
+
+    fn router(&self, sender: Sender<Value>) -> Router {
+        Router::new()
+            .route(&self.path, post(handle_post))
+            .with_state(HandlerState {
+                sender,
+                batch: self.batch,
+            })
+    }
+}
+
+/// Parse and queue one POSTed body.
+async fn handle_post(State(state): State<HandlerState>, body: Bytes) -> Response {
+    let val = match serde_json::from_slice::<Value>(&body) {
+        Ok(val) => val,
+        Err(e) => {
+            warn!(target: "notabot::sources", error = %e, "Invalid JSON in HTTP request body");
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "status": "error", "error": e.to_string() })),
+            )
+                .into_response();
+        }
+    };
+
+    trace!(target: "notabot::sources", "Parsed JSON from HTTP request");
+    if let Err(e) = send_events(&state.sender, val, state.batch).await {
+        error!(
+            target: "notabot::sources",
+            error = %e,
+            "Channel closed while sending HTTP event"
+        );
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({ "status": "error", "error": "event channel closed" })),
+        )
+            .into_response();
+    }
+    Json(json!({ "status": "ok" })).into_response()
+}
+
+impl EventSource for HttpSource {
+    fn name(&self) -> &'static str {
+        "http"
+    }
+
+    fn start(&self, sender: Sender<Value>, shutdown: CancellationToken) -> JoinHandle<()> {
+        let bind = self.bind.clone();
+        let path = self.path.clone();
+        let router = self.router(sender);
+        let batch = self.batch;
+        tokio::spawn(async move {
+            info!(target: "notabot::sources", %bind, %path, batch, "HttpSource listener starting");
+
+            let listener = match TcpListener::bind(&bind).await {
+                Ok(l) => l,
+                Err(e) => {
+                    error!(
+                        target: "notabot::sources",
+                        %bind,
+                        error = %e,
+                        "Failed to bind HTTP listener (terminating task)"
+                    );
+                    return;
+                }
+            };
+
+            let server =
+                axum::serve(listener, router).with_graceful_shutdown(shutdown.cancelled_owned());
+            if let Err(e) = server.await {
+                error!(
+                    target: "notabot::sources",
+                    %bind,
+                    error = %e,
+                    "HTTP server failed (terminating task)"
+                );
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+    use tokio::sync::mpsc;
+
+    /// POST `body` to `path` and return the raw HTTP response.
+    async fn post(addr: std::net::SocketAddr, path: &str, body: &str) -> String {
+        let mut client = TcpStream::connect(addr).await.unwrap();
+        let request = format!(
+            "POST {path} HTTP/1.1\r\nHost: {addr}\r\nContent-Type: application/json\r\n\
+             Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        );
+        client.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        client.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn test_posted_events_are_forwarded() {
+        let (tx, mut rx) = mpsc::channel::<Value>(8);
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let router = HttpSource::new(addr.to_string(), "/events")
+            .with_batch(true)
+            .router(tx);
+        let server = tokio::spawn(async move { axum::serve(listener, router).await });
+
+        let ok = post(addr, "/events", r#"{"type":"hook","n":1}"#).await;
+        assert!(ok.starts_with("HTTP/1.1 200"), "{ok}");
+        assert!(ok.ends_with(r#"{"status":"ok"}"#), "{ok}");
+        assert_eq!(rx.recv().await.unwrap()["type"], "hook");
+
+        let batch = post(addr, "/events", r#"[{"type":"a"},{"type":"b"}]"#).await;
+        assert!(batch.starts_with("HTTP/1.1 200"), "{batch}");
+        assert_eq!(rx.recv().await.unwrap()["type"], "a");
+        assert_eq!(rx.recv().await.unwrap()["type"], "b");
+
+        let bad = post(addr, "/events", "{ not json").await;
+        assert!(bad.starts_with("HTTP/1.1 400"), "{bad}");
+        let missing = post(addr, "/other", "{}").await;
+        assert!(missing.starts_with("HTTP/1.1 404"), "{missing}");
+        assert!(rx.try_recv().is_err());
+
+        server.abort();
+    }
+}
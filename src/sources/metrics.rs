@@ -0,0 +1,102 @@
+//! Per-source outcome counters.
+//!
+//! A source that tracks its outcomes owns an `Arc<SourceMetrics>`, increments it from
+//! its task and hands out clones through `EventSource::metrics`. Counters are relaxed
+//! atomics: cheap to bump, and a `snapshot` is a consistent-enough view for logging.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Counters updated by a running source.
+#[derive(Debug, Default)]
+pub struct SourceMetrics {
+    events_sent: AtomicU64,
+    parse_errors: AtomicU64,
+    read_errors: AtomicU64,
+    skipped: AtomicU64,
+    files_deleted: AtomicU64,
+    delete_errors: AtomicU64,
+}
+
+/// Point-in-time copy of a `SourceMetrics`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SourceMetricsSnapshot {
+    /// Events pushed into the channel.
+    pub events_sent: u64,
+    /// Inputs that were not valid JSON.
+    pub parse_errors: u64,
+    /// Failed reads (I/O errors).
+    pub read_errors: u64,
+    /// Inputs passed over without an event (empty, or not yet stable).
+    pub skipped: u64,
+    /// Files deleted after dispatch.
+    pub files_deleted: u64,
+    /// Files that could not be deleted after dispatch.
+    pub delete_errors: u64,
+}
+
+impl SourceMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record_event_sent(&self) {
+        self.events_sent.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_parse_error(&self) {
+        self.parse_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_read_error(&self) {
+        self.read_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_skipped(&self) {
+        self.skipped.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_file_deleted(&self) {
+        self.files_deleted.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_delete_error(&self) {
+        self.delete_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Current counter values.
+    pub fn snapshot(&self) -> SourceMetricsSnapshot {
+        SourceMetricsSnapshot {
+            events_sent: self.events_sent.load(Ordering::Relaxed),
+            parse_errors: self.parse_errors.load(Ordering::Relaxed),
+            read_errors: self.read_errors.load(Ordering::Relaxed),
+            skipped: self.skipped.load(Ordering::Relaxed),
+            files_deleted: self.files_deleted.load(Ordering::Relaxed),
+            delete_errors: self.delete_errors.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshot_reflects_recorded_outcomes() {
+        let m = SourceMetrics::new();
+        m.record_event_sent();
+        m.record_event_sent();
+        m.record_parse_error();
+        m.record_skipped();
+        m.record_file_deleted();
+        assert_eq!(
+            m.snapshot(),
+            SourceMetricsSnapshot {
+                events_sent: 2,
+                parse_errors: 1,
+                skipped: 1,
+                files_deleted: 1,
+                ..Default::default()
+            }
+        );
+    }
+}
@@ -6,15 +6,21 @@ This module only defines the core `EventSource` trait and orchestration helpers
 live in their own files:
 
 - `file.rs`      -> `FileSource`     (poll a single JSON file)
-- `directory.rs` -> `DirectorySource` (poll / (future) watch a directory of JSON files)
+- `directory.rs` -> `DirectorySource` (poll or watch a directory of JSON files)
 - `tcp.rs`       -> `TcpSource`      (newline-delimited JSON over TCP)
+- `udp.rs`       -> `UdpSource`      (one JSON value per UDP datagram)
+- `http.rs`      -> `HttpSource`     (POSTed JSON webhooks; `http` feature)
+- `websocket.rs` -> `WebSocketSource` (JSON text frames from a WebSocket server; `websocket` feature)
 - `stdin_source.rs` -> `StdinSource`    (newline-delimited JSON from standard input)
+- `transform.rs` -> `TransformedSource` (decorator applying a per-source `transform`)
+- `metrics.rs`   -> `SourceMetrics`  (outcome counters a source may expose)
 
 Each source implementation is responsible for:
 - Parsing raw input into `serde_json::Value`
 - Pushing events via `Sender<Value>` while respecting backpressure (`send().await`)
 - Logging errors and continuing (never panicking inside tasks)
 - Being cancellation-safe (task ends cleanly when channel closes / loop breaks)
+- Ending promptly once its shutdown `CancellationToken` is cancelled
 
 Adding a new source:
 1. Create `src/sources/your_source.rs`
@@ -26,21 +32,39 @@ This keeps `mod.rs` lean and focused on wiring, making each source simpler to
 maintain and test in isolation.
 */
 
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
 use serde_json::Value;
 use tokio::{sync::mpsc::Sender, task::JoinHandle};
-use tracing::info;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, trace};
 
 use crate::config::{Config, SourceConfig};
 
 pub mod directory;
 pub mod file;
+#[cfg(feature = "http")]
+pub mod http;
+pub mod metrics;
 pub mod stdin_source;
 pub mod tcp;
+pub mod transform;
+pub mod udp;
+#[cfg(feature = "websocket")]
+pub mod websocket;
 
-pub use directory::DirectorySource;
+pub use directory::{DirectorySource, FileFilter};
 pub use file::FileSource;
+#[cfg(feature = "http")]
+pub use http::HttpSource;
+pub use metrics::{SourceMetrics, SourceMetricsSnapshot};
 pub use stdin_source::StdinSource;
 pub use tcp::TcpSource;
+pub use transform::{EventTransform, TransformedSource};
+pub use udp::UdpSource;
+#[cfg(feature = "websocket")]
+pub use websocket::WebSocketSource;
 
 /// Trait implemented by all event sources.
 ///
@@ -58,7 +82,14 @@ pub trait EventSource: Send + Sync {
     /// - spawn a `tokio::task`
     /// - loop, producing events
     /// - exit when channel is closed or unrecoverable error occurs
-    fn start(&self, sender: Sender<Value>) -> JoinHandle<()>;
+    /// - exit when `shutdown` is cancelled, checked wherever the task waits for input
+    ///   (an event being handled is finished first)
+    fn start(&self, sender: Sender<Value>, shutdown: CancellationToken) -> JoinHandle<()>;
+
+    /// Outcome counters of this source, if it keeps any (default: none).
+    fn metrics(&self) -> Option<Arc<SourceMetrics>> {
+        None
+    }
 }
 
 /// Construct all configured sources.
@@ -66,54 +97,152 @@ pub trait EventSource: Send + Sync {
 /// Notes:
 /// - If a `stdin` source is present it will be added (only one usually makes sense).
 /// - Order of sources in the returned vector is the same as in the config.
-pub fn build_sources_from_config(cfg: &Config) -> Vec<Box<dyn EventSource>> {
+/// - Sources with a `transform` are wrapped in a `TransformedSource`; an invalid
+///   transform fails the whole build with an error naming the source.
+pub fn build_sources_from_config(cfg: &Config) -> Result<Vec<Box<dyn EventSource>>> {
     let mut out: Vec<Box<dyn EventSource>> = Vec::new();
 
-    for sc in &cfg.sources {
-        match sc {
+    for (idx, sc) in cfg.sources.iter().enumerate() {
+        let source: Box<dyn EventSource> = match sc {
             SourceConfig::File {
                 path,
                 poll_ms,
                 delete_on_success,
-            } => out.push(Box::new(FileSource::new(
-                path.clone(),
-                *poll_ms,
-                *delete_on_success,
-            ))),
+                dedup,
+                ..
+            } => Box::new(
+                FileSource::new(path.clone(), *poll_ms, *delete_on_success).with_dedup(*dedup),
+            ),
 
             SourceConfig::Directory {
                 path,
                 pattern,
+                include,
+                exclude,
                 recursive,
-            } => out.push(Box::new(DirectorySource::new(
-                path.clone(),
-                pattern.clone(),
-                recursive.unwrap_or(false),
-            ))),
-
-            SourceConfig::Tcp { bind, ack } => {
-                out.push(Box::new(TcpSource::new(bind.clone(), ack.unwrap_or(true))));
+                poll_ms,
+                require_stable_ms,
+                watch,
+                ..
+            } => {
+                let includes = pattern
+                    .iter()
+                    .chain(include.iter().flatten())
+                    .cloned()
+                    .collect();
+                let filter = FileFilter::new(includes, exclude.clone().unwrap_or_default());
+                Box::new(
+                    DirectorySource::new(
+                        path.clone(),
+                        filter,
+                        recursive.unwrap_or(false),
+                        *poll_ms,
+                    )
+                    .with_require_stable_ms(*require_stable_ms)
+                    .with_watch(watch.unwrap_or(false)),
+                )
             }
 
-            SourceConfig::Stdin => {
-                out.push(Box::new(StdinSource::new()));
+            SourceConfig::Tcp {
+                bind,
+                ack,
+                batch,
+                framing,
+                token,
+                tls,
+                ..
+            } => {
+                let mut source = TcpSource::new(bind.clone(), ack.unwrap_or(true))
+                    .with_batch(batch.unwrap_or(false))
+                    .with_framing(*framing)
+                    .with_token(token.clone());
+                if let Some(tls) = tls {
+                    source = source.with_tls(tls).with_context(|| {
+                        format!("Invalid TLS settings for source #{idx} (tcp {bind})")
+                    })?;
+                }
+                Box::new(source)
+            }
+
+            SourceConfig::Udp { bind, .. } => Box::new(UdpSource::new(bind.clone())),
+
+            #[cfg(feature = "http")]
+            SourceConfig::Http {
+                bind, path, batch, ..
+            } => Box::new(
+                HttpSource::new(bind.clone(), path.clone()).with_batch(batch.unwrap_or(false)),
+            ),
+            #[cfg(not(feature = "http"))]
+            SourceConfig::Http { bind, .. } => {
+                anyhow::bail!("Source #{idx} (http {bind}) requires the `http` feature")
             }
+
+            #[cfg(feature = "websocket")]
+            SourceConfig::WebSocket { url, reconnect, .. } => Box::new(
+                WebSocketSource::new(url.clone()).with_reconnect(reconnect.unwrap_or(true)),
+            ),
+            #[cfg(not(feature = "websocket"))]
+            SourceConfig::WebSocket { url, .. } => {
+                anyhow::bail!("Source #{idx} (websocket {url}) requires the `websocket` feature")
+            }
+
+            SourceConfig::Stdin { .. } => Box::new(StdinSource::new()),
+        };
+
+        match sc.transform() {
+            Some(spec) => {
+                let transform = EventTransform::new(spec).with_context(|| {
+                    format!("Invalid transform for source #{idx} ({})", source.name())
+                })?;
+                out.push(Box::new(TransformedSource::new(source, transform)));
+            }
+            None => out.push(source),
         }
     }
 
-    out
+    Ok(out)
 }
 
-/// Spawn every source, returning their `JoinHandle`s.
+/// Send `val`, or each of its elements when batching and `val` is an array.
+pub(crate) async fn send_events(
+    sender: &Sender<Value>,
+    val: Value,
+    batch: bool,
+) -> Result<(), tokio::sync::mpsc::error::SendError<Value>> {
+    match val {
+        Value::Array(items) if batch => {
+            trace!(target: "notabot::sources", size = items.len(), "Splitting batch");
+            for item in items {
+                sender.send(item).await?;
+            }
+            Ok(())
+        }
+        val => sender.send(val).await,
+    }
+}
+
+/// Metrics handles of the sources that keep counters, with their names.
+///
+/// The handles stay live while the sources run, so callers can snapshot them
+/// periodically (e.g. to log a summary).
+pub fn source_metrics(sources: &[Box<dyn EventSource>]) -> Vec<(&'static str, Arc<SourceMetrics>)> {
+    sources
+        .iter()
+        .filter_map(|src| Some((src.name(), src.metrics()?)))
+        .collect()
+}
+
+/// Spawn every source, returning their `JoinHandle`s and the shutdown token they share.
 ///
-/// The caller may store these if it wishes to monitor or await their termination.
-/// Typically the application just keeps them detached and relies on process
-/// lifetime / Ctrl+C for shutdown.
+/// Cancelling the token stops every source; awaiting the handles afterwards waits
+/// for them to finish. The caller may also keep the handles detached and rely on
+/// process lifetime / Ctrl+C for shutdown.
 pub fn spawn_all_sources(
     sources: &[Box<dyn EventSource>],
     sender: Sender<Value>,
-) -> Vec<JoinHandle<()>> {
-    sources
+) -> (Vec<JoinHandle<()>>, CancellationToken) {
+    let shutdown = CancellationToken::new();
+    let handles = sources
         .iter()
         .map(|src| {
             info!(
@@ -121,7 +250,8 @@ pub fn spawn_all_sources(
                 source = %src.name(),
                 "Starting source task"
             );
-            src.start(sender.clone())
+            src.start(sender.clone(), shutdown.clone())
         })
-        .collect()
+        .collect();
+    (handles, shutdown)
 }
@@ -7,10 +7,11 @@
 //! - Successfully parsed JSON values (any JSON type) are forwarded through the event channel.
 //! - Malformed JSON lines are logged with `warn!` and ignored; reading continues.
 //! - End Of File (EOF) or a channel send error (receiver dropped) terminates the task gracefully.
+//! - Cancelling the shutdown token ends the task while it waits for the next line.
 //!
 //! Rationale:
 //! - This source is useful for simple shell pipelines, e.g.:
-//!     echo '{"type":"send_text","text":"Hello"}' | notabot --config config/default.json
+//!   echo '{"type":"send_text","text":"Hello"}' | notabot --config config/default.json
 //! - Backpressure is naturally respected via `sender.send(value).await`.
 //!
 //! Potential Enhancements:
@@ -34,6 +35,7 @@ use tokio::{
     sync::mpsc::Sender,
     task::JoinHandle,
 };
+use tokio_util::sync::CancellationToken;
 use tracing::{error, info, trace, warn};
 
 use super::EventSource;
@@ -50,12 +52,18 @@ impl StdinSource {
     }
 }
 
+impl Default for StdinSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl EventSource for StdinSource {
     fn name(&self) -> &'static str {
         "stdin"
     }
 
-    fn start(&self, sender: Sender<Value>) -> JoinHandle<()> {
+    fn start(&self, sender: Sender<Value>, shutdown: CancellationToken) -> JoinHandle<()> {
         tokio::spawn(async move {
             info!(target: "notabot::sources", "StdinSource task started (reading lines)");
             let stdin = io::stdin();
@@ -64,7 +72,14 @@ impl EventSource for StdinSource {
 
             loop {
                 line.clear();
-                match reader.read_line(&mut line).await {
+                let read = tokio::select! {
+                    _ = shutdown.cancelled() => {
+                        info!(target: "notabot::sources", "Shutdown requested; StdinSource exiting");
+                        break;
+                    }
+                    read = reader.read_line(&mut line) => read,
+                };
+                match read {
                     Ok(0) => {
                         // EOF
                         info!(target: "notabot::sources", "EOF on stdin; StdinSource exiting");
@@ -133,7 +148,7 @@ mod tests {
     async fn test_spawn_returns_handle() {
         let (tx, mut rx) = mpsc::channel::<Value>(1);
         let src = StdinSource::new();
-        let handle = src.start(tx);
+        let handle = src.start(tx, CancellationToken::new());
         // We can't feed stdin easily here; just cancel quickly.
         handle.abort();
         // Channel unused; ensure receiver not closed implicitly yet.
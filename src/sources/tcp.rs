@@ -1,15 +1,25 @@
-use std::net::SocketAddr;
+use std::{io, net::SocketAddr, sync::Arc};
 
+use anyhow::Result;
 use serde_json::Value;
 use tokio::{
-    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader},
     net::{TcpListener, TcpStream},
     sync::mpsc::Sender,
     task::JoinHandle,
 };
+use tokio_util::sync::CancellationToken;
 use tracing::{error, info, trace, warn};
 
-use super::EventSource;
+use super::{EventSource, send_events};
+use crate::config::models::{TcpFraming, TlsConfig};
+
+/// Largest accepted `length_prefixed` payload; a bigger header closes the connection.
+const MAX_FRAME_LEN: u32 = 16 * 1024 * 1024;
+
+/// Outcome of reading one message into the buffer: `None` at end of stream, otherwise
+/// whether the message could be decoded (a failed frame does not desync the stream).
+type ReadMessage = io::Result<Option<Result<(), std::string::FromUtf8Error>>>;
 
 /// TCP-based event source.
 ///
@@ -17,30 +27,77 @@ use super::EventSource;
 /// - Each inbound connection may send one or more newline-delimited JSON values (NDJSON style).
 /// - Each non-empty line is trimmed and parsed as JSON (must be a single JSON value, typically an object).
 /// - If `ack = true`, the server replies with `OK\n` on success, or `ERROR <message>\n` on parse failure.
+/// - With `framing = length_prefixed`, each message is instead a 4-byte big-endian length
+///   followed by exactly that many bytes of UTF-8 JSON, so payloads may contain newlines
+///   (e.g. pretty-printed). ACKs are still `OK\n` / `ERROR ...\n` lines. A length above
+///   `MAX_FRAME_LEN` gets an error ACK and closes the connection.
+/// - With `batch = true`, a line holding a JSON array is split into one event per element
+///   (in order). The line still gets a single `OK` once every element has been queued, so
+///   clients keep a one-reply-per-line protocol. Other values are sent unchanged.
+///
+/// Authentication:
+/// - With `with_token`, the first message of a connection must be `AUTH <token>` (framed
+///   like any other message). The server answers `OK` and then processes messages as
+///   above, or answers `ERROR unauthorized` and closes the connection. Nothing a client
+///   sends before authenticating reaches the event channel.
 ///
 /// Behavior & Robustness:
 /// - Connections are handled concurrently (one task per connection).
 /// - Malformed JSON lines are logged with `warn!` and (optionally) receive an error ACK; the connection stays open.
 /// - Channel backpressure is respected (`sender.send(...).await`).
 /// - If the event channel is closed, the per-connection task terminates early.
-/// - The listener task runs indefinitely until the process is shut down (no explicit stop signal implemented).
+/// - Cancelling the shutdown token stops accepting and closes every connection once it
+///   waits for its next message (a message being handled still gets its ACK).
+///
+/// TLS (`tls` cargo feature):
+/// - With `with_tls`, every accepted connection completes a TLS handshake first; the
+///   protocol above then runs unchanged over the encrypted stream. Failed handshakes are
+///   logged and the connection is dropped.
+/// - The certificate and key are loaded when the source is built, so a bad path or PEM
+///   file fails startup instead of the first connection.
 ///
 /// Security / Hardening Notes:
-/// - No authentication is performed (intended for local / trusted network use).
+/// - Without a token no authentication is performed (intended for local / trusted
+///   network use). The token is sent in clear text unless TLS is enabled.
 /// - For production / untrusted networks, consider:
-///     * TLS / mTLS
+///     * TLS (`tls` feature) / mTLS
 ///     * Length limiting
 ///     * Rate limiting
 ///     * JSON schema validation at the source boundary
 ///
 /// Future Enhancements:
-/// - Support for batched JSON arrays splitting into multiple events.
-/// - Optional framing (length-prefix) for binary-safe transport.
 /// - Metrics (accepted connections, messages processed, errors).
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct TcpSource {
     bind: String,
     ack: bool,
+    batch: bool,
+    framing: TcpFraming,
+    token: Option<Arc<str>>,
+    #[cfg(feature = "tls")]
+    tls: Option<tokio_rustls::TlsAcceptor>,
+}
+
+impl std::fmt::Debug for TcpSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TcpSource")
+            .field("bind", &self.bind)
+            .field("ack", &self.ack)
+            .field("batch", &self.batch)
+            .field("framing", &self.framing)
+            .field("token", &self.token.as_ref().map(|_| "<redacted>"))
+            .field("tls", &self.uses_tls())
+            .finish()
+    }
+}
+
+/// Per-connection settings copied from the `TcpSource`.
+#[derive(Debug, Clone)]
+struct ClientOptions {
+    ack: bool,
+    batch: bool,
+    framing: TcpFraming,
+    token: Option<Arc<str>>,
 }
 
 impl TcpSource {
@@ -49,11 +106,75 @@ impl TcpSource {
     /// `bind` is the socket address to listen on (e.g. "127.0.0.1:5000").
     /// `ack` controls whether "OK"/"ERROR ..." lines are written back to clients.
     pub fn new(bind: String, ack: bool) -> Self {
-        Self { bind, ack }
+        Self {
+            bind,
+            ack,
+            batch: false,
+            framing: TcpFraming::Lines,
+            token: None,
+            #[cfg(feature = "tls")]
+            tls: None,
+        }
+    }
+
+    /// Serve TLS with the certificate and key in `tls` (see "TLS" above).
+    /// Fails if the files cannot be read or parsed, or without the `tls` feature.
+    #[cfg(feature = "tls")]
+    pub fn with_tls(mut self, tls: &TlsConfig) -> Result<Self> {
+        self.tls = Some(tls_acceptor::load(tls)?);
+        Ok(self)
+    }
+
+    #[cfg(not(feature = "tls"))]
+    pub fn with_tls(self, tls: &TlsConfig) -> Result<Self> {
+        anyhow::bail!(
+            "TLS for tcp sources requires the `tls` feature (cert_path: {})",
+            tls.cert_path
+        )
+    }
+
+    fn uses_tls(&self) -> bool {
+        #[cfg(feature = "tls")]
+        return self.tls.is_some();
+        #[cfg(not(feature = "tls"))]
+        false
+    }
+
+    /// How messages are delimited on the wire (see "Protocol" above).
+    pub fn with_framing(mut self, framing: TcpFraming) -> Self {
+        self.framing = framing;
+        self
+    }
+
+    /// Require `AUTH <token>` as the first message of every connection
+    /// (see "Authentication" above). `None` disables authentication.
+    pub fn with_token(mut self, token: Option<String>) -> Self {
+        self.token = token.map(Arc::from);
+        self
+    }
+
+    /// Split JSON array lines into one event per element (see "Protocol" above).
+    pub fn with_batch(mut self, batch: bool) -> Self {
+        self.batch = batch;
+        self
+    }
+
+    fn client_options(&self) -> ClientOptions {
+        ClientOptions {
+            ack: self.ack,
+            batch: self.batch,
+            framing: self.framing,
+            token: self.token.clone(),
+        }
     }
 
     /// Spawn a task to handle a single accepted client connection.
-    async fn handle_client(mut stream: TcpStream, sender: Sender<Value>, ack: bool) {
+    async fn handle_client(
+        stream: TcpStream,
+        sender: Sender<Value>,
+        opts: ClientOptions,
+        shutdown: CancellationToken,
+    ) {
         let peer: SocketAddr = match stream.peer_addr() {
             Ok(a) => a,
             Err(e) => {
@@ -61,29 +182,101 @@ impl TcpSource {
                 return;
             }
         };
+        Self::serve_client(stream, peer, sender, opts, shutdown).await;
+    }
 
-        let (read_half, mut write_half) = stream.split();
+    /// Complete the TLS handshake, then serve the connection like `handle_client`.
+    #[cfg(feature = "tls")]
+    async fn handle_tls_client(
+        stream: TcpStream,
+        acceptor: tokio_rustls::TlsAcceptor,
+        sender: Sender<Value>,
+        opts: ClientOptions,
+        shutdown: CancellationToken,
+    ) {
+        let peer: SocketAddr = match stream.peer_addr() {
+            Ok(a) => a,
+            Err(e) => {
+                warn!(target: "notabot::sources", error = %e, "Could not get peer address");
+                return;
+            }
+        };
+        match acceptor.accept(stream).await {
+            Ok(tls_stream) => Self::serve_client(tls_stream, peer, sender, opts, shutdown).await,
+            Err(e) => {
+                warn!(target: "notabot::sources", peer = %peer, error = %e, "TLS handshake failed");
+            }
+        }
+    }
+
+    /// Read messages from an established (plain or TLS) connection until it closes.
+    async fn serve_client<S: AsyncRead + AsyncWrite>(
+        stream: S,
+        peer: SocketAddr,
+        sender: Sender<Value>,
+        opts: ClientOptions,
+        shutdown: CancellationToken,
+    ) {
+        let ClientOptions {
+            ack,
+            batch,
+            framing,
+            token,
+        } = opts;
+        let (read_half, mut write_half) = tokio::io::split(stream);
         let mut reader = BufReader::new(read_half);
-        let mut line = String::new();
+        let mut buf = String::new();
 
-        trace!(target: "notabot::sources", peer = %peer, "TCP client handler started");
+        trace!(target: "notabot::sources", peer = %peer, ?framing, "TCP client handler started");
+
+        if let Some(token) = token {
+            let read = tokio::select! {
+                _ = shutdown.cancelled() => return,
+                read = read_message(&mut reader, framing, &mut buf) => read,
+            };
+            let authorized = matches!(read, Ok(Some(Ok(())))) && is_valid_auth(buf.trim(), &token);
+            if !authorized {
+                warn!(target: "notabot::sources", peer = %peer, "TCP client failed to authenticate");
+                let _ = write_half.write_all(b"ERROR unauthorized\n").await;
+                return;
+            }
+            if write_half.write_all(b"OK\n").await.is_err() {
+                return;
+            }
+            trace!(target: "notabot::sources", peer = %peer, "TCP client authenticated");
+        }
 
         loop {
-            line.clear();
-            match reader.read_line(&mut line).await {
-                Ok(0) => {
+            let read = tokio::select! {
+                _ = shutdown.cancelled() => {
+                    trace!(target: "notabot::sources", peer = %peer, "Shutdown requested; closing TCP client");
+                    break;
+                }
+                read = read_message(&mut reader, framing, &mut buf) => read,
+            };
+            match read {
+                Ok(None) => {
                     trace!(target: "notabot::sources", peer = %peer, "TCP client closed connection");
                     break;
                 }
-                Ok(_) => {
-                    let raw = line.trim();
+                Ok(Some(Err(e))) => {
+                    // Undecodable frame (e.g. invalid UTF-8); the stream is still in sync
+                    warn!(target: "notabot::sources", peer = %peer, error = %e, "Invalid TCP frame");
+                    if ack {
+                        let _ = write_half
+                            .write_all(format!("ERROR {e}\n").as_bytes())
+                            .await;
+                    }
+                }
+                Ok(Some(Ok(()))) => {
+                    let raw = buf.trim();
                     if raw.is_empty() {
                         continue;
                     }
 
                     match serde_json::from_str::<Value>(raw) {
                         Ok(val) => {
-                            if let Err(e) = sender.send(val).await {
+                            if let Err(e) = send_events(&sender, val, batch).await {
                                 error!(
                                     target: "notabot::sources",
                                     peer = %peer,
@@ -92,16 +285,14 @@ impl TcpSource {
                                 );
                                 break;
                             }
-                            if ack {
-                                if let Err(e) = write_half.write_all(b"OK\n").await {
-                                    warn!(
-                                        target: "notabot::sources",
-                                        peer = %peer,
-                                        error = %e,
-                                        "Failed to write OK ACK; closing connection"
-                                    );
-                                    break;
-                                }
+                            if ack && let Err(e) = write_half.write_all(b"OK\n").await {
+                                warn!(
+                                    target: "notabot::sources",
+                                    peer = %peer,
+                                    error = %e,
+                                    "Failed to write OK ACK; closing connection"
+                                );
+                                break;
                             }
                         }
                         Err(e) => {
@@ -128,6 +319,11 @@ impl TcpSource {
                         error = %e,
                         "Error reading from TCP client"
                     );
+                    if ack && e.kind() == io::ErrorKind::InvalidData {
+                        let _ = write_half
+                            .write_all(format!("ERROR {e}\n").as_bytes())
+                            .await;
+                    }
                     break;
                 }
             }
@@ -142,13 +338,17 @@ impl EventSource for TcpSource {
         "tcp"
     }
 
-    fn start(&self, sender: Sender<Value>) -> JoinHandle<()> {
+    fn start(&self, sender: Sender<Value>, shutdown: CancellationToken) -> JoinHandle<()> {
         let bind = self.bind.clone();
-        let ack = self.ack;
+        let opts = self.client_options();
+        let tls = self.uses_tls();
+        #[cfg(feature = "tls")]
+        let acceptor = self.tls.clone();
         tokio::spawn(async move {
             info!(
                 target: "notabot::sources",
-                %bind, ack,
+                %bind, ack = opts.ack, batch = opts.batch, framing = ?opts.framing, tls,
+                auth = opts.token.is_some(),
                 "TcpSource listener starting"
             );
 
@@ -166,7 +366,11 @@ impl EventSource for TcpSource {
             };
 
             loop {
-                match listener.accept().await {
+                let accepted = tokio::select! {
+                    _ = shutdown.cancelled() => break,
+                    accepted = listener.accept() => accepted,
+                };
+                match accepted {
                     Ok((stream, addr)) => {
                         trace!(
                             target: "notabot::sources",
@@ -175,7 +379,23 @@ impl EventSource for TcpSource {
                             "Accepted TCP connection"
                         );
                         let s = sender.clone();
-                        tokio::spawn(Self::handle_client(stream, s, ack));
+                        #[cfg(feature = "tls")]
+                        if let Some(acceptor) = acceptor.clone() {
+                            tokio::spawn(Self::handle_tls_client(
+                                stream,
+                                acceptor,
+                                s,
+                                opts.clone(),
+                                shutdown.clone(),
+                            ));
+                            continue;
+                        }
+                        tokio::spawn(Self::handle_client(
+                            stream,
+                            s,
+                            opts.clone(),
+                            shutdown.clone(),
+                        ));
                     }
                     Err(e) => {
                         warn!(
@@ -189,10 +409,107 @@ impl EventSource for TcpSource {
                     }
                 }
             }
+
+            info!(target: "notabot::sources", %bind, "TcpSource listener stopped");
         })
     }
 }
 
+/// Loading of the TLS server configuration (`tls` feature).
+#[cfg(feature = "tls")]
+mod tls_acceptor {
+    use anyhow::{Context, Result};
+    use std::sync::Arc;
+    use tokio_rustls::TlsAcceptor;
+    use tokio_rustls::rustls::ServerConfig;
+    use tokio_rustls::rustls::pki_types::pem::PemObject;
+    use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+
+    use crate::config::models::TlsConfig;
+
+    /// Build an acceptor from the PEM certificate chain and private key in `tls`.
+    pub(super) fn load(tls: &TlsConfig) -> Result<TlsAcceptor> {
+        let certs = CertificateDer::pem_file_iter(&tls.cert_path)
+            .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+            .with_context(|| format!("Failed to load TLS certificate {}", tls.cert_path))?;
+        if certs.is_empty() {
+            anyhow::bail!("No certificate found in {}", tls.cert_path);
+        }
+        let key = PrivateKeyDer::from_pem_file(&tls.key_path)
+            .with_context(|| format!("Failed to load TLS private key {}", tls.key_path))?;
+        let config = ServerConfig::builder_with_provider(Arc::new(
+            tokio_rustls::rustls::crypto::ring::default_provider(),
+        ))
+        .with_safe_default_protocol_versions()
+        .context("Failed to configure TLS protocol versions")?
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .context("TLS certificate and private key do not match")?;
+        Ok(TlsAcceptor::from(Arc::new(config)))
+    }
+}
+
+/// Read the next message into `buf` using `framing`.
+async fn read_message<R: AsyncBufReadExt + Unpin>(
+    reader: &mut R,
+    framing: TcpFraming,
+    buf: &mut String,
+) -> ReadMessage {
+    match framing {
+        TcpFraming::Lines => read_line_message(reader, buf).await,
+        TcpFraming::LengthPrefixed => read_framed_message(reader, buf).await,
+    }
+}
+
+/// Whether `message` is `AUTH <token>`. Compares in constant time for equal lengths.
+fn is_valid_auth(message: &str, token: &str) -> bool {
+    let Some(given) = message.strip_prefix("AUTH ") else {
+        return false;
+    };
+    given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Read one newline-terminated message into `buf`.
+async fn read_line_message<R: AsyncBufReadExt + Unpin>(
+    reader: &mut R,
+    buf: &mut String,
+) -> ReadMessage {
+    buf.clear();
+    match reader.read_line(buf).await? {
+        0 => Ok(None),
+        _ => Ok(Some(Ok(()))),
+    }
+}
+
+/// Read one length-prefixed message (4-byte big-endian length, then the payload) into `buf`.
+async fn read_framed_message<R: AsyncRead + Unpin>(
+    reader: &mut R,
+    buf: &mut String,
+) -> ReadMessage {
+    buf.clear();
+    let mut header = [0u8; 4];
+    match reader.read_exact(&mut header).await {
+        Ok(_) => {}
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+    let len = u32::from_be_bytes(header);
+    if len > MAX_FRAME_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("frame of {len} bytes exceeds the {MAX_FRAME_LEN} byte limit"),
+        ));
+    }
+    let mut payload = vec![0u8; len as usize];
+    reader.read_exact(&mut payload).await?;
+    Ok(Some(String::from_utf8(payload).map(|text| *buf = text)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -214,12 +531,12 @@ mod tests {
         let (tx, mut rx) = mpsc::channel::<Value>(4);
         let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
         let addr = listener.local_addr().unwrap();
-        let ack = true;
+        let opts = TcpSource::new(addr.to_string(), true).client_options();
 
         // Spawn accept loop for a single test connection then break.
         let accept_task = tokio::spawn(async move {
             if let Ok((stream, _)) = listener.accept().await {
-                TcpSource::handle_client(stream, tx, ack).await;
+                TcpSource::handle_client(stream, tx, opts, CancellationToken::new()).await;
             }
         });
 
@@ -239,4 +556,144 @@ mod tests {
         // Ensure task completes
         accept_task.await.unwrap();
     }
+
+    #[tokio::test]
+    async fn test_batch_lines_are_split_with_one_ack() {
+        use tokio::io::AsyncWriteExt;
+        use tokio::sync::mpsc;
+
+        let (tx, mut rx) = mpsc::channel::<Value>(8);
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let opts = TcpSource::new(addr.to_string(), true)
+            .with_batch(true)
+            .client_options();
+        let accept_task = tokio::spawn(async move {
+            if let Ok((stream, _)) = listener.accept().await {
+                TcpSource::handle_client(stream, tx, opts, CancellationToken::new()).await;
+            }
+        });
+
+        let client = TcpStream::connect(addr).await.unwrap();
+        let (read_half, mut write_half) = client.into_split();
+        let mut replies = BufReader::new(read_half).lines();
+        write_half
+            .write_all(b"[{\"type\":\"a\"},{\"type\":\"b\"}]\n{\"type\":\"c\"}\n[oops\n")
+            .await
+            .unwrap();
+
+        assert_eq!(replies.next_line().await.unwrap().unwrap(), "OK");
+        assert_eq!(replies.next_line().await.unwrap().unwrap(), "OK");
+        assert!(
+            replies
+                .next_line()
+                .await
+                .unwrap()
+                .unwrap()
+                .starts_with("ERROR")
+        );
+        drop(write_half);
+        drop(replies);
+        accept_task.await.unwrap();
+
+        let mut types = Vec::new();
+        while let Some(val) = rx.recv().await {
+            types.push(val["type"].as_str().unwrap().to_string());
+        }
+        assert_eq!(types, ["a", "b", "c"]);
+    }
+
+    #[tokio::test]
+    async fn test_framed_reader_handles_eof_utf8_and_oversize() {
+        let mut buf = String::new();
+
+        let mut input: &[u8] = &[0, 0, 0, 2, b'{', b'}'];
+        assert!(matches!(
+            read_framed_message(&mut input, &mut buf).await,
+            Ok(Some(Ok(())))
+        ));
+        assert_eq!(buf, "{}");
+        assert!(matches!(
+            read_framed_message(&mut input, &mut buf).await,
+            Ok(None)
+        ));
+
+        let mut invalid: &[u8] = &[0, 0, 0, 1, 0xff];
+        assert!(matches!(
+            read_framed_message(&mut invalid, &mut buf).await,
+            Ok(Some(Err(_)))
+        ));
+
+        let mut oversize: &[u8] = &(MAX_FRAME_LEN + 1).to_be_bytes();
+        let err = read_framed_message(&mut oversize, &mut buf)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_auth_message_must_match_token() {
+        assert!(is_valid_auth("AUTH s3cret", "s3cret"));
+        assert!(!is_valid_auth("AUTH s3cre", "s3cret"));
+        assert!(!is_valid_auth("AUTH s3creT", "s3cret"));
+        assert!(!is_valid_auth("s3cret", "s3cret"));
+        assert!(!is_valid_auth("{\"type\":\"x\"}", "s3cret"));
+    }
+
+    #[tokio::test]
+    async fn test_unauthenticated_events_are_never_delivered() {
+        use tokio::io::AsyncWriteExt;
+        use tokio::sync::mpsc;
+
+        let (tx, mut rx) = mpsc::channel::<Value>(8);
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let opts = TcpSource::new(addr.to_string(), true)
+            .with_token(Some("s3cret".into()))
+            .client_options();
+        let accept_task = tokio::spawn(async move {
+            for _ in 0..2 {
+                if let Ok((stream, _)) = listener.accept().await {
+                    TcpSource::handle_client(
+                        stream,
+                        tx.clone(),
+                        opts.clone(),
+                        CancellationToken::new(),
+                    )
+                    .await;
+                }
+            }
+        });
+
+        // Wrong token: rejected and disconnected, the event behind it is dropped
+        let client = TcpStream::connect(addr).await.unwrap();
+        let (read_half, mut write_half) = client.into_split();
+        let mut replies = BufReader::new(read_half).lines();
+        write_half
+            .write_all(b"AUTH wrong\n{\"type\":\"intruder\"}\n")
+            .await
+            .unwrap();
+        assert_eq!(
+            replies.next_line().await.unwrap().unwrap(),
+            "ERROR unauthorized"
+        );
+        assert!(replies.next_line().await.unwrap().is_none());
+
+        // Right token: events after AUTH are delivered
+        let client = TcpStream::connect(addr).await.unwrap();
+        let (read_half, mut write_half) = client.into_split();
+        let mut replies = BufReader::new(read_half).lines();
+        write_half
+            .write_all(b"AUTH s3cret\n{\"type\":\"friend\"}\n")
+            .await
+            .unwrap();
+        assert_eq!(replies.next_line().await.unwrap().unwrap(), "OK");
+        assert_eq!(replies.next_line().await.unwrap().unwrap(), "OK");
+        drop(write_half);
+        drop(replies);
+        accept_task.await.unwrap();
+
+        assert_eq!(rx.recv().await.unwrap()["type"], "friend");
+        assert!(rx.recv().await.is_none());
+    }
 }
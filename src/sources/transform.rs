@@ -0,0 +1,250 @@
+//! Per-source event transforms.
+//!
+//! A transform reshapes each parsed event at the source boundary, before it reaches
+//! the runtime. It is configured as a map of `target path -> source path` (dotted):
+//!
+//! ```json
+//! { "type": "tcp", "bind": "127.0.0.1:6000", "transform": { "type": "t", "order.side": "side" } }
+//! ```
+//!
+//! Semantics:
+//! - Every mapped value is first taken out of the event, then written at its target path,
+//!   so swaps (`{ "a": "b", "b": "a" }`) behave as expected.
+//! - Missing source paths are skipped; fields not mentioned are kept untouched.
+//! - Intermediate objects along the target path are created when absent.
+//! - Non-object events (arrays, scalars) pass through unchanged.
+//!
+//! Invalid specs (empty paths or segments, duplicate source paths) are rejected when
+//! the transform is compiled, which happens while building sources at startup.
+
+use std::sync::Arc;
+
+use anyhow::{Result, bail};
+use serde_json::{Map, Value};
+use tokio::{
+    sync::mpsc::{self, Sender},
+    task::JoinHandle,
+};
+use tokio_util::sync::CancellationToken;
+use tracing::{trace, warn};
+
+use super::{EventSource, SourceMetrics};
+use crate::config::TransformSpec;
+
+/// A compiled, validated event transform.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventTransform {
+    /// (target segments, source segments), sorted by target path (the spec is a
+    /// `BTreeMap`, so the order in the config file is not kept).
+    mappings: Vec<(Vec<String>, Vec<String>)>,
+}
+
+impl EventTransform {
+    /// Compile a transform spec, validating every path.
+    pub fn new(spec: &TransformSpec) -> Result<Self> {
+        let mut mappings = Vec::with_capacity(spec.len());
+        let mut seen_sources = std::collections::BTreeSet::new();
+        for (target, source) in spec {
+            let target_segs = parse_path(target)
+                .map_err(|e| anyhow::anyhow!("Invalid transform target '{target}': {e}"))?;
+            let source_segs = parse_path(source).map_err(|e| {
+                anyhow::anyhow!("Invalid transform source '{source}' (for '{target}'): {e}")
+            })?;
+            if !seen_sources.insert(source.trim().to_string()) {
+                bail!("Transform source '{source}' is mapped more than once");
+            }
+            mappings.push((target_segs, source_segs));
+        }
+        Ok(Self { mappings })
+    }
+
+    /// Apply the transform to an event, returning the reshaped value.
+    pub fn apply(&self, event: Value) -> Value {
+        let Value::Object(mut map) = event else {
+            trace!(target: "notabot::sources", "Transform skipped for non-object event");
+            return event;
+        };
+
+        let taken: Vec<(&[String], Value)> = self
+            .mappings
+            .iter()
+            .filter_map(|(target, source)| take_path(&mut map, source).map(|v| (&target[..], v)))
+            .collect();
+
+        for (target, value) in taken {
+            insert_path(&mut map, target, value);
+        }
+        Value::Object(map)
+    }
+}
+
+fn parse_path(path: &str) -> Result<Vec<String>> {
+    let path = path.trim();
+    if path.is_empty() {
+        bail!("path is empty");
+    }
+    let segs: Vec<String> = path.split('.').map(|s| s.trim().to_string()).collect();
+    if segs.iter().any(String::is_empty) {
+        bail!("path contains an empty segment");
+    }
+    Ok(segs)
+}
+
+/// Remove and return the value at `path`, if present.
+fn take_path(map: &mut Map<String, Value>, path: &[String]) -> Option<Value> {
+    let (last, parents) = path.split_last()?;
+    let mut current = map;
+    for seg in parents {
+        current = current.get_mut(seg)?.as_object_mut()?;
+    }
+    current.remove(last)
+}
+
+/// Insert `value` at `path`, creating (or replacing non-object) intermediate nodes.
+fn insert_path(map: &mut Map<String, Value>, path: &[String], value: Value) {
+    let Some((last, parents)) = path.split_last() else {
+        return;
+    };
+    let mut current = map;
+    for seg in parents {
+        let entry = current
+            .entry(seg.clone())
+            .or_insert_with(|| Value::Object(Map::new()));
+        if !entry.is_object() {
+            warn!(
+                target: "notabot::sources",
+                segment = %seg,
+                "Transform overwrote a non-object value to reach its target path"
+            );
+            *entry = Value::Object(Map::new());
+        }
+        current = entry
+            .as_object_mut()
+            .expect("entry was just made an object");
+    }
+    current.insert(last.clone(), value);
+}
+
+/// Decorator that applies an `EventTransform` to everything an inner source emits.
+///
+/// The inner source writes into a private channel; a forwarding task reshapes each
+/// event and pushes it to the real sender. The forwarder ends when the inner source
+/// ends (its sender is dropped, e.g. after shutdown) or when the downstream channel closes.
+pub struct TransformedSource {
+    inner: Box<dyn EventSource>,
+    transform: EventTransform,
+}
+
+impl TransformedSource {
+    /// Wrap `inner` so each of its events goes through `transform`.
+    pub fn new(inner: Box<dyn EventSource>, transform: EventTransform) -> Self {
+        Self { inner, transform }
+    }
+}
+
+impl EventSource for TransformedSource {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    fn metrics(&self) -> Option<Arc<SourceMetrics>> {
+        self.inner.metrics()
+    }
+
+    fn start(&self, sender: Sender<Value>, shutdown: CancellationToken) -> JoinHandle<()> {
+        let (inner_tx, mut inner_rx) = mpsc::channel::<Value>(sender.max_capacity());
+        let inner_handle = self.inner.start(inner_tx, shutdown);
+        let transform = self.transform.clone();
+        let name = self.inner.name();
+
+        tokio::spawn(async move {
+            while let Some(event) = inner_rx.recv().await {
+                if sender.send(transform.apply(event)).await.is_err() {
+                    trace!(
+                        target: "notabot::sources",
+                        source = %name,
+                        "Channel closed; transform forwarder exiting"
+                    );
+                    inner_handle.abort();
+                    break;
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn spec(pairs: &[(&str, &str)]) -> TransformSpec {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn renames_and_nests_fields() {
+        let t = EventTransform::new(&spec(&[("type", "t"), ("order.side", "s")])).unwrap();
+        let out = t.apply(json!({"t": "trade", "s": "buy", "qty": 3}));
+        assert_eq!(
+            out,
+            json!({"type": "trade", "order": {"side": "buy"}, "qty": 3})
+        );
+    }
+
+    #[test]
+    fn swaps_and_missing_sources() {
+        let t = EventTransform::new(&spec(&[("a", "b"), ("b", "a"), ("c", "nope")])).unwrap();
+        assert_eq!(t.apply(json!({"a": 1, "b": 2})), json!({"a": 2, "b": 1}));
+        assert_eq!(t.apply(json!([1, 2])), json!([1, 2]));
+    }
+
+    #[test]
+    fn nested_targets_are_written_in_sorted_order() {
+        // Listed `a.b` first, but `a` sorts first, so `a.b` overwrites it
+        let t = EventTransform::new(&spec(&[("a.b", "y"), ("a", "x")])).unwrap();
+        assert_eq!(t.apply(json!({"x": 1, "y": 2})), json!({"a": {"b": 2}}));
+    }
+
+    #[test]
+    fn invalid_specs_are_rejected() {
+        assert!(EventTransform::new(&spec(&[("", "t")])).is_err());
+        assert!(EventTransform::new(&spec(&[("type", "a..b")])).is_err());
+        let err = EventTransform::new(&spec(&[("x", "t"), ("y", "t")])).unwrap_err();
+        assert!(err.to_string().contains("more than once"));
+    }
+
+    struct FixedSource(Vec<Value>);
+
+    impl EventSource for FixedSource {
+        fn name(&self) -> &'static str {
+            "fixed"
+        }
+
+        fn start(&self, sender: Sender<Value>, _shutdown: CancellationToken) -> JoinHandle<()> {
+            let events = self.0.clone();
+            tokio::spawn(async move {
+                for e in events {
+                    let _ = sender.send(e).await;
+                }
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn transformed_source_forwards_reshaped_events() {
+        let inner = Box::new(FixedSource(vec![json!({"t": "a"}), json!({"t": "b"})]));
+        let t = EventTransform::new(&spec(&[("type", "t")])).unwrap();
+        let src = TransformedSource::new(inner, t);
+        assert_eq!(src.name(), "fixed");
+
+        let (tx, mut rx) = mpsc::channel(4);
+        src.start(tx, CancellationToken::new()).await.unwrap();
+        assert_eq!(rx.recv().await, Some(json!({"type": "a"})));
+        assert_eq!(rx.recv().await, Some(json!({"type": "b"})));
+        assert_eq!(rx.recv().await, None);
+    }
+}
@@ -0,0 +1,164 @@
+use serde_json::Value;
+use tokio::{net::UdpSocket, sync::mpsc::Sender, task::JoinHandle};
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, trace, warn};
+
+use super::EventSource;
+
+/// Largest datagram read in one go (the maximum UDP payload size).
+const MAX_DATAGRAM_LEN: usize = 65_535;
+
+/// UDP-based event source.
+///
+/// Protocol:
+/// - Each datagram holds exactly one JSON value (surrounding whitespace is ignored).
+/// - Nothing is sent back: there are no ACKs, producers fire and forget.
+///
+/// Behavior & Robustness:
+/// - Malformed (or non-UTF-8) datagrams are logged with `warn!` and dropped.
+/// - Empty datagrams are ignored.
+/// - Channel backpressure is respected (`sender.send(...).await`); datagrams arriving
+///   meanwhile queue in the socket buffer and may be dropped by the OS once it is full.
+/// - If the event channel is closed, or the shutdown token is cancelled, the task terminates.
+///
+/// Security Notes:
+/// - No authentication and no encryption (intended for local / trusted network use).
+///   Source addresses are trivially spoofed; prefer `tcp` with a token across networks.
+#[derive(Debug, Clone)]
+pub struct UdpSource {
+    bind: String,
+}
+
+impl UdpSource {
+    /// Create a new UDP source.
+    ///
+    /// * `bind` - Address (e.g. "127.0.0.1:5001")
+    pub fn new(bind: impl Into<String>) -> Self {
+        Self { bind: bind.into() }
+    }
+
+    /// Receive datagrams from `socket` until the channel closes, the socket fails or
+    /// `shutdown` is cancelled.
+    async fn run(socket: UdpSocket, sender: Sender<Value>, shutdown: CancellationToken) {
+        let mut buf = vec![0u8; MAX_DATAGRAM_LEN];
+        loop {
+            let received = tokio::select! {
+                _ = shutdown.cancelled() => break,
+                received = socket.recv_from(&mut buf) => received,
+            };
+            let (len, peer) = match received {
+                Ok(received) => received,
+                Err(e) => {
+                    error!(
+                        target: "notabot::sources",
+                        error = %e,
+                        "Error receiving UDP datagram; terminating task"
+                    );
+                    break;
+                }
+            };
+
+            let raw = match std::str::from_utf8(&buf[..len]) {
+                Ok(s) => s.trim(),
+                Err(e) => {
+                    warn!(
+                        target: "notabot::sources",
+                        peer = %peer,
+                        error = %e,
+                        "Dropping non-UTF-8 UDP datagram"
+                    );
+                    continue;
+                }
+            };
+            if raw.is_empty() {
+                continue;
+            }
+
+            match serde_json::from_str::<Value>(raw) {
+                Ok(val) => {
+                    trace!(target: "notabot::sources", peer = %peer, "Parsed JSON from UDP datagram");
+                    if sender.send(val).await.is_err() {
+                        error!(
+                            target: "notabot::sources",
+                            peer = %peer,
+                            "Channel closed while sending UDP event; terminating task"
+                        );
+                        break;
+                    }
+                }
+                Err(e) => {
+                    warn!(
+                        target: "notabot::sources",
+                        peer = %peer,
+                        error = %e,
+                        datagram = raw,
+                        "Failed to parse UDP JSON datagram"
+                    );
+                }
+            }
+        }
+
+        trace!(target: "notabot::sources", "UdpSource task ended");
+    }
+}
+
+impl EventSource for UdpSource {
+    fn name(&self) -> &'static str {
+        "udp"
+    }
+
+    fn start(&self, sender: Sender<Value>, shutdown: CancellationToken) -> JoinHandle<()> {
+        let bind = self.bind.clone();
+        tokio::spawn(async move {
+            info!(target: "notabot::sources", %bind, "UdpSource starting");
+
+            let socket = match UdpSocket::bind(&bind).await {
+                Ok(s) => s,
+                Err(e) => {
+                    error!(
+                        target: "notabot::sources",
+                        %bind,
+                        error = %e,
+                        "Failed to bind UDP socket (terminating task)"
+                    );
+                    return;
+                }
+            };
+
+            Self::run(socket, sender, shutdown).await;
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::mpsc;
+
+    #[tokio::test]
+    async fn test_forwards_valid_datagrams_and_drops_malformed() {
+        let (tx, mut rx) = mpsc::channel::<Value>(8);
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = socket.local_addr().unwrap();
+        let handle = tokio::spawn(UdpSource::run(socket, tx, CancellationToken::new()));
+
+        let client = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        for datagram in [
+            &b"{ not json"[..],
+            b"\xff\xfe",
+            b"  ",
+            b"{\"type\":\"ping\",\"n\":1}\n",
+        ] {
+            client.send_to(datagram, addr).await.unwrap();
+        }
+
+        let event = rx.recv().await.unwrap();
+        assert_eq!(event["type"], "ping");
+        assert_eq!(event["n"], 1);
+        assert!(rx.try_recv().is_err());
+
+        drop(rx);
+        client.send_to(b"{\"type\":\"late\"}", addr).await.unwrap();
+        handle.await.unwrap();
+    }
+}
@@ -0,0 +1,230 @@
+use std::time::Duration;
+
+use futures_util::StreamExt;
+use serde_json::Value;
+use tokio::{net::TcpStream, sync::mpsc::Sender, task::JoinHandle};
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream, connect_async, tungstenite::Message};
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, trace, warn};
+
+use super::EventSource;
+
+/// Delay before the first reconnection attempt; doubled after every failure.
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+/// Upper bound for the reconnection delay.
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// WebSocket event source (`websocket` cargo feature).
+///
+/// Connects out to a WebSocket server (client mode) and treats every text frame as
+/// one JSON value.
+///
+/// Behavior & Robustness:
+/// - Malformed text frames are logged with `warn!` and dropped; binary frames are
+///   ignored. Ping/pong is handled by the WebSocket layer.
+/// - With `reconnect = true` (the default), a failed connection attempt or a dropped
+///   socket is retried after an exponential backoff (`INITIAL_BACKOFF` doubling up to
+///   `MAX_BACKOFF`, reset once a connection succeeds). Every attempt is logged.
+///   Without it, the task ends once the first connection closes or fails.
+/// - Channel backpressure is respected (`sender.send(...).await`); frames queue in the
+///   socket meanwhile.
+/// - If the event channel is closed, the task terminates.
+/// - Cancelling the shutdown token ends the task while it connects, waits for a frame
+///   or backs off.
+///
+/// Limitations:
+/// - Only `ws://` URLs are supported (no TLS for `wss://`); the config loader rejects
+///   any other scheme.
+#[derive(Debug, Clone)]
+pub struct WebSocketSource {
+    url: String,
+    reconnect: bool,
+}
+
+/// Why a connection stopped being read.
+enum Disconnect {
+    /// The socket closed or failed; a new connection may be attempted.
+    Socket,
+    /// The event channel closed or shutdown was requested; the source is done.
+    Stop,
+}
+
+impl WebSocketSource {
+    /// Create a new WebSocket source that reconnects on failure.
+    ///
+    /// * `url` - Server to connect to (e.g. "ws://127.0.0.1:9000/events")
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            reconnect: true,
+        }
+    }
+
+    /// Retry dropped or failed connections with exponential backoff (default: true).
+    pub fn with_reconnect(mut self, reconnect: bool) -> Self {
+        self.reconnect = reconnect;
+        self
+    }
+
+    /// Connect (and reconnect) to `url` until the channel closes, `shutdown` is
+    /// cancelled or reconnecting is off.
+    async fn run(url: String, reconnect: bool, sender: Sender<Value>, shutdown: CancellationToken) {
+        let mut backoff = INITIAL_BACKOFF;
+        let mut attempt: u32 = 0;
+        loop {
+            attempt += 1;
+            info!(target: "notabot::sources", %url, attempt, "Connecting to WebSocket");
+            let connected = tokio::select! {
+                _ = shutdown.cancelled() => break,
+                connected = connect_async(url.as_str()) => connected,
+            };
+            match connected {
+                Ok((stream, _)) => {
+                    info!(target: "notabot::sources", %url, "WebSocket connected");
+                    backoff = INITIAL_BACKOFF;
+                    attempt = 0;
+                    if let Disconnect::Stop = read_frames(stream, &url, &sender, &shutdown).await {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    warn!(
+                        target: "notabot::sources",
+                        %url,
+                        attempt,
+                        error = %e,
+                        "WebSocket connection failed"
+                    );
+                }
+            }
+
+            if !reconnect || sender.is_closed() {
+                break;
+            }
+            info!(
+                target: "notabot::sources",
+                %url,
+                delay_ms = backoff.as_millis() as u64,
+                "Reconnecting to WebSocket after backoff"
+            );
+            tokio::select! {
+                _ = shutdown.cancelled() => break,
+                _ = tokio::time::sleep(backoff) => {}
+            }
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+        }
+
+        trace!(target: "notabot::sources", %url, "WebSocketSource task ended");
+    }
+}
+
+/// Forward the text frames of one connection until it closes.
+async fn read_frames(
+    mut stream: WebSocketStream<MaybeTlsStream<TcpStream>>,
+    url: &str,
+    sender: &Sender<Value>,
+    shutdown: &CancellationToken,
+) -> Disconnect {
+    loop {
+        let frame = tokio::select! {
+            _ = shutdown.cancelled() => {
+                let _ = stream.close(None).await;
+                return Disconnect::Stop;
+            }
+            frame = stream.next() => match frame {
+                Some(frame) => frame,
+                None => break,
+            },
+        };
+        match frame {
+            Ok(Message::Text(text)) => match serde_json::from_str::<Value>(text.trim()) {
+                Ok(val) => {
+                    trace!(target: "notabot::sources", %url, "Parsed JSON from WebSocket frame");
+                    if sender.send(val).await.is_err() {
+                        error!(
+                            target: "notabot::sources",
+                            %url,
+                            "Channel closed while sending WebSocket event; terminating task"
+                        );
+                        return Disconnect::Stop;
+                    }
+                }
+                Err(e) => {
+                    warn!(
+                        target: "notabot::sources",
+                        %url,
+                        error = %e,
+                        frame = text.as_str(),
+                        "Failed to parse WebSocket JSON frame"
+                    );
+                }
+            },
+            Ok(Message::Binary(_)) => {
+                warn!(target: "notabot::sources", %url, "Ignoring binary WebSocket frame");
+            }
+            Ok(Message::Close(_)) => {
+                info!(target: "notabot::sources", %url, "WebSocket closed by server");
+                break;
+            }
+            Ok(_) => {}
+            Err(e) => {
+                warn!(target: "notabot::sources", %url, error = %e, "WebSocket read failed");
+                break;
+            }
+        }
+    }
+    Disconnect::Socket
+}
+
+impl EventSource for WebSocketSource {
+    fn name(&self) -> &'static str {
+        "websocket"
+    }
+
+    fn start(&self, sender: Sender<Value>, shutdown: CancellationToken) -> JoinHandle<()> {
+        let url = self.url.clone();
+        let reconnect = self.reconnect;
+        tokio::spawn(async move {
+            info!(target: "notabot::sources", %url, reconnect, "WebSocketSource starting");
+            WebSocketSource::run(url, reconnect, sender, shutdown).await;
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::SinkExt;
+    use tokio::net::TcpListener;
+    use tokio::sync::mpsc;
+
+    #[tokio::test]
+    async fn test_forwards_text_frames_and_reconnects() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let (tx, mut rx) = mpsc::channel::<Value>(8);
+        let handle = WebSocketSource::new(url).start(tx, CancellationToken::new());
+
+        // First connection: one valid frame, one malformed, then the server hangs up
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+        ws.send(Message::text("{ not json")).await.unwrap();
+        ws.send(Message::text(r#"{"type":"tick","n":1}"#))
+            .await
+            .unwrap();
+        ws.close(None).await.unwrap();
+        drop(ws);
+        assert_eq!(rx.recv().await.unwrap()["n"], 1);
+
+        // The source comes back after the backoff
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+        ws.send(Message::text(r#"{"type":"tick","n":2}"#))
+            .await
+            .unwrap();
+        assert_eq!(rx.recv().await.unwrap()["n"], 2);
+        assert!(rx.try_recv().is_err());
+
+        handle.abort();
+    }
+}
@@ -0,0 +1,172 @@
+//! Helpers for end-to-end workflow tests.
+//!
+//! - `InMemorySource` emits a fixed list of events, then ends.
+//! - `CapturingExecutor` records every low-level action (moves, clicks, typed text,
+//!   sleeps, logs, ...) instead of simulating input.
+//! - `capturing_runtime` / `run_events` wire them to a `Runtime` and the event loop.
+//!
+//! Available in this crate's own tests, and to other crates with the `testing` feature.
+//!
+//! ```ignore
+//! let (mut rt, capture) = capturing_runtime(config);
+//! run_events(&mut rt, [json!({"type": "greet", "name": "Ada"})]).await?;
+//! assert_eq!(capture.actions(), vec![CapturedAction::TypeText("Hi Ada".into())]);
+//! ```
+
+use anyhow::Result;
+use serde_json::Value;
+use tokio::sync::mpsc::{self, Sender};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+use crate::app::{self, RunExit};
+use crate::config::Config;
+use crate::executor::Runtime;
+use crate::executor::actions::CaptureLog;
+use crate::sources::EventSource;
+
+pub use crate::executor::actions::CapturedAction;
+
+/// Source that sends a fixed list of events in order, then ends.
+#[derive(Debug, Clone, Default)]
+pub struct InMemorySource {
+    events: Vec<Value>,
+}
+
+impl InMemorySource {
+    pub fn new(events: impl IntoIterator<Item = Value>) -> Self {
+        Self {
+            events: events.into_iter().collect(),
+        }
+    }
+}
+
+impl EventSource for InMemorySource {
+    fn name(&self) -> &'static str {
+        "in_memory"
+    }
+
+    fn start(&self, sender: Sender<Value>, shutdown: CancellationToken) -> JoinHandle<()> {
+        let events = self.events.clone();
+        tokio::spawn(async move {
+            for event in events {
+                if shutdown.is_cancelled() || sender.send(event).await.is_err() {
+                    break;
+                }
+            }
+        })
+    }
+}
+
+/// Handle to the actions recorded by a runtime it was attached to.
+#[derive(Debug, Clone, Default)]
+pub struct CapturingExecutor {
+    log: CaptureLog,
+}
+
+impl CapturingExecutor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Make `runtime` record into this capture instead of simulating input
+    /// (the runtime is switched to dry-run).
+    pub fn attach(&self, runtime: &mut Runtime) {
+        runtime.set_capture(self.log.clone());
+    }
+
+    /// Actions recorded so far, in order.
+    pub fn actions(&self) -> Vec<CapturedAction> {
+        self.log.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Return the recorded actions and clear the list.
+    pub fn take(&self) -> Vec<CapturedAction> {
+        std::mem::take(&mut *self.log.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+/// Build a runtime for `config` whose actions are captured instead of performed.
+pub fn capturing_runtime(config: Config) -> (Runtime, CapturingExecutor) {
+    let mut runtime = Runtime::new(config, true);
+    let capture = CapturingExecutor::new();
+    capture.attach(&mut runtime);
+    (runtime, capture)
+}
+
+/// Feed `events` through the event loop (as a single `InMemorySource`) until they
+/// are all handled. A runtime can only be driven by the event loop once.
+pub async fn run_events(
+    runtime: &mut Runtime,
+    events: impl IntoIterator<Item = Value>,
+) -> Result<RunExit> {
+    let sources: Vec<Box<dyn EventSource>> = vec![Box::new(InMemorySource::new(events))];
+    let (_signal_tx, signal_rx) = mpsc::unbounded_channel();
+    app::run(runtime, &sources, signal_rx).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ActionDef, EventBinding, MouseButton};
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    fn config() -> Config {
+        let mut cfg = Config::default();
+        cfg.workflows.insert(
+            "greet".into(),
+            vec![
+                ActionDef::MouseClick {
+                    button: MouseButton::Left,
+                    count: None,
+                    post_delay_ms: None,
+                },
+                ActionDef::TypeText {
+                    text: "Hi {{name}}".into(),
+                    per_char_ms: None,
+                    post_delay_ms: None,
+                },
+            ],
+        );
+        cfg.events.insert(
+            "greet".into(),
+            EventBinding {
+                workflow: "greet".into(),
+                vars_map: HashMap::from([("name".to_string(), "name".to_string())]),
+                schema: None,
+            },
+        );
+        cfg
+    }
+
+    #[tokio::test]
+    async fn two_events_produce_captured_action_sequence() {
+        let (mut rt, capture) = capturing_runtime(config());
+        let exit = run_events(
+            &mut rt,
+            [
+                json!({"type": "greet", "name": "Ada"}),
+                json!({"type": "greet", "name": "Linus"}),
+            ],
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(exit, RunExit::SourcesEnded);
+        let click = CapturedAction::MouseClick {
+            button: MouseButton::Left,
+            count: 1,
+        };
+        assert_eq!(
+            capture.take(),
+            vec![
+                click.clone(),
+                CapturedAction::TypeText("Hi Ada".into()),
+                click,
+                CapturedAction::TypeText("Hi Linus".into()),
+            ]
+        );
+        assert!(capture.actions().is_empty());
+    }
+}
@@ -0,0 +1,91 @@
+use anyhow::Result;
+use tracing::debug;
+
+/// Run `f` on the process-wide clipboard handle, opening it on first use.
+///
+/// On Linux (X11/Wayland) the contents we set are served by our own `Clipboard` for as
+/// long as it lives, so dropping it right after a copy would lose them. One handle is
+/// kept for the whole run instead.
+#[cfg(feature = "clipboard")]
+fn with_clipboard<T>(f: impl FnOnce(&mut arboard::Clipboard) -> Result<T>) -> Result<T> {
+    use anyhow::Context;
+    use std::sync::Mutex;
+
+    static CLIPBOARD: Mutex<Option<arboard::Clipboard>> = Mutex::new(None);
+    let mut clipboard = CLIPBOARD.lock().unwrap_or_else(|e| e.into_inner());
+    if clipboard.is_none() {
+        *clipboard = Some(arboard::Clipboard::new().context("Clipboard is not available")?);
+    }
+    f(clipboard.as_mut().expect("clipboard was just opened"))
+}
+
+/// Copy an RGBA image (row-major, 4 bytes per pixel) to the system clipboard.
+///
+/// Requires the `clipboard-image` cargo feature; without it this always returns an error
+/// so callers can surface a clear message instead of silently doing nothing.
+/// Errors are also returned when the clipboard backend is unavailable (e.g. headless Linux).
+#[cfg(feature = "clipboard-image")]
+pub fn set_image(width: usize, height: usize, rgba: &[u8]) -> Result<()> {
+    use anyhow::Context;
+
+    debug!(target: "notabot::clipboard", width, height, "Copying image to clipboard");
+    with_clipboard(|clipboard| {
+        clipboard
+            .set_image(arboard::ImageData {
+                width,
+                height,
+                bytes: std::borrow::Cow::Borrowed(rgba),
+            })
+            .context("Failed to copy image to clipboard")
+    })
+}
+
+#[cfg(not(feature = "clipboard-image"))]
+pub fn set_image(width: usize, height: usize, _rgba: &[u8]) -> Result<()> {
+    debug!(target: "notabot::clipboard", width, height, "Image clipboard requested");
+    anyhow::bail!("Copying images to the clipboard requires the `clipboard-image` feature")
+}
+
+/// Replace the clipboard contents with `text`.
+///
+/// Requires the `clipboard` cargo feature; without it this always returns an error.
+/// Errors are also returned when the clipboard backend is unavailable (e.g. headless Linux).
+#[cfg(feature = "clipboard")]
+pub fn set_text(text: &str) -> Result<()> {
+    use anyhow::Context;
+
+    debug!(target: "notabot::clipboard", len = text.len(), "Copying text to clipboard");
+    with_clipboard(|clipboard| {
+        clipboard
+            .set_text(text)
+            .context("Failed to copy text to clipboard")
+    })
+}
+
+#[cfg(not(feature = "clipboard"))]
+pub fn set_text(text: &str) -> Result<()> {
+    debug!(target: "notabot::clipboard", len = text.len(), "Text clipboard requested");
+    anyhow::bail!("Clipboard text actions require the `clipboard` feature")
+}
+
+/// Read the current clipboard contents as text.
+///
+/// Requires the `clipboard` cargo feature; without it this always returns an error.
+/// Errors are also returned when the clipboard is unavailable or holds no text.
+#[cfg(feature = "clipboard")]
+pub fn get_text() -> Result<String> {
+    use anyhow::Context;
+
+    debug!(target: "notabot::clipboard", "Reading text from clipboard");
+    with_clipboard(|clipboard| {
+        clipboard
+            .get_text()
+            .context("Failed to read text from clipboard")
+    })
+}
+
+#[cfg(not(feature = "clipboard"))]
+pub fn get_text() -> Result<String> {
+    debug!(target: "notabot::clipboard", "Text clipboard read requested");
+    anyhow::bail!("Clipboard text actions require the `clipboard` feature")
+}
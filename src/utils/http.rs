@@ -0,0 +1,124 @@
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::time::Duration;
+use tracing::debug;
+
+/// Status code and body text of an HTTP response.
+#[derive(Debug, Clone)]
+pub struct Response {
+    pub status: u16,
+    pub body: String,
+}
+
+impl Response {
+    /// Whether the status is 2xx.
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+/// Methods `http_request` may use.
+pub const METHODS: &[&str] = &["GET", "HEAD", "POST", "PUT", "PATCH", "DELETE", "OPTIONS"];
+
+/// Normalize `method` (trimmed, upper-cased) and check that it is one of `METHODS`.
+pub fn parse_method(method: &str) -> Result<String> {
+    let normalized = method.trim().to_ascii_uppercase();
+    if !METHODS.contains(&normalized.as_str()) {
+        anyhow::bail!(
+            "Unsupported HTTP method '{method}' (expected one of {})",
+            METHODS.join(", ")
+        );
+    }
+    Ok(normalized)
+}
+
+/// Send a request and read the whole response body, whatever its status.
+///
+/// Requires the `http-client` cargo feature; without it this always returns an error so
+/// callers can surface a clear message instead of silently skipping the request.
+/// `timeout` covers the whole exchange, from connecting to reading the body.
+#[cfg(feature = "http-client")]
+pub async fn send(
+    method: &str,
+    url: &str,
+    headers: &BTreeMap<String, String>,
+    body: Option<&str>,
+    timeout: Duration,
+) -> Result<Response> {
+    use anyhow::Context;
+
+    debug!(target: "notabot::http", %method, %url, "Sending HTTP request");
+    let method = reqwest::Method::from_bytes(method.as_bytes())
+        .with_context(|| format!("Invalid HTTP method '{method}'"))?;
+    let client = reqwest::Client::builder()
+        .timeout(timeout)
+        .build()
+        .context("Failed to build HTTP client")?;
+    let mut request = client.request(method, url);
+    for (name, value) in headers {
+        request = request.header(name, value);
+    }
+    if let Some(body) = body {
+        request = request.body(body.to_string());
+    }
+    let response = request.send().await?;
+    let status = response.status().as_u16();
+    let body = response.text().await.context("Failed to read body")?;
+    Ok(Response { status, body })
+}
+
+#[cfg(not(feature = "http-client"))]
+pub async fn send(
+    method: &str,
+    url: &str,
+    _headers: &BTreeMap<String, String>,
+    _body: Option<&str>,
+    _timeout: Duration,
+) -> Result<Response> {
+    debug!(target: "notabot::http", %method, %url, "HTTP request requested");
+    anyhow::bail!("Outgoing HTTP requests require the `http-client` feature")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_method_normalizes_and_rejects_unknown_methods() {
+        assert_eq!(parse_method(" post ").unwrap(), "POST");
+        assert_eq!(parse_method("delete").unwrap(), "DELETE");
+        let err = parse_method("PSOT").unwrap_err();
+        assert!(
+            err.to_string().contains("Unsupported HTTP method 'PSOT'"),
+            "{err}"
+        );
+        assert!(parse_method("").is_err());
+    }
+
+    #[test]
+    fn only_2xx_statuses_are_success() {
+        let response = |status| Response {
+            status,
+            body: String::new(),
+        };
+        assert!(response(200).is_success());
+        assert!(response(204).is_success());
+        assert!(!response(302).is_success());
+        assert!(!response(503).is_success());
+    }
+
+    #[cfg(not(feature = "http-client"))]
+    #[tokio::test]
+    async fn send_without_feature_is_an_error() {
+        let err = send(
+            "GET",
+            "http://127.0.0.1/",
+            &BTreeMap::new(),
+            None,
+            Duration::from_secs(1),
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("`http-client` feature"));
+    }
+}
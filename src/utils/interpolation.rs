@@ -1,15 +1,28 @@
+use rand::random_range;
 use serde_json::Value;
 use std::collections::{BTreeMap, HashMap};
+use tracing::warn;
+
+/// Maximum chain length when globals reference other globals (`a -> b -> c ...`).
+const MAX_GLOBAL_REF_DEPTH: usize = 16;
 
 /// Interpolate a template string by replacing tokens with values from `vars` and `globals`.
 ///
 /// Supported token formats:
 /// - `{{var_name}}` -> replaced with `vars["var_name"]` if present
 /// - `{{@global_key}}` -> replaced with `globals["global_key"]` if present
+/// - `{{$event.path}}` -> a field of the current event (only resolved by
+///   `Template::render_with_event`; left intact here)
+/// - `{{name|filter|...}}` -> the value passed through filters, left to right:
+///   `upper`, `lower`, `trim`, `basename` (last component of a `/` or `\` path)
+/// - `{{~rand_int:lo-hi}}` -> a random integer in `lo..=hi`; `{{~uuid}}` -> a random
+///   (v4) UUID. Drawn again on every render (filters apply, defaults do not)
+/// - `{{name:default text}}` / `{{@key:default}}` -> `default text` when the name does
+///   not resolve (everything after the first `:` up to `}}`; filters apply to it too)
 ///
 /// Notes:
 /// - Whitespace around the token content is ignored: `{{  var  }}` == `{{var}}`.
-/// - Unknown tokens are left intact to aid debugging.
+/// - Unknown tokens (and tokens using an unknown filter) are left intact to aid debugging.
 /// - Globals support dotted paths into JSON objects, e.g. `{{@app.name}}`.
 /// - When a global is not a string, it is rendered as JSON (e.g., numbers as `42`, objects as `{"k":"v"}`).
 pub fn interpolate_string(
@@ -17,54 +30,321 @@ pub fn interpolate_string(
     vars: &HashMap<String, String>,
     globals: &BTreeMap<String, Value>,
 ) -> String {
-    let mut out = String::with_capacity(template.len());
-    let mut idx = 0;
-    let bytes = template.as_bytes();
+    render(template, vars, globals, &mut |_| {})
+}
 
-    while let Some(start) = find_subslice(bytes, b"{{", idx) {
-        // Push everything up to the start of the token
-        out.push_str(&template[idx..start]);
-
-        // Find the end delimiter
-        let content_start = start + 2;
-        if let Some(end) = find_subslice(bytes, b"}}", content_start) {
-            let raw = &template[content_start..end];
-            let token = raw.trim();
-
-            if token.is_empty() {
-                // Keep empty tokens intact
-                out.push_str(&template[start..end + 2]);
-            } else {
-                let replaced = if let Some(stripped) = token.strip_prefix('@') {
-                    // Global lookup (supports dotted paths)
-                    lookup_global(globals, stripped.trim()).unwrap_or_else(|| {
-                        // Unknown -> keep original token
-                        template[start..end + 2].to_string()
-                    })
-                } else {
-                    // Variable lookup
-                    vars.get(token)
-                        .cloned()
-                        .unwrap_or_else(|| template[start..end + 2].to_string())
-                };
-                out.push_str(&replaced);
-            }
+/// Kind of an interpolation token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    /// Workflow variable: `{{name}}`.
+    Var,
+    /// Global: `{{@name}}`.
+    Global,
+    /// Event field: `{{$path}}`.
+    Event,
+}
+
+/// A token that could not be resolved during interpolation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingToken {
+    /// Token content without braces or the `@`/`$` prefix (e.g. `app.name`).
+    pub name: String,
+    /// Whether the token referred to a variable or a global.
+    pub kind: TokenKind,
+}
+
+/// Same rendering as `interpolate_string`, plus the list of tokens that failed to resolve
+/// (in order of appearance). Useful for config authoring tools and diagnostics.
+pub fn interpolate_with_report(
+    template: &str,
+    vars: &HashMap<String, String>,
+    globals: &BTreeMap<String, Value>,
+) -> (String, Vec<MissingToken>) {
+    let mut missing = Vec::new();
+    let out = render(template, vars, globals, &mut |m| missing.push(m));
+    (out, missing)
+}
 
+/// Shared rendering path; `on_missing` is called for every unresolved token.
+/// Templates without `{{` are returned as-is without being parsed.
+fn render(
+    template: &str,
+    vars: &HashMap<String, String>,
+    globals: &BTreeMap<String, Value>,
+    on_missing: &mut dyn FnMut(MissingToken),
+) -> String {
+    if !template.contains("{{") {
+        return template.to_string();
+    }
+    Template::parse(template).render_with(vars, globals, None, on_missing)
+}
+
+/// A template pre-split into literal text and tokens, so repeated renders skip the
+/// delimiter scan. Renders exactly like `interpolate_string` on the source text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Token {
+        /// Original `{{ ... }}` text, emitted when the token does not resolve.
+        raw: String,
+        name: String,
+        kind: TokenKind,
+        filters: Vec<Filter>,
+        /// Text used when the name does not resolve (`{{name:default}}`).
+        default: Option<String>,
+    },
+    /// A `{{~...}}` token, producing a fresh value on every render.
+    Random {
+        generator: Generator,
+        filters: Vec<Filter>,
+    },
+}
+
+/// Source of a `{{~...}}` token value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Generator {
+    /// `~rand_int:lo-hi`, inclusive.
+    RandInt { lo: i64, hi: i64 },
+    /// `~uuid`, a v4 UUID.
+    Uuid,
+}
+
+impl Generator {
+    fn parse(spec: &str) -> Option<Self> {
+        if spec == "uuid" {
+            return Some(Self::Uuid);
+        }
+        let range = spec.strip_prefix("rand_int:")?.trim();
+        // Skip the first character so a negative lower bound keeps its sign
+        let sep = range.get(1..)?.find('-')? + 1;
+        let lo = range[..sep].trim().parse().ok()?;
+        let hi = range[sep + 1..].trim().parse().ok()?;
+        (lo <= hi).then_some(Self::RandInt { lo, hi })
+    }
+
+    fn generate(self) -> String {
+        match self {
+            Self::RandInt { lo, hi } => random_range(lo..=hi).to_string(),
+            Self::Uuid => uuid::Uuid::new_v4().to_string(),
+        }
+    }
+}
+
+/// A `|filter` applied to a resolved token value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Filter {
+    Upper,
+    Lower,
+    Trim,
+    Basename,
+}
+
+impl Filter {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "upper" => Some(Self::Upper),
+            "lower" => Some(Self::Lower),
+            "trim" => Some(Self::Trim),
+            "basename" => Some(Self::Basename),
+            _ => None,
+        }
+    }
+
+    fn apply(self, value: String) -> String {
+        match self {
+            Self::Upper => value.to_uppercase(),
+            Self::Lower => value.to_lowercase(),
+            Self::Trim => value.trim().to_string(),
+            Self::Basename => value
+                .trim_end_matches(['/', '\\'])
+                .rsplit(['/', '\\'])
+                .next()
+                .unwrap_or_default()
+                .to_string(),
+        }
+    }
+}
+
+impl Template {
+    /// Split `template` into segments (same token rules as `interpolate_string`).
+    pub fn parse(template: &str) -> Self {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut idx = 0;
+        let bytes = template.as_bytes();
+
+        while let Some(start) = find_subslice(bytes, b"{{", idx) {
+            literal.push_str(&template[idx..start]);
+
+            // Find the end delimiter
+            let content_start = start + 2;
+            let Some(end) = find_subslice(bytes, b"}}", content_start) else {
+                // No matching end: the rest is literal text
+                idx = start;
+                break;
+            };
+            let raw = &template[start..end + 2];
+            let token = template[content_start..end].trim();
+
+            match parse_token(token, raw) {
+                Some(segment) => {
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(segment);
+                }
+                // Keep empty tokens, unknown filters and unknown generators intact
+                None => literal.push_str(raw),
+            }
             idx = end + 2;
-        } else {
-            // No matching end, push rest and stop
-            out.push_str(&template[start..]);
-            idx = template.len();
-            break;
         }
+
+        literal.push_str(&template[idx..]);
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+        Self { segments }
+    }
+
+    /// True if the template has no tokens (rendering returns it unchanged).
+    pub fn is_literal(&self) -> bool {
+        self.segments
+            .iter()
+            .all(|s| matches!(s, Segment::Literal(_)))
     }
 
-    // Push any trailing text
-    if idx < template.len() {
-        out.push_str(&template[idx..]);
+    /// The JSON value behind a template made of a single `{{@global}}` or `{{$path}}`
+    /// token without filters, keeping its type (e.g. a number stays a number).
+    /// `None` for any other template, or when the token does not resolve.
+    pub fn typed_value(&self, globals: &BTreeMap<String, Value>, event: &Value) -> Option<Value> {
+        let [
+            Segment::Token {
+                name,
+                kind,
+                filters,
+                ..
+            },
+        ] = self.segments.as_slice()
+        else {
+            return None;
+        };
+        if !filters.is_empty() {
+            return None;
+        }
+        match kind {
+            TokenKind::Global => lookup_global_value(globals, name).cloned(),
+            TokenKind::Event => get_json_path(event, name).cloned(),
+            TokenKind::Var => None,
+        }
     }
 
-    out
+    /// Render with the given variables and globals; unknown tokens are left intact.
+    pub fn render(
+        &self,
+        vars: &HashMap<String, String>,
+        globals: &BTreeMap<String, Value>,
+    ) -> String {
+        self.render_with(vars, globals, None, &mut |_| {})
+    }
+
+    /// Render like `render`, also resolving `{{$path}}` tokens against `event`.
+    pub fn render_with_event(
+        &self,
+        vars: &HashMap<String, String>,
+        globals: &BTreeMap<String, Value>,
+        event: &Value,
+    ) -> String {
+        self.render_with(vars, globals, Some(event), &mut |_| {})
+    }
+
+    fn render_with(
+        &self,
+        vars: &HashMap<String, String>,
+        globals: &BTreeMap<String, Value>,
+        event: Option<&Value>,
+        on_missing: &mut dyn FnMut(MissingToken),
+    ) -> String {
+        let mut out = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(text) => out.push_str(text),
+                Segment::Random { generator, filters } => {
+                    let value = filters.iter().fold(generator.generate(), |v, f| f.apply(v));
+                    out.push_str(&value);
+                }
+                Segment::Token {
+                    raw,
+                    name,
+                    kind,
+                    filters,
+                    default,
+                } => {
+                    let resolved = match kind {
+                        TokenKind::Global => lookup_global(globals, name),
+                        TokenKind::Var => vars.get(name).cloned(),
+                        TokenKind::Event => event
+                            .and_then(|e| get_json_path(e, name))
+                            .map(json_value_to_string),
+                    }
+                    .or_else(|| default.clone())
+                    .map(|value| filters.iter().fold(value, |v, f| f.apply(v)));
+                    match resolved {
+                        Some(value) => out.push_str(&value),
+                        None => {
+                            // Unknown -> keep original token
+                            out.push_str(raw);
+                            on_missing(MissingToken {
+                                name: name.clone(),
+                                kind: *kind,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Parse the (trimmed) content of a `{{...}}` token; `None` if it stays literal text.
+fn parse_token(token: &str, raw: &str) -> Option<Segment> {
+    // `~` tokens use `:` for their arguments and take no default
+    let (expr, default) = match token.split_once(':') {
+        Some((expr, default)) if !token.starts_with('~') => (expr, Some(default.to_string())),
+        _ => (token, None),
+    };
+    let mut parts = expr.split('|');
+    let path = parts.next().unwrap_or_default().trim();
+    let filters = parts
+        .map(|f| Filter::parse(f.trim()))
+        .collect::<Option<Vec<_>>>()?;
+    if path.is_empty() {
+        return None;
+    }
+
+    if let Some(spec) = path.strip_prefix('~') {
+        let generator = Generator::parse(spec.trim())?;
+        return Some(Segment::Random { generator, filters });
+    }
+    let (name, kind) = if let Some(stripped) = path.strip_prefix('@') {
+        (stripped.trim(), TokenKind::Global)
+    } else if let Some(stripped) = path.strip_prefix('$') {
+        (stripped.trim(), TokenKind::Event)
+    } else {
+        (path, TokenKind::Var)
+    };
+    Some(Segment::Token {
+        raw: raw.to_string(),
+        name: name.to_string(),
+        kind,
+        filters,
+        default,
+    })
 }
 
 /// Interpolates all string values in a JSON structure (recursively).
@@ -95,25 +375,150 @@ pub fn interpolate_json(
     }
 }
 
+/// Resolve `{{@...}}` references between globals so derived globals are precomputed.
+///
+/// - String values (including strings nested in arrays/objects) are interpolated against
+///   the other globals, resolving dependencies first (e.g. `"greeting": "Hello {{@app}}"`).
+/// - Reference cycles (`a -> b -> a`) and chains deeper than `MAX_GLOBAL_REF_DEPTH` are
+///   not expanded: the offending tokens are left intact and a warning is logged.
+/// - Non-global tokens (`{{var}}`) are untouched; they are resolved at run time.
+pub fn resolve_global_references(globals: &BTreeMap<String, Value>) -> BTreeMap<String, Value> {
+    let mut resolved = BTreeMap::new();
+    for key in globals.keys() {
+        resolve_global(key, globals, &mut resolved, &mut Vec::new());
+    }
+    resolved
+}
+
+fn resolve_global(
+    key: &str,
+    globals: &BTreeMap<String, Value>,
+    resolved: &mut BTreeMap<String, Value>,
+    stack: &mut Vec<String>,
+) {
+    if resolved.contains_key(key) {
+        return;
+    }
+    let Some(raw) = globals.get(key) else {
+        return;
+    };
+
+    stack.push(key.to_string());
+    let value = resolve_global_value(raw, globals, resolved, stack);
+    stack.pop();
+
+    if value_has_global_token(&value) {
+        warn!(
+            target: "notabot::interpolation",
+            global = %key,
+            "Global still contains unresolved '{{{{@...}}}}' references after load-time resolution"
+        );
+    }
+    resolved.insert(key.to_string(), value);
+}
+
+fn resolve_global_value(
+    value: &Value,
+    globals: &BTreeMap<String, Value>,
+    resolved: &mut BTreeMap<String, Value>,
+    stack: &mut Vec<String>,
+) -> Value {
+    match value {
+        Value::String(s) => {
+            let mut deps = BTreeMap::new();
+            for dep in referenced_global_keys(s) {
+                if stack.contains(&dep) {
+                    warn!(
+                        target: "notabot::interpolation",
+                        cycle = %format!("{} -> {}", stack.join(" -> "), dep),
+                        "Global reference cycle detected; leaving token intact"
+                    );
+                    continue;
+                }
+                if stack.len() >= MAX_GLOBAL_REF_DEPTH {
+                    warn!(
+                        target: "notabot::interpolation",
+                        global = %dep, max_depth = MAX_GLOBAL_REF_DEPTH,
+                        "Global reference chain too deep; leaving token intact"
+                    );
+                    continue;
+                }
+                resolve_global(&dep, globals, resolved, stack);
+                if let Some(v) = resolved.get(&dep) {
+                    deps.insert(dep, v.clone());
+                }
+            }
+            Value::String(interpolate_string(s, &HashMap::new(), &deps))
+        }
+        Value::Array(arr) => Value::Array(
+            arr.iter()
+                .map(|v| resolve_global_value(v, globals, resolved, stack))
+                .collect(),
+        ),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(k, v)| (k.clone(), resolve_global_value(v, globals, resolved, stack)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+/// Top-level global keys referenced by `{{@key...}}` tokens in `template`.
+fn referenced_global_keys(template: &str) -> Vec<String> {
+    let bytes = template.as_bytes();
+    let mut keys = Vec::new();
+    let mut idx = 0;
+    while let Some(start) = find_subslice(bytes, b"{{", idx) {
+        let Some(end) = find_subslice(bytes, b"}}", start + 2) else {
+            break;
+        };
+        if let Some(path) = template[start + 2..end].trim().strip_prefix('@') {
+            let key = path
+                .split(['.', '|', ':'])
+                .next()
+                .unwrap_or_default()
+                .trim();
+            if !key.is_empty() && !keys.iter().any(|k| k == key) {
+                keys.push(key.to_string());
+            }
+        }
+        idx = end + 2;
+    }
+    keys
+}
+
+fn value_has_global_token(value: &Value) -> bool {
+    match value {
+        Value::String(s) => !referenced_global_keys(s).is_empty(),
+        Value::Array(arr) => arr.iter().any(value_has_global_token),
+        Value::Object(map) => map.values().any(value_has_global_token),
+        _ => false,
+    }
+}
+
 /// Find the first occurrence of `needle` in `haystack` starting at `from`.
 fn find_subslice(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
     if needle.is_empty() || from >= haystack.len() {
         return None;
     }
-    let end = haystack.len().saturating_sub(needle.len()) + 1;
-    for i in from..end {
-        if &haystack[i..i + needle.len()] == needle {
-            return Some(i);
-        }
-    }
-    None
+    haystack[from..]
+        .windows(needle.len())
+        .position(|w| w == needle)
+        .map(|pos| from + pos)
 }
 
-/// Lookup a global value using a dotted path (e.g., "app.name").
+/// Lookup a global value using a dotted path (e.g., "app.name", "servers.0").
+/// Numeric segments index into arrays.
 /// Returns a string representation:
 /// - If the final value is a JSON string, the contained string is returned.
 /// - Otherwise, the value is serialized to compact JSON (e.g., numbers, objects).
 fn lookup_global(globals: &BTreeMap<String, Value>, path: &str) -> Option<String> {
+    lookup_global_value(globals, path).map(json_value_to_string)
+}
+
+/// The global value at a dotted path (see `lookup_global`).
+fn lookup_global_value<'a>(globals: &'a BTreeMap<String, Value>, path: &str) -> Option<&'a Value> {
     let mut segments = path.split('.');
 
     // First segment is a top-level key in the globals map
@@ -126,13 +531,42 @@ fn lookup_global(globals: &BTreeMap<String, Value>, path: &str) -> Option<String
             Value::Object(map) => {
                 current = map.get(seg)?;
             }
+            // Numeric segments index into arrays (out of range -> unresolved)
+            Value::Array(items) => {
+                current = items.get(seg.parse::<usize>().ok()?)?;
+            }
             _ => return None,
         }
     }
 
-    match current {
-        Value::String(s) => Some(s.clone()),
-        other => Some(other.to_string()),
+    Some(current)
+}
+
+/// Get a JSON value by a dotted path (e.g., "order.side").
+pub(crate) fn get_json_path<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    if path.is_empty() {
+        return Some(value);
+    }
+    let mut current = value;
+    for seg in path.split('.') {
+        match current {
+            Value::Object(map) => {
+                current = map.get(seg)?;
+            }
+            _ => return None,
+        }
+    }
+    Some(current)
+}
+
+/// Convert a JSON value to a user-friendly string:
+/// - Strings are returned as-is.
+/// - Numbers/bools are rendered via to_string().
+/// - Arrays/objects are serialized as compact JSON.
+pub(crate) fn json_value_to_string(v: &Value) -> String {
+    match v {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
     }
 }
 
@@ -199,6 +633,206 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_interpolate_with_report_lists_unresolved_tokens() {
+        let mut vars = HashMap::new();
+        let mut globals = BTreeMap::new();
+        vars.insert("user".into(), "Alice".into());
+        globals.insert("app".into(), json!({ "name": "Notabot" }));
+
+        let t = "{{user}} {{ missing }} {{@app.name}} {{@app.version}} {{}}";
+        let (out, missing) = interpolate_with_report(t, &vars, &globals);
+        assert_eq!(out, interpolate_string(t, &vars, &globals));
+        assert_eq!(out, "Alice {{ missing }} Notabot {{@app.version}} {{}}");
+        assert_eq!(
+            missing,
+            vec![
+                MissingToken {
+                    name: "missing".into(),
+                    kind: TokenKind::Var,
+                },
+                MissingToken {
+                    name: "app.version".into(),
+                    kind: TokenKind::Global,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_interpolate_indexes_array_globals() {
+        let vars = HashMap::new();
+        let mut globals = BTreeMap::new();
+        globals.insert("servers".into(), json!(["a", "b"]));
+        globals.insert("cluster".into(), json!({ "nodes": [{ "host": "n0" }, 7] }));
+
+        let out = interpolate_string(
+            "{{@servers.0}} {{@servers.1}} {{@cluster.nodes.0.host}} {{@cluster.nodes.1}}",
+            &vars,
+            &globals,
+        );
+        assert_eq!(out, "a b n0 7");
+
+        // Out-of-range and non-numeric indices stay unresolved
+        let out = interpolate_string("{{@servers.2}} {{@servers.x}}", &vars, &globals);
+        assert_eq!(out, "{{@servers.2}} {{@servers.x}}");
+    }
+
+    #[test]
+    fn test_filters_apply_left_to_right() {
+        let vars = HashMap::from([
+            ("x".to_string(), "  Mixed Case ".to_string()),
+            ("path".to_string(), "C:\\shots\\day 1/shot.png".to_string()),
+            ("dir".to_string(), "/tmp/out/".to_string()),
+        ]);
+        let globals = BTreeMap::from([("app".to_string(), json!({ "name": "Notabot" }))]);
+
+        let out = interpolate_string(
+            "[{{x|trim|upper}}] [{{ x | lower }}] {{path|basename}} {{dir|basename}} {{@app.name|upper}}",
+            &vars,
+            &globals,
+        );
+        assert_eq!(out, "[MIXED CASE] [  mixed case ] shot.png out NOTABOT");
+
+        // Unknown filters and unknown names keep the whole token
+        let (out, missing) = interpolate_with_report("{{x|shout}} {{nope|upper}}", &vars, &globals);
+        assert_eq!(out, "{{x|shout}} {{nope|upper}}");
+        assert_eq!(
+            missing,
+            vec![MissingToken {
+                name: "nope".into(),
+                kind: TokenKind::Var,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_defaults_fill_in_missing_tokens_only() {
+        let vars = HashMap::from([
+            ("name".to_string(), "Alice".to_string()),
+            ("empty".to_string(), String::new()),
+        ]);
+        let globals = BTreeMap::from([("app".to_string(), json!("Notabot"))]);
+
+        // Present values win, including empty strings
+        assert_eq!(
+            interpolate_string(
+                "{{name:anon}}|{{empty:unused}}|{{@app:none}}",
+                &vars,
+                &globals
+            ),
+            "Alice||Notabot"
+        );
+
+        // Absent names use the default (which may be empty or contain ':' and '{')
+        let (out, missing) = interpolate_with_report(
+            "{{nick:anon}} {{@theme.color:blue}} [{{title:}}] {{url:http://x}} {{raw:{a}}}",
+            &vars,
+            &globals,
+        );
+        assert_eq!(out, "anon blue [] http://x {a}");
+        assert!(missing.is_empty(), "{missing:?}");
+
+        // Filters apply to the default too
+        assert_eq!(
+            interpolate_string("{{nick|upper:anon}}", &vars, &globals),
+            "ANON"
+        );
+    }
+
+    #[test]
+    fn test_random_tokens_are_fresh_and_in_range() {
+        let vars = HashMap::new();
+        let globals = BTreeMap::new();
+        let template = Template::parse("{{~rand_int:100-999}}|{{ ~rand_int: -3-3 }}|{{~uuid}}");
+
+        let mut ids = Vec::new();
+        for _ in 0..200 {
+            let out = template.render(&vars, &globals);
+            let parts: Vec<&str> = out.split('|').collect();
+            let n: i64 = parts[0].parse().unwrap();
+            assert!((100..=999).contains(&n), "{n}");
+            let m: i64 = parts[1].parse().unwrap();
+            assert!((-3..=3).contains(&m), "{m}");
+            let id = uuid::Uuid::parse_str(parts[2]).unwrap();
+            assert_eq!(id.get_version_num(), 4);
+            ids.push(id);
+        }
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), 200, "uuids must be drawn on every render");
+
+        assert_eq!(
+            interpolate_string("{{~rand_int:5-5}} {{~uuid|upper}}", &vars, &globals).len(),
+            2 + 36
+        );
+        // Bad ranges and unknown generators stay intact
+        let kept = "{{~rand_int:9-1}} {{~rand_int:x-2}} {{~dice}}";
+        assert_eq!(interpolate_string(kept, &vars, &globals), kept);
+    }
+
+    #[test]
+    fn test_typed_value_only_for_single_unfiltered_tokens() {
+        let globals = BTreeMap::from([("net".to_string(), json!({ "port": 8080 }))]);
+        let event = json!({ "ok": true });
+        let typed = |t: &str| Template::parse(t).typed_value(&globals, &event);
+
+        assert_eq!(typed("{{@net.port}}"), Some(json!(8080)));
+        assert_eq!(typed(" {{ $ok }}"), None);
+        assert_eq!(typed("{{ $ok }}"), Some(json!(true)));
+        assert_eq!(typed("{{@net.port|trim}}"), None);
+        assert_eq!(typed("{{@net.port}}{{@net.port}}"), None);
+        assert_eq!(typed("{{@missing:1}}"), None);
+        assert_eq!(typed("{{port}}"), None);
+    }
+
+    #[test]
+    fn test_interpolate_short_strings() {
+        let vars = HashMap::new();
+        let globals = BTreeMap::new();
+        assert_eq!(interpolate_string("3", &vars, &globals), "3");
+        assert_eq!(interpolate_string("{", &vars, &globals), "{");
+    }
+
+    #[test]
+    fn test_interpolate_with_report_empty_when_all_resolved() {
+        let vars = HashMap::from([("x".to_string(), "1".to_string())]);
+        let (out, missing) = interpolate_with_report("x={{x}}", &vars, &BTreeMap::new());
+        assert_eq!(out, "x=1");
+        assert!(missing.is_empty());
+    }
+
+    #[test]
+    fn test_resolve_global_references_chains() {
+        let mut globals = BTreeMap::new();
+        globals.insert("app".into(), json!("Notabot"));
+        globals.insert("greeting".into(), json!("Hello {{@app}}"));
+        globals.insert(
+            "banner".into(),
+            json!({ "text": "{{@greeting}}!", "user": "{{user}}" }),
+        );
+
+        let out = resolve_global_references(&globals);
+        assert_eq!(out["greeting"], json!("Hello Notabot"));
+        assert_eq!(
+            out["banner"],
+            json!({ "text": "Hello Notabot!", "user": "{{user}}" })
+        );
+        assert_eq!(out["app"], json!("Notabot"));
+    }
+
+    #[test]
+    fn test_resolve_global_references_cycles_and_unknowns_stay_intact() {
+        let mut globals = BTreeMap::new();
+        globals.insert("a".into(), json!("A({{@b}})"));
+        globals.insert("b".into(), json!("B({{@a}})"));
+        globals.insert("c".into(), json!("{{@missing}} and {{@c}}"));
+
+        let out = resolve_global_references(&globals);
+        assert_eq!(out["a"], json!("A(B({{@a}}))"));
+        assert_eq!(out["c"], json!("{{@missing}} and {{@c}}"));
+    }
+
     #[test]
     fn test_interpolate_json_recursive() {
         let mut vars = HashMap::new();
@@ -223,4 +857,73 @@ mod tests {
             })
         );
     }
+
+    fn template_cases() -> Vec<&'static str> {
+        vec![
+            "",
+            "plain text, no tokens",
+            "{",
+            "}}",
+            "Hi {{user}} from {{@app}}",
+            "{{ user }}{{user}}",
+            "{{}} and {{   }} stay",
+            "{{@ app.name }} / {{@servers.1}}",
+            "{{missing}} and {{@missing}}",
+            "unterminated {{user",
+            "{{user}} then {{ unterminated",
+            "{{{user}}}",
+            "{{user|upper}} {{user|nope}} {{|upper}}",
+            "{{user:x}} {{missing:fallback}} {{@missing:}} {{:x}}",
+            "{{$order.side}} {{ $missing:none }}",
+            "{{~nope}} {{~rand_int:2-1}}",
+            "héllo {{user}} ✓",
+        ]
+    }
+
+    #[test]
+    fn test_template_matches_interpolate_string() {
+        let mut vars = HashMap::new();
+        vars.insert("user".to_string(), "Alice".to_string());
+        let mut globals = BTreeMap::new();
+        globals.insert("app".into(), json!({"name": "Notabot"}));
+        globals.insert("servers".into(), json!(["a", "b"]));
+
+        for case in template_cases() {
+            let template = Template::parse(case);
+            assert_eq!(
+                template.render(&vars, &globals),
+                interpolate_string(case, &vars, &globals),
+                "template {case:?}"
+            );
+        }
+        assert!(Template::parse("no {{}} tokens {{ here").is_literal());
+        assert!(!Template::parse("{{user}}").is_literal());
+    }
+
+    #[test]
+    fn test_cached_template_renders_like_naive_over_many_iterations() {
+        let mut vars = HashMap::new();
+        let globals = BTreeMap::from([("app".to_string(), json!("Notabot"))]);
+        let cases = template_cases();
+        let cached: Vec<Template> = cases.iter().map(|c| Template::parse(c)).collect();
+
+        let iterations = 2_000;
+        let mut naive_out = Vec::with_capacity(iterations * cases.len());
+        for i in 0..iterations {
+            vars.insert("user".to_string(), format!("user{i}"));
+            for case in &cases {
+                naive_out.push(interpolate_string(case, &vars, &globals));
+            }
+        }
+
+        let mut cached_out = Vec::with_capacity(naive_out.len());
+        for i in 0..iterations {
+            vars.insert("user".to_string(), format!("user{i}"));
+            for template in &cached {
+                cached_out.push(template.render(&vars, &globals));
+            }
+        }
+
+        assert_eq!(naive_out, cached_out);
+    }
 }
@@ -3,8 +3,20 @@
 //! This module aggregates utility helpers used across the crate.
 //!
 //! Submodules:
+//! - `clipboard`: Clipboard helpers (text behind the `clipboard` feature, images behind
+//!   `clipboard-image`).
+//! - `http`: Outgoing HTTP requests (behind the `http-client` feature).
 //! - `interpolation`: Templating helpers for variables like `{{var}}` and globals `{{@key}}`.
-//! - `window`: OS-specific window management helpers (no-op on unsupported platforms).
+//! - `ocr`: Text recognition via Tesseract (behind the `ocr` feature).
+//! - `pattern`: Glob-style `*` matching for file names and event types.
+//! - `screen`: Screen pixel sampling (Windows, or X11 behind the `x11` feature).
+//! - `window`: OS-specific window management helpers (X11 behind the `x11` feature;
+//!   no-op on unsupported platforms).
 
+pub mod clipboard;
+pub mod http;
 pub mod interpolation;
+pub mod ocr;
+pub mod pattern;
+pub mod screen;
 pub mod window;
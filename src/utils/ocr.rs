@@ -0,0 +1,124 @@
+use anyhow::Result;
+use std::path::Path;
+use tracing::debug;
+
+/// The Tesseract command-line tool run by `recognize_text`.
+#[cfg(feature = "ocr")]
+const TESSERACT: &str = "tesseract";
+
+/// Recognize the text in an image file with Tesseract.
+///
+/// Requires the `ocr` cargo feature and the `tesseract` command-line tool on `PATH`
+/// (no native library is linked, so a missing tool is only noticed here, with an error
+/// saying so). Without the feature this always returns an error so callers can surface
+/// a clear message instead of silently never matching. Tesseract runs as a child process
+/// that is awaited, not waited on, and killed if the future is dropped.
+#[cfg(feature = "ocr")]
+pub async fn recognize_text(image: &Path) -> Result<String> {
+    run_tesseract(TESSERACT, image).await
+}
+
+#[cfg(feature = "ocr")]
+async fn run_tesseract(program: &str, image: &Path) -> Result<String> {
+    use anyhow::{anyhow, bail};
+    use std::process::Stdio;
+
+    debug!(target: "notabot::ocr", image = %image.display(), "Running tesseract");
+    let output = tokio::process::Command::new(program)
+        .arg(image)
+        .arg("stdout")
+        .stdin(Stdio::null())
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                anyhow!(
+                    "`{program}` was not found on PATH: the `ocr` feature needs the Tesseract \
+                     OCR command-line tool installed"
+                )
+            } else {
+                anyhow!(e).context(format!("Failed to run `{program}`"))
+            }
+        })?;
+    if !output.status.success() {
+        bail!(
+            "tesseract failed on {}: {}",
+            image.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(not(feature = "ocr"))]
+pub async fn recognize_text(image: &Path) -> Result<String> {
+    debug!(target: "notabot::ocr", image = %image.display(), "OCR requested");
+    anyhow::bail!("OCR requires the `ocr` feature")
+}
+
+/// Recognize the text in a PNG image held in memory (e.g., a screen capture).
+///
+/// The image is written to a temporary file for `recognize_text`, which is removed
+/// afterwards. Same requirements as `recognize_text`.
+#[cfg(feature = "ocr")]
+pub async fn recognize_png(png: &[u8]) -> Result<String> {
+    use anyhow::Context;
+
+    let file = tempfile::Builder::new()
+        .prefix("notabot-ocr-")
+        .suffix(".png")
+        .tempfile()
+        .context("Failed to create a temporary file for OCR")?;
+    tokio::fs::write(file.path(), png)
+        .await
+        .with_context(|| format!("Failed to write {}", file.path().display()))?;
+    recognize_text(file.path()).await
+}
+
+#[cfg(not(feature = "ocr"))]
+pub async fn recognize_png(png: &[u8]) -> Result<String> {
+    debug!(target: "notabot::ocr", len = png.len(), "OCR requested");
+    anyhow::bail!("OCR requires the `ocr` feature")
+}
+
+/// Case-insensitive check that `needle` appears in the recognized `text`.
+/// Runs of whitespace (including line breaks inserted by OCR) compare equal to one space.
+pub fn text_contains(text: &str, needle: &str) -> bool {
+    let normalize = |s: &str| {
+        s.split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase()
+    };
+    normalize(text).contains(&normalize(needle))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_contains_ignores_case_and_line_breaks() {
+        let text = "Order\nSUCCESSFUL  Payment\n";
+        assert!(text_contains(text, "successful payment"));
+        assert!(text_contains(text, "order"));
+        assert!(!text_contains(text, "failed"));
+    }
+
+    #[cfg(not(feature = "ocr"))]
+    #[tokio::test]
+    async fn recognize_text_without_feature_is_an_error() {
+        let err = recognize_text(Path::new("shot.png")).await.unwrap_err();
+        assert!(err.to_string().contains("`ocr` feature"));
+    }
+
+    #[cfg(feature = "ocr")]
+    #[tokio::test]
+    async fn missing_tesseract_is_reported_clearly() {
+        let err = run_tesseract("notabot-no-such-tesseract", Path::new("shot.png"))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("was not found on PATH"), "{err}");
+    }
+}
@@ -0,0 +1,91 @@
+//! Glob-style matching shared by directory sources (file names) and event bindings
+//! (event types).
+
+/// Very small glob-like matcher supporting `*` wildcards (match any substring).
+/// Multiple `*` supported. Case-sensitive.
+pub fn simple_pattern_match(text: &str, pattern: &str) -> bool {
+    if pattern == "*" {
+        return true;
+    }
+    if !pattern.contains('*') {
+        return text == pattern;
+    }
+
+    let mut parts: Vec<&str> = pattern.split('*').collect();
+    if parts.is_empty() {
+        return true;
+    }
+
+    let starts_with_star = pattern.starts_with('*');
+    let ends_with_star = pattern.ends_with('*');
+
+    // Trim leading/trailing empties from boundary stars
+    if starts_with_star && parts.first().is_some_and(|first| first.is_empty()) {
+        parts.remove(0);
+    }
+    if ends_with_star && parts.last().is_some_and(|last| last.is_empty()) {
+        parts.pop();
+    }
+
+    let mut remainder = text;
+
+    // First segment (prefix) if no leading star
+    if !starts_with_star && let Some(first) = parts.first() {
+        if !remainder.starts_with(first) {
+            return false;
+        }
+        remainder = &remainder[first.len()..];
+        parts.remove(0);
+    }
+
+    // Intermediate segments
+    while parts.len() > 1 {
+        let seg = parts.remove(0);
+        if let Some(pos) = remainder.find(seg) {
+            remainder = &remainder[pos + seg.len()..];
+        } else {
+            return false;
+        }
+    }
+
+    // Last segment
+    if let Some(last) = parts.first() {
+        if !ends_with_star {
+            remainder.ends_with(last)
+        } else {
+            remainder.contains(last)
+        }
+    } else {
+        // No segments -> pattern was all stars
+        starts_with_star || ends_with_star || remainder.is_empty()
+    }
+}
+
+/// Length of the literal text before the first `*` of `pattern` (all of it without one).
+pub fn literal_prefix_len(pattern: &str) -> usize {
+    pattern.find('*').unwrap_or(pattern.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simple_pattern_match() {
+        assert!(simple_pattern_match("event_123.json", "event_*.json"));
+        assert!(simple_pattern_match("data.txt", "*.txt"));
+        assert!(simple_pattern_match("abc", "abc"));
+        assert!(!simple_pattern_match("abc", "abcd"));
+        assert!(simple_pattern_match("abcd", "a*d"));
+        assert!(simple_pattern_match("axyzd", "a*z*d"));
+        assert!(!simple_pattern_match("abcd", "a*z*c"));
+        assert!(simple_pattern_match("anything", "*"));
+    }
+
+    #[test]
+    fn literal_prefix_stops_at_first_star() {
+        assert_eq!(literal_prefix_len("order.*"), 6);
+        assert_eq!(literal_prefix_len("*.created"), 0);
+        assert_eq!(literal_prefix_len("order"), 5);
+    }
+}
@@ -0,0 +1,399 @@
+use anyhow::{Context, Result, bail};
+use std::fmt;
+use tracing::trace;
+
+use crate::config::Rect;
+
+/// An 8-bit-per-channel RGB color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Rgb {
+    /// Parse a `#RRGGBB` hex color (the `#` is optional, digits are case-insensitive).
+    pub fn parse_hex(s: &str) -> Result<Self> {
+        let hex = s.trim();
+        let hex = hex.strip_prefix('#').unwrap_or(hex);
+        if hex.len() != 6 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            bail!("Invalid color '{s}' (expected #RRGGBB)");
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16);
+        Ok(Self {
+            r: channel(0)?,
+            g: channel(2)?,
+            b: channel(4)?,
+        })
+    }
+
+    /// Whether every channel differs from `other` by at most `tolerance`.
+    pub fn within(self, other: Rgb, tolerance: u8) -> bool {
+        self.r.abs_diff(other.r) <= tolerance
+            && self.g.abs_diff(other.g) <= tolerance
+            && self.b.abs_diff(other.b) <= tolerance
+    }
+}
+
+impl fmt::Display for Rgb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+/// Read the color of the screen pixel at (`x`, `y`).
+///
+/// Supported on Windows (GDI) and, with the `x11` feature, on X11. Elsewhere this
+/// returns an error so callers do not silently branch on a made-up color.
+pub fn pixel_color(x: i32, y: i32) -> Result<Rgb> {
+    trace!(target: "notabot::screen", x, y, "pixel_color requested");
+    pixel_color_impl(x, y).with_context(|| format!("Failed to read the pixel at ({x}, {y})"))
+}
+
+#[cfg(windows)]
+fn pixel_color_impl(x: i32, y: i32) -> Result<Rgb> {
+    use windows::Win32::Graphics::Gdi::{CLR_INVALID, GetDC, GetPixel, ReleaseDC};
+
+    // SAFETY: the screen DC is released before returning.
+    let color = unsafe {
+        let hdc = GetDC(None);
+        if hdc.is_invalid() {
+            bail!("GetDC failed");
+        }
+        let color = GetPixel(hdc, x, y);
+        ReleaseDC(None, hdc);
+        color.0
+    };
+    if color == CLR_INVALID {
+        bail!("the point is outside the screen");
+    }
+    // COLORREF is 0x00BBGGRR
+    Ok(Rgb {
+        r: (color & 0xff) as u8,
+        g: ((color >> 8) & 0xff) as u8,
+        b: ((color >> 16) & 0xff) as u8,
+    })
+}
+
+#[cfg(all(feature = "x11", not(windows)))]
+fn pixel_color_impl(x: i32, y: i32) -> Result<Rgb> {
+    let shot = capture_impl(Some(Rect {
+        x,
+        y,
+        width: 1,
+        height: 1,
+    }))?;
+    Ok(Rgb {
+        r: shot.rgba[0],
+        g: shot.rgba[1],
+        b: shot.rgba[2],
+    })
+}
+
+/// Extract the channel selected by `mask` from `pixel`, scaled to 8 bits.
+#[cfg(all(feature = "x11", not(windows)))]
+fn channel(pixel: u32, mask: u32) -> u8 {
+    if mask == 0 {
+        return 0;
+    }
+    let max = u64::from(mask >> mask.trailing_zeros());
+    let value = u64::from((pixel & mask) >> mask.trailing_zeros());
+    (value * 255 / max) as u8
+}
+
+#[cfg(all(not(feature = "x11"), not(windows)))]
+fn pixel_color_impl(_x: i32, _y: i32) -> Result<Rgb> {
+    bail!(
+        "reading screen pixels is not supported on this platform (enable the `x11` feature on Linux/X11)"
+    )
+}
+
+/// A captured screen area: `width * height` RGBA pixels, row-major.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Screenshot {
+    pub width: usize,
+    pub height: usize,
+    pub rgba: Vec<u8>,
+}
+
+impl Screenshot {
+    /// Encode the image as an 8-bit RGBA PNG.
+    pub fn to_png(&self) -> Result<Vec<u8>> {
+        let mut png = Vec::new();
+        let mut encoder = png::Encoder::new(
+            &mut png,
+            u32::try_from(self.width).context("image is too wide")?,
+            u32::try_from(self.height).context("image is too tall")?,
+        );
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().context("Failed to encode PNG")?;
+        writer
+            .write_image_data(&self.rgba)
+            .context("Failed to encode PNG")?;
+        writer.finish().context("Failed to encode PNG")?;
+        Ok(png)
+    }
+}
+
+/// Capture `region` of the screen, or the whole main screen when `None`.
+///
+/// Same platform support as `pixel_color`; elsewhere this returns an error.
+pub fn capture(region: Option<Rect>) -> Result<Screenshot> {
+    trace!(target: "notabot::screen", ?region, "capture requested");
+    capture_impl(region).context("Failed to capture the screen")
+}
+
+#[cfg(windows)]
+fn capture_impl(region: Option<Rect>) -> Result<Screenshot> {
+    use windows::Win32::Graphics::Gdi::{
+        BI_RGB, BITMAPINFO, BITMAPINFOHEADER, BitBlt, CreateCompatibleBitmap, CreateCompatibleDC,
+        DIB_RGB_COLORS, DeleteDC, DeleteObject, GetDC, GetDIBits, ReleaseDC, SRCCOPY, SelectObject,
+    };
+    use windows::Win32::UI::WindowsAndMessaging::{GetSystemMetrics, SM_CXSCREEN, SM_CYSCREEN};
+
+    // SAFETY: plain metric queries.
+    let area = region.unwrap_or_else(|| unsafe {
+        Rect {
+            x: 0,
+            y: 0,
+            width: GetSystemMetrics(SM_CXSCREEN),
+            height: GetSystemMetrics(SM_CYSCREEN),
+        }
+    });
+    if area.width <= 0 || area.height <= 0 {
+        bail!("the capture region is empty");
+    }
+    let (width, height) = (area.width as usize, area.height as usize);
+    let mut pixels = vec![0u8; width * height * 4];
+    // SAFETY: every GDI object created here is released before returning, and
+    // `pixels` holds `height` rows of 32-bit pixels as described by `info`.
+    let copied = unsafe {
+        let screen = GetDC(None);
+        if screen.is_invalid() {
+            bail!("GetDC failed");
+        }
+        let memory = CreateCompatibleDC(Some(screen));
+        let bitmap = CreateCompatibleBitmap(screen, area.width, area.height);
+        let previous = SelectObject(memory, bitmap.into());
+        let blitted = BitBlt(
+            memory,
+            0,
+            0,
+            area.width,
+            area.height,
+            Some(screen),
+            area.x,
+            area.y,
+            SRCCOPY,
+        );
+        SelectObject(memory, previous);
+        // A negative height asks for top-down rows
+        let mut info = BITMAPINFO {
+            bmiHeader: BITMAPINFOHEADER {
+                biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+                biWidth: area.width,
+                biHeight: -area.height,
+                biPlanes: 1,
+                biBitCount: 32,
+                biCompression: BI_RGB.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let lines = GetDIBits(
+            memory,
+            bitmap,
+            0,
+            area.height as u32,
+            Some(pixels.as_mut_ptr().cast()),
+            &mut info,
+            DIB_RGB_COLORS,
+        );
+        let _ = DeleteObject(bitmap.into());
+        let _ = DeleteDC(memory);
+        ReleaseDC(None, screen);
+        blitted.is_ok() && lines == area.height
+    };
+    if !copied {
+        bail!("copying the screen contents failed");
+    }
+    // GDI pixels are BGRA with an undefined alpha channel
+    for pixel in pixels.chunks_exact_mut(4) {
+        pixel.swap(0, 2);
+        pixel[3] = 255;
+    }
+    Ok(Screenshot {
+        width,
+        height,
+        rgba: pixels,
+    })
+}
+
+#[cfg(all(feature = "x11", not(windows)))]
+fn capture_impl(region: Option<Rect>) -> Result<Screenshot> {
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xproto::{ConnectionExt as _, ImageFormat, ImageOrder};
+
+    let (conn, screen_num) = x11rb::connect(None).context("Failed to connect to the X server")?;
+    let setup = conn.setup();
+    let screen = &setup.roots[screen_num];
+    let area = region.unwrap_or(Rect {
+        x: 0,
+        y: 0,
+        width: screen.width_in_pixels.into(),
+        height: screen.height_in_pixels.into(),
+    });
+    let (x, y) = (
+        i16::try_from(area.x).context("x is out of range")?,
+        i16::try_from(area.y).context("y is out of range")?,
+    );
+    let (width, height) = (
+        u16::try_from(area.width).context("width is out of range")?,
+        u16::try_from(area.height).context("height is out of range")?,
+    );
+    if width == 0 || height == 0 {
+        bail!("the capture region is empty");
+    }
+    let image = conn
+        .get_image(
+            ImageFormat::Z_PIXMAP,
+            screen.root,
+            x,
+            y,
+            width,
+            height,
+            u32::MAX,
+        )?
+        .reply()?;
+    let visual = screen
+        .allowed_depths
+        .iter()
+        .flat_map(|depth| &depth.visuals)
+        .find(|visual| visual.visual_id == image.visual)
+        .context("the root window uses an unknown visual")?;
+    let format = setup
+        .pixmap_formats
+        .iter()
+        .find(|format| format.depth == image.depth)
+        .context("the root window uses an unknown pixmap format")?;
+    let bits = usize::from(format.bits_per_pixel);
+    let bytes = bits.div_ceil(8).min(4);
+    let pad = usize::from(format.scanline_pad).max(8);
+    let (width, height) = (usize::from(width), usize::from(height));
+    let stride = (width * bits).div_ceil(pad) * pad / 8;
+    let lsb_first = setup.image_byte_order == ImageOrder::LSB_FIRST;
+
+    let mut rgba = Vec::with_capacity(width * height * 4);
+    for row in 0..height {
+        for col in 0..width {
+            let start = row * stride + col * bits / 8;
+            let data = image
+                .data
+                .get(start..start + bytes)
+                .context("the X server returned a truncated image")?;
+            let pixel = if lsb_first {
+                data.iter()
+                    .rev()
+                    .fold(0u32, |acc, &b| (acc << 8) | u32::from(b))
+            } else {
+                data.iter().fold(0u32, |acc, &b| (acc << 8) | u32::from(b))
+            };
+            rgba.extend([
+                channel(pixel, visual.red_mask),
+                channel(pixel, visual.green_mask),
+                channel(pixel, visual.blue_mask),
+                255,
+            ]);
+        }
+    }
+    Ok(Screenshot {
+        width,
+        height,
+        rgba,
+    })
+}
+
+#[cfg(all(not(feature = "x11"), not(windows)))]
+fn capture_impl(_region: Option<Rect>) -> Result<Screenshot> {
+    bail!(
+        "capturing the screen is not supported on this platform (enable the `x11` feature on Linux/X11)"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hex_accepts_rrggbb() {
+        let c = Rgb::parse_hex("#1E90ff").unwrap();
+        assert_eq!(
+            c,
+            Rgb {
+                r: 0x1e,
+                g: 0x90,
+                b: 0xff
+            }
+        );
+        assert_eq!(Rgb::parse_hex("000000").unwrap(), Rgb { r: 0, g: 0, b: 0 });
+        assert_eq!(c.to_string(), "#1e90ff");
+        assert!(Rgb::parse_hex("#fff").is_err());
+        assert!(Rgb::parse_hex("#gg0000").is_err());
+        assert!(Rgb::parse_hex("#ff00001").is_err());
+    }
+
+    #[test]
+    fn test_within_checks_every_channel() {
+        let target = Rgb {
+            r: 100,
+            g: 150,
+            b: 200,
+        };
+        assert!(target.within(target, 0));
+        assert!(target.within(
+            Rgb {
+                r: 105,
+                g: 145,
+                b: 200
+            },
+            5
+        ));
+        assert!(!target.within(
+            Rgb {
+                r: 100,
+                g: 150,
+                b: 206
+            },
+            5
+        ));
+        assert!(Rgb { r: 0, g: 0, b: 0 }.within(
+            Rgb {
+                r: 255,
+                g: 255,
+                b: 255
+            },
+            255
+        ));
+    }
+
+    #[test]
+    fn test_screenshot_encodes_as_rgba_png() {
+        let shot = Screenshot {
+            width: 2,
+            height: 1,
+            rgba: vec![255, 0, 0, 255, 0, 128, 255, 255],
+        };
+        let png = shot.to_png().unwrap();
+
+        let mut reader = png::Decoder::new(std::io::Cursor::new(png))
+            .read_info()
+            .unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size().unwrap()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        assert_eq!((info.width, info.height), (2, 1));
+        assert_eq!(info.color_type, png::ColorType::Rgba);
+        assert_eq!(&pixels[..info.buffer_size()], shot.rgba.as_slice());
+    }
+}
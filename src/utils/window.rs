@@ -1,5 +1,9 @@
 use anyhow::Result;
-use tracing::{debug, warn};
+#[cfg(any(windows, not(feature = "x11")))]
+use tracing::warn;
+use tracing::{debug, trace};
+
+use crate::config::Rect;
 
 /// Attempt to focus a window whose title contains the given substring.
 ///
@@ -9,11 +13,13 @@ use tracing::{debug, warn};
 /// - Err(_) only for unexpected internal errors.
 ///
 /// Notes:
-/// - This is a placeholder. A real implementation would use the Windows API (e.g., `windows` crate)
-///   to enumerate top-level windows, match by title (case-insensitive contains), bring the window
-///   to the foreground, and restore if minimized.
-/// - On non-Windows platforms, this function is a no-op and returns Ok(false).
-/// - Even on Windows, without linking a proper implementation, this returns Ok(false).
+/// - With the `x11` feature (non-Windows), top-level windows are matched by
+///   `_NET_WM_NAME`/`WM_NAME` and activated through `_NET_ACTIVE_WINDOW`, so an
+///   EWMH-compliant window manager is expected.
+/// - On Windows, visible top-level windows are matched by their title; a minimized match
+///   is restored before being brought to the foreground. Ok(false) is also returned when
+///   Windows refuses the foreground change (e.g., another app has the user's focus lock).
+/// - Elsewhere this function is a no-op and returns Ok(false).
 pub fn focus_window(title_contains: &str) -> Result<bool> {
     debug!(target: "notabot::window", %title_contains, "focus_window requested");
     focus_window_impl(title_contains)
@@ -21,28 +27,384 @@ pub fn focus_window(title_contains: &str) -> Result<bool> {
 
 #[cfg(windows)]
 fn focus_window_impl(title_contains: &str) -> Result<bool> {
-    // Placeholder for real Win32 integration (SetForegroundWindow, ShowWindow, FindWindowEx, etc.)
-    // When implementing:
-    // 1. Enumerate top-level windows.
-    // 2. Query window titles (GetWindowTextW).
-    // 3. Case-insensitive `contains` match against `title_contains`.
-    // 4. If minimized, restore (ShowWindow with SW_RESTORE).
-    // 5. Bring to foreground (SetForegroundWindow).
-    // 6. Return Ok(true) on success.
+    use windows::Win32::UI::WindowsAndMessaging::{
+        IsIconic, SW_RESTORE, SetForegroundWindow, ShowWindow,
+    };
+
+    let Some((hwnd, title)) = win32::top_level_windows()?
+        .into_iter()
+        .find(|(_, title)| title_matches(title, title_contains))
+    else {
+        return Ok(false);
+    };
+    debug!(target: "notabot::window", %title, "Focusing window");
+    // SAFETY: `hwnd` comes from `EnumWindows`; a window closed since then only makes
+    // these calls fail.
+    unsafe {
+        if IsIconic(hwnd).as_bool() {
+            let _ = ShowWindow(hwnd, SW_RESTORE);
+        }
+        if !SetForegroundWindow(hwnd).as_bool() {
+            warn!(
+                target: "notabot::window",
+                %title,
+                "Windows refused to bring the window to the foreground"
+            );
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+#[cfg(all(feature = "x11", not(windows)))]
+fn focus_window_impl(title_contains: &str) -> Result<bool> {
+    x11::focus_window(title_contains)
+}
+
+#[cfg(all(not(feature = "x11"), not(windows)))]
+fn focus_window_impl(_title_contains: &str) -> Result<bool> {
+    // No-op without a window system integration.
     warn!(
         target: "notabot::window",
-        %title_contains,
-        "focus_window is not implemented yet on Windows; returning Ok(false)"
+        "focus_window is not supported on this platform; returning Ok(false)"
     );
     Ok(false)
 }
 
-#[cfg(not(windows))]
-fn focus_window_impl(_title_contains: &str) -> Result<bool> {
-    // No-op on non-Windows platforms.
+/// Check whether a window whose title contains the given substring exists, without
+/// focusing it. Same matching and platform support as `focus_window`; returns Ok(false)
+/// on unsupported platforms.
+pub fn find_window(title_contains: &str) -> Result<bool> {
+    trace!(target: "notabot::window", %title_contains, "find_window requested");
+    find_window_impl(title_contains)
+}
+
+#[cfg(windows)]
+fn find_window_impl(title_contains: &str) -> Result<bool> {
+    Ok(win32::top_level_windows()?
+        .iter()
+        .any(|(_, title)| title_matches(title, title_contains)))
+}
+
+#[cfg(all(feature = "x11", not(windows)))]
+fn find_window_impl(title_contains: &str) -> Result<bool> {
+    x11::find_window(title_contains)
+}
+
+#[cfg(all(not(feature = "x11"), not(windows)))]
+fn find_window_impl(_title_contains: &str) -> Result<bool> {
     warn!(
         target: "notabot::window",
-        "focus_window is not supported on this platform; returning Ok(false)"
+        "find_window is not supported on this platform; returning Ok(false)"
     );
     Ok(false)
 }
+
+/// Case-insensitive check that a window `title` contains `title_contains`.
+#[cfg_attr(not(any(feature = "x11", windows)), allow(dead_code))]
+pub(crate) fn title_matches(title: &str, title_contains: &str) -> bool {
+    title
+        .to_lowercase()
+        .contains(&title_contains.to_lowercase())
+}
+
+/// Look up the screen bounds of the first window whose title contains the substring.
+///
+/// Returns:
+/// - Ok(Some(rect)) with the window's top-left origin and size in screen coordinates.
+/// - Ok(None) if no matching window was found.
+/// - Err(_) on unsupported platforms and for unexpected internal errors.
+///
+/// Supported on Windows and, with the `x11` feature, on X11 (same matching as
+/// `focus_window`); see `BOUNDS_SUPPORTED`.
+pub fn window_bounds(title_contains: &str) -> Result<Option<Rect>> {
+    debug!(target: "notabot::window", %title_contains, "window_bounds requested");
+    window_bounds_impl(title_contains)
+}
+
+/// Whether `window_bounds` can look up windows in this build.
+pub const BOUNDS_SUPPORTED: bool = cfg!(any(windows, feature = "x11"));
+
+/// Translate window-relative coordinates into absolute screen coordinates.
+pub fn relative_to_window(bounds: Rect, x: i32, y: i32) -> (i32, i32) {
+    (bounds.x.saturating_add(x), bounds.y.saturating_add(y))
+}
+
+#[cfg(windows)]
+fn window_bounds_impl(title_contains: &str) -> Result<Option<Rect>> {
+    let Some((hwnd, title)) = win32::top_level_windows()?
+        .into_iter()
+        .find(|(_, title)| title_matches(title, title_contains))
+    else {
+        return Ok(None);
+    };
+    let bounds = win32::window_rect(hwnd)?;
+    trace!(target: "notabot::window", %title, ?bounds, "Window bounds");
+    Ok(Some(bounds))
+}
+
+#[cfg(all(feature = "x11", not(windows)))]
+fn window_bounds_impl(title_contains: &str) -> Result<Option<Rect>> {
+    x11::window_bounds(title_contains)
+}
+
+#[cfg(all(not(feature = "x11"), not(windows)))]
+fn window_bounds_impl(_title_contains: &str) -> Result<Option<Rect>> {
+    anyhow::bail!(
+        "window_bounds is not supported on this platform (enable the `x11` feature on Linux/X11)"
+    )
+}
+
+#[cfg(all(feature = "x11", not(windows)))]
+mod x11 {
+    use anyhow::{Context, Result};
+    use tracing::{debug, trace};
+    use x11rb::CURRENT_TIME;
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xproto::{
+        Atom, AtomEnum, ClientMessageEvent, ConfigureWindowAux, ConnectionExt as _, EventMask,
+        StackMode, Window,
+    };
+    use x11rb::rust_connection::RustConnection;
+
+    use crate::config::Rect;
+
+    /// `_NET_ACTIVE_WINDOW` source indication for a regular application.
+    const SOURCE_APPLICATION: u32 = 1;
+
+    /// A connection to the X server with the EWMH atoms used here.
+    struct Display {
+        conn: RustConnection,
+        root: Window,
+        net_client_list: Atom,
+        net_wm_name: Atom,
+        net_active_window: Atom,
+        utf8_string: Atom,
+    }
+
+    pub(super) fn find_window(title_contains: &str) -> Result<bool> {
+        Ok(Display::connect()?.find(title_contains)?.is_some())
+    }
+
+    pub(super) fn window_bounds(title_contains: &str) -> Result<Option<Rect>> {
+        let display = Display::connect()?;
+        let Some(window) = display.find(title_contains)? else {
+            return Ok(None);
+        };
+        let bounds = display.bounds(window)?;
+        trace!(target: "notabot::window", window, ?bounds, "X11 window bounds");
+        Ok(Some(bounds))
+    }
+
+    pub(super) fn focus_window(title_contains: &str) -> Result<bool> {
+        let display = Display::connect()?;
+        let Some(window) = display.find(title_contains)? else {
+            return Ok(false);
+        };
+        debug!(target: "notabot::window", window, "Activating X11 window");
+        display.activate(window)?;
+        Ok(true)
+    }
+
+    impl Display {
+        fn connect() -> Result<Self> {
+            let (conn, screen_num) =
+                x11rb::connect(None).context("Failed to connect to the X server")?;
+            let root = conn.setup().roots[screen_num].root;
+            let intern =
+                |name: &[u8]| -> Result<Atom> { Ok(conn.intern_atom(false, name)?.reply()?.atom) };
+            Ok(Self {
+                net_client_list: intern(b"_NET_CLIENT_LIST")?,
+                net_wm_name: intern(b"_NET_WM_NAME")?,
+                net_active_window: intern(b"_NET_ACTIVE_WINDOW")?,
+                utf8_string: intern(b"UTF8_STRING")?,
+                conn,
+                root,
+            })
+        }
+
+        /// First top-level window whose title contains `title_contains`.
+        fn find(&self, title_contains: &str) -> Result<Option<Window>> {
+            for window in self.top_level_windows()? {
+                let Some(title) = self.window_title(window)? else {
+                    continue;
+                };
+                trace!(target: "notabot::window", window, %title, "X11 window");
+                if super::title_matches(&title, title_contains) {
+                    return Ok(Some(window));
+                }
+            }
+            Ok(None)
+        }
+
+        /// Size of `window` and the position of its top-left corner on the root window
+        /// (the geometry's own origin is relative to the parent, e.g. a WM frame).
+        fn bounds(&self, window: Window) -> Result<Rect> {
+            let geometry = self.conn.get_geometry(window)?.reply()?;
+            let origin = self
+                .conn
+                .translate_coordinates(window, self.root, 0, 0)?
+                .reply()?;
+            Ok(Rect {
+                x: origin.dst_x.into(),
+                y: origin.dst_y.into(),
+                width: geometry.width.into(),
+                height: geometry.height.into(),
+            })
+        }
+
+        /// Ask the window manager to activate `window` (restoring it if minimized), and
+        /// raise it ourselves for window managers that ignore the request.
+        fn activate(&self, window: Window) -> Result<()> {
+            let event = ClientMessageEvent::new(
+                32,
+                window,
+                self.net_active_window,
+                [SOURCE_APPLICATION, CURRENT_TIME, 0, 0, 0],
+            );
+            self.conn.send_event(
+                false,
+                self.root,
+                EventMask::SUBSTRUCTURE_REDIRECT | EventMask::SUBSTRUCTURE_NOTIFY,
+                event,
+            )?;
+            self.conn.configure_window(
+                window,
+                &ConfigureWindowAux::new().stack_mode(StackMode::ABOVE),
+            )?;
+            self.conn.flush()?;
+            Ok(())
+        }
+
+        /// Managed windows from `_NET_CLIENT_LIST`, or the root's children when the
+        /// window manager does not publish that list.
+        fn top_level_windows(&self) -> Result<Vec<Window>> {
+            let list = self
+                .conn
+                .get_property(
+                    false,
+                    self.root,
+                    self.net_client_list,
+                    AtomEnum::WINDOW,
+                    0,
+                    u32::MAX,
+                )?
+                .reply()?;
+            if let Some(windows) = list.value32() {
+                let windows: Vec<Window> = windows.collect();
+                if !windows.is_empty() {
+                    return Ok(windows);
+                }
+            }
+            Ok(self.conn.query_tree(self.root)?.reply()?.children)
+        }
+
+        /// `_NET_WM_NAME` (UTF-8), falling back to the legacy `WM_NAME`.
+        fn window_title(&self, window: Window) -> Result<Option<String>> {
+            for (property, kind) in [
+                (self.net_wm_name, self.utf8_string),
+                (AtomEnum::WM_NAME.into(), AtomEnum::ANY.into()),
+            ] {
+                let reply = self
+                    .conn
+                    .get_property(false, window, property, kind, 0, u32::MAX)?
+                    .reply()?;
+                if !reply.value.is_empty() {
+                    return Ok(Some(String::from_utf8_lossy(&reply.value).into_owned()));
+                }
+            }
+            Ok(None)
+        }
+    }
+}
+
+#[cfg(windows)]
+mod win32 {
+    use anyhow::{Context, Result};
+    use windows::Win32::Foundation::{HWND, LPARAM, RECT};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        EnumWindows, GetWindowRect, GetWindowTextLengthW, GetWindowTextW, IsWindowVisible,
+    };
+    use windows::core::BOOL;
+
+    use crate::config::Rect;
+
+    /// Screen bounds of `hwnd` (including its frame).
+    pub(super) fn window_rect(hwnd: HWND) -> Result<Rect> {
+        let mut rect = RECT::default();
+        // SAFETY: `rect` outlives the call; a stale `hwnd` only makes it fail.
+        unsafe { GetWindowRect(hwnd, &mut rect) }.context("GetWindowRect failed")?;
+        Ok(Rect {
+            x: rect.left,
+            y: rect.top,
+            width: rect.right.saturating_sub(rect.left),
+            height: rect.bottom.saturating_sub(rect.top),
+        })
+    }
+
+    /// Visible top-level windows with a non-empty title, in Z order.
+    pub(super) fn top_level_windows() -> Result<Vec<(HWND, String)>> {
+        let mut windows: Vec<(HWND, String)> = Vec::new();
+        // SAFETY: the callback only runs during this call, while `windows` is borrowed.
+        unsafe {
+            EnumWindows(
+                Some(collect_window),
+                LPARAM(&mut windows as *mut Vec<(HWND, String)> as isize),
+            )
+        }
+        .context("EnumWindows failed")?;
+        Ok(windows)
+    }
+
+    unsafe extern "system" fn collect_window(hwnd: HWND, lparam: LPARAM) -> BOOL {
+        // SAFETY: `lparam` is the `Vec` passed by `top_level_windows`.
+        let windows = unsafe { &mut *(lparam.0 as *mut Vec<(HWND, String)>) };
+        unsafe {
+            if IsWindowVisible(hwnd).as_bool() {
+                let len = GetWindowTextLengthW(hwnd);
+                if len > 0 {
+                    let mut buf = vec![0u16; len as usize + 1];
+                    let copied = GetWindowTextW(hwnd, &mut buf).max(0) as usize;
+                    windows.push((hwnd, String::from_utf16_lossy(&buf[..copied])));
+                }
+            }
+        }
+        // Keep enumerating
+        BOOL(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_title_matches_ignores_case() {
+        assert!(title_matches("Untitled - Notepad", "notepad"));
+        assert!(title_matches("Calculator", "CALC"));
+        assert!(!title_matches("Calculator", "Notepad"));
+    }
+
+    #[test]
+    fn test_relative_to_window_offsets_origin() {
+        let bounds = Rect {
+            x: 100,
+            y: 250,
+            width: 800,
+            height: 600,
+        };
+        assert_eq!(relative_to_window(bounds, 10, 20), (110, 270));
+        assert_eq!(relative_to_window(bounds, -5, 0), (95, 250));
+    }
+
+    #[test]
+    fn test_relative_to_window_saturates() {
+        let bounds = Rect {
+            x: i32::MAX - 1,
+            y: 0,
+            width: 1,
+            height: 1,
+        };
+        assert_eq!(relative_to_window(bounds, 10, 1), (i32::MAX, 1));
+    }
+}
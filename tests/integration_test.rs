@@ -1 +1,823 @@
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::thread;
 
+use notabot::config::{ActionDef, Config};
+use notabot::executor::Runtime;
+
+/// Spawn a one-shot TCP peer that reads a line and answers with `reply`.
+/// Returns the bound address and a handle yielding the received line.
+fn spawn_ack_peer(reply: &'static str) -> (String, thread::JoinHandle<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    let handle = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut line = String::new();
+        BufReader::new(&stream).read_line(&mut line).unwrap();
+        stream.write_all(format!("{reply}\n").as_bytes()).unwrap();
+        line.trim().to_string()
+    });
+    (addr, handle)
+}
+
+fn tcp_send_runtime(addr: &str, expect_ack: bool) -> Runtime {
+    let mut cfg = Config::default();
+    cfg.workflows.insert(
+        "notify_peer".into(),
+        vec![ActionDef::TcpSend {
+            addr: addr.into(),
+            line: "{\"type\":\"done\",\"id\":\"{{id}}\"}".into(),
+            expect_ack,
+        }],
+    );
+    // TcpSend does not touch Enigo, so a real (non-dry-run) runtime is fine here.
+    Runtime::new(cfg, false)
+}
+
+#[tokio::test]
+async fn tcp_send_waits_for_ok_ack() {
+    let (addr, peer) = spawn_ack_peer("OK");
+    let mut rt = tcp_send_runtime(&addr, true);
+
+    let vars = HashMap::from([("id".to_string(), "42".to_string())]);
+    rt.run_workflow_by_name("notify_peer", vars).await.unwrap();
+
+    assert_eq!(peer.join().unwrap(), r#"{"type":"done","id":"42"}"#);
+}
+
+#[tokio::test]
+async fn tcp_send_fails_on_non_ok_ack() {
+    let (addr, peer) = spawn_ack_peer("ERROR busy");
+    let mut rt = tcp_send_runtime(&addr, true);
+
+    let err = rt
+        .run_workflow_by_name("notify_peer", HashMap::new())
+        .await
+        .unwrap_err();
+    assert!(format!("{err:#}").contains("ERROR busy"), "{err:#}");
+    peer.join().unwrap();
+}
+
+#[tokio::test]
+async fn source_transform_makes_event_routable() {
+    use notabot::sources::EventTransform;
+    use serde_json::json;
+
+    let cfg = notabot::config::load_from_str(
+        r#"{
+            "sources": [{ "type": "stdin", "transform": { "type": "t" } }],
+            "workflows": { "greet": [{ "type": "log", "level": "info", "message": "hi" }] },
+            "events": { "greet": { "workflow": "greet" } }
+        }"#,
+    )
+    .unwrap();
+    let spec = cfg.sources[0].transform().unwrap().clone();
+    let mut rt = Runtime::new(cfg, true);
+
+    let raw = json!({ "t": "greet" });
+    assert!(
+        rt.run_event(&raw).await.is_err(),
+        "untransformed event has no 'type'"
+    );
+
+    let transform = EventTransform::new(&spec).unwrap();
+    rt.run_event(&transform.apply(raw)).await.unwrap();
+}
+
+#[test]
+fn globals_referencing_globals_are_resolved_at_load() {
+    let cfg = notabot::config::load_from_str(
+        r#"{
+            "globals": {
+                "app": "Notabot",
+                "greeting": "Hello {{@app}}",
+                "loop_a": "{{@loop_b}}",
+                "loop_b": "{{@loop_a}}"
+            }
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(cfg.globals["greeting"], "Hello Notabot");
+    // Cycles are not expanded further; their tokens are left intact.
+    for key in ["loop_a", "loop_b"] {
+        assert!(cfg.globals[key].as_str().unwrap().contains("{{@loop_"));
+    }
+}
+
+#[test]
+fn require_arm_starts_disarmed_and_rejects_unreadable_keys() {
+    let cfg =
+        notabot::config::load_from_str(r#"{ "require_arm": true, "arm_key": "f9" }"#).unwrap();
+    let rt = Runtime::new(cfg, true);
+    assert!(!rt.arm_switch().is_armed());
+
+    let err = notabot::config::load_from_str(r#"{ "require_arm": true, "arm_key": "ctrl" }"#)
+        .unwrap_err();
+    assert!(err.to_string().contains("arm_key"), "{err:#}");
+
+    let err = notabot::config::load_from_str(
+        r#"{ "require_arm": true, "sources": [{ "type": "stdin" }] }"#,
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("stdin source"), "{err:#}");
+}
+
+#[test]
+fn parallel_rejects_input_actions_at_load_even_through_refs() {
+    let err = notabot::config::load_from_str(
+        r#"{
+            "actions": { "press": { "type": "key_seq", "text": "hi" } },
+            "workflows": { "wf": [{ "type": "parallel", "steps": [
+                { "type": "sleep_ms", "ms": 10 },
+                { "type": "sequence", "steps": [{ "type": "ref", "name": "press" }] }
+            ]}]}
+        }"#,
+    )
+    .unwrap_err();
+    let msg = format!("{err:#}");
+    assert!(
+        msg.contains("parallel step 1 contains input action 'key_seq'"),
+        "{msg}"
+    );
+
+    notabot::config::load_from_str(
+        r#"{ "workflows": { "wf": [{ "type": "parallel", "steps": [
+            { "type": "sleep_ms", "ms": 10 },
+            { "type": "log", "level": "info", "message": "hi" }
+        ]}]}}"#,
+    )
+    .unwrap();
+}
+
+#[test]
+fn regex_extract_patterns_are_checked_at_load() {
+    let load = |pattern: &str, group: u32| {
+        notabot::config::load_from_str(&format!(
+            r#"{{ "workflows": {{ "wf": [{{ "type": "regex_extract", "input": "x",
+                "pattern": "{pattern}", "into_var": "v", "group": {group} }}] }} }}"#
+        ))
+    };
+    let err = load("(unclosed", 1).unwrap_err();
+    assert!(
+        format!("{err:#}").contains("Invalid regex_extract pattern"),
+        "{err:#}"
+    );
+    let err = load("(a)", 2).unwrap_err();
+    assert!(format!("{err:#}").contains("no capture group 2"), "{err:#}");
+    load("(a)", 1).unwrap();
+    // Without `group` the first capture group is extracted, so the pattern needs one
+    let err = notabot::config::load_from_str(
+        r#"{ "workflows": { "wf": [{ "type": "regex_extract", "input": "x",
+            "pattern": "a+", "into_var": "v" }] } }"#,
+    )
+    .unwrap_err();
+    assert!(format!("{err:#}").contains("no capture group 1"), "{err:#}");
+    // Patterns with tokens are only known at run time
+    load("{{p}}(", 1).unwrap();
+}
+
+#[test]
+fn nested_workflow_references_must_resolve() {
+    let load = |target: &str| {
+        notabot::config::load_from_str(&format!(
+            r#"{{ "workflows": {{
+                "followup": [],
+                "main": [
+                    {{ "type": "log", "level": "info", "message": "start" }},
+                    {{ "type": "sequence", "steps": [
+                        {{ "type": "log", "level": "info", "message": "inner" }},
+                        {{ "type": "conditional", "when": "a", "equals": "a",
+                          "then": {{ "type": "schedule_workflow", "name": "{target}", "delay_ms": 10 }} }}
+                    ] }}
+                ]
+            }} }}"#
+        ))
+    };
+    let err = format!("{:#}", load("missing").unwrap_err());
+    assert!(
+        err.contains(
+            "Invalid reference in workflow 'main' at step 1: Invalid reference in sequence at index 1: \
+             Invalid reference in conditional `then` branch: \
+             Scheduled workflow 'missing' was not found in `workflows`"
+        ),
+        "{err}"
+    );
+    load("followup").unwrap();
+    load("{{next}}").unwrap();
+}
+
+#[test]
+fn configs_are_checked_against_the_generated_schema() {
+    let mut cfg = notabot::config::load_from_str(
+        r#"{ "workflows": { "wf": [ { "type": "log", "level": "info", "message": "hi" } ] } }"#,
+    )
+    .unwrap();
+    notabot::config::loader::validate_against_schema(&cfg).unwrap();
+
+    // A value JSON cannot carry serializes as `null`, which the schema rejects
+    cfg.dry_run_time_scale = f32::NAN;
+    let err = format!("{:#}", notabot::config::validate_config(&cfg).unwrap_err());
+    assert!(
+        err.contains("Config does not match its schema:\n  /dry_run_time_scale: null is not of type \"number\""),
+        "{err}"
+    );
+}
+
+#[test]
+fn invalid_event_schemas_are_rejected_at_load() {
+    let err = notabot::config::load_from_str(
+        r#"{ "workflows": { "wf": [] },
+             "events": { "e": { "workflow": "wf", "schema": { "type": "text" } } } }"#,
+    )
+    .unwrap_err();
+    assert!(
+        format!("{err:#}").contains("Event 'e' has an invalid schema"),
+        "{err:#}"
+    );
+}
+
+#[test]
+fn call_workflow_targets_must_exist() {
+    let err = notabot::config::load_from_str(
+        r#"{ "workflows": { "main": [ { "type": "call_workflow", "name": "missing" } ] } }"#,
+    )
+    .unwrap_err();
+    assert!(
+        format!("{err:#}").contains("Called workflow 'missing' was not found in `workflows`"),
+        "{err:#}"
+    );
+
+    // Input actions stay out of `parallel`, even inside a called workflow
+    let err = notabot::config::load_from_str(
+        r#"{ "workflows": {
+            "click": [ { "type": "mouse_click", "button": "left" } ],
+            "main": [ { "type": "parallel", "steps": [ { "type": "call_workflow", "name": "click" } ] } ]
+        } }"#,
+    )
+    .unwrap_err();
+    assert!(
+        format!("{err:#}").contains("contains input action 'mouse_click'"),
+        "{err:#}"
+    );
+}
+
+#[test]
+fn ref_cycles_are_rejected_at_load() {
+    let err = notabot::config::load_from_str(
+        r#"{ "actions": { "again": { "type": "sequence", "steps": [
+                { "type": "ref", "name": "again" } ] } } }"#,
+    )
+    .unwrap_err();
+    assert!(
+        format!("{err:#}").contains("Reference cycle: action 'again' -> action 'again'"),
+        "{err:#}"
+    );
+
+    let err = notabot::config::load_from_str(
+        r#"{ "actions": {
+            "a": { "type": "ref", "name": "b" },
+            "b": { "type": "repeat", "count": 2, "body": { "type": "ref", "name": "c" } },
+            "c": { "type": "conditional", "when": "x", "equals": "y",
+                   "then": { "type": "log", "level": "info", "message": "ok" },
+                   "else": { "type": "ref", "name": "a" } }
+        } }"#,
+    )
+    .unwrap_err();
+    assert!(
+        format!("{err:#}").contains("action 'a' -> action 'b' -> action 'c' -> action 'a'"),
+        "{err:#}"
+    );
+
+    // Cycles through `call_workflow` are caught too
+    let err = notabot::config::load_from_str(
+        r#"{ "actions": { "relay": { "type": "call_workflow", "name": "main" } },
+             "workflows": { "main": [ { "type": "ref", "name": "relay" } ] } }"#,
+    )
+    .unwrap_err();
+    assert!(
+        format!("{err:#}").contains("action 'relay' -> workflow 'main' -> action 'relay'"),
+        "{err:#}"
+    );
+
+    // Shared targets without a cycle are fine
+    notabot::config::load_from_str(
+        r#"{ "actions": {
+            "leaf": { "type": "log", "level": "info", "message": "ok" },
+            "both": { "type": "sequence", "steps": [
+                { "type": "ref", "name": "leaf" }, { "type": "ref", "name": "leaf" } ] }
+        } }"#,
+    )
+    .unwrap();
+}
+
+#[test]
+fn pixel_color_check_colors_and_branches_are_checked_at_load() {
+    let load = |expected: &str, then: &str| {
+        notabot::config::load_from_str(&format!(
+            r#"{{ "workflows": {{ "wf": [{{ "type": "pixel_color_check", "x": 10, "y": 20,
+                "expected": "{expected}", "then": {then} }}] }} }}"#
+        ))
+    };
+    let log = r#"{ "type": "log", "level": "info", "message": "ok" }"#;
+    let err = load("#12345", log).unwrap_err();
+    assert!(format!("{err:#}").contains("expected #RRGGBB"), "{err:#}");
+    let err = load("#123456", r#"{ "type": "ref", "name": "missing" }"#).unwrap_err();
+    assert!(
+        format!("{err:#}").contains("pixel_color_check `then`"),
+        "{err:#}"
+    );
+    load("#1e90FF", log).unwrap();
+    load("{{color}}", log).unwrap();
+}
+
+#[test]
+fn mouse_move_window_rel_needs_window_support() {
+    let result = notabot::config::load_from_str(
+        r#"{ "workflows": { "wf": [{ "type": "try",
+            "body": { "type": "mouse_move_window_rel", "title_contains": "Calc", "x": 1, "y": 2 },
+            "catch": { "type": "log", "level": "warn", "message": "{{error}}" } }] } }"#,
+    );
+    if cfg!(any(windows, feature = "x11")) {
+        result.unwrap();
+    } else {
+        let err = result.unwrap_err();
+        assert!(format!("{err:#}").contains("not supported"), "{err:#}");
+    }
+}
+
+#[test]
+fn http_source_paths_must_be_absolute() {
+    let load = |path: &str| {
+        notabot::config::load_from_str(&format!(
+            r#"{{ "sources": [{{ "type": "http", "bind": "127.0.0.1:0", "path": "{path}" }}] }}"#
+        ))
+    };
+    let err = load("events").unwrap_err();
+    assert!(
+        format!("{err:#}").contains("must start with '/'"),
+        "{err:#}"
+    );
+    assert!(load("").is_err());
+    load("/events").unwrap();
+}
+
+#[test]
+fn websocket_source_urls_must_use_ws() {
+    let load = |url: &str| {
+        notabot::config::load_from_str(&format!(
+            r#"{{ "sources": [{{ "type": "websocket", "url": "{url}" }}] }}"#
+        ))
+    };
+    let err = load("wss://127.0.0.1:9000/events").unwrap_err();
+    assert!(
+        format!("{err:#}").contains("must start with 'ws://'"),
+        "{err:#}"
+    );
+    assert!(load("http://127.0.0.1:9000/events").is_err());
+    assert!(load("127.0.0.1:9000").is_err());
+    load("ws://127.0.0.1:9000/events").unwrap();
+}
+
+#[test]
+fn zero_config_drift_interval_is_rejected() {
+    let err = notabot::config::load_from_str(r#"{ "config_drift_check_ms": 0 }"#).unwrap_err();
+    assert!(
+        format!("{err:#}").contains("config_drift_check_ms must be greater than 0"),
+        "{err:#}"
+    );
+    notabot::config::load_from_str(r#"{ "config_drift_check_ms": "30s" }"#).unwrap();
+}
+
+#[tokio::test]
+async fn missing_config_path_falls_back_to_embedded_default() {
+    let dir = tempfile::tempdir().unwrap();
+    let cfg = notabot::config::load_from_path_or_default_async(dir.path().join("missing.json"))
+        .await
+        .unwrap();
+    assert_eq!(cfg, notabot::config::embedded_default().unwrap());
+    assert!(cfg.workflows.contains_key("echo"));
+
+    // An existing but invalid file is still an error
+    let bad = dir.path().join("bad.json");
+    std::fs::write(&bad, "{ not json").unwrap();
+    assert!(
+        notabot::config::load_from_path_or_default_async(&bad)
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn yaml_config_loads_like_json() {
+    let yaml = r#"
+globals:
+  app: Notabot
+workflows:
+  greet:
+    - type: type_text
+      text: "Hello {{name}} from {{@app}}"
+    - type: sleep_ms
+      ms: 1.5s
+events:
+  greet:
+    workflow: greet
+    vars_map:
+      name: user.name
+"#;
+    let json = r#"{
+        "globals": { "app": "Notabot" },
+        "workflows": {
+            "greet": [
+                { "type": "type_text", "text": "Hello {{name}} from {{@app}}" },
+                { "type": "sleep_ms", "ms": 1500 }
+            ]
+        },
+        "events": { "greet": { "workflow": "greet", "vars_map": { "name": "user.name" } } }
+    }"#;
+    let expected = notabot::config::load_from_str(json).unwrap();
+    assert_eq!(notabot::config::load_from_str_yaml(yaml).unwrap(), expected);
+
+    let dir = tempfile::tempdir().unwrap();
+    for name in ["flow.yaml", "flow.YML"] {
+        let path = dir.path().join(name);
+        std::fs::write(&path, yaml).unwrap();
+        assert_eq!(notabot::config::load_from_path(&path).unwrap(), expected);
+        assert_eq!(
+            notabot::config::load_from_path_async(&path).await.unwrap(),
+            expected
+        );
+    }
+
+    // Validation runs for YAML too
+    let err =
+        notabot::config::load_from_str_yaml("events:\n  x:\n    workflow: missing\n").unwrap_err();
+    assert!(format!("{err:#}").contains("missing"), "{err:#}");
+}
+
+#[test]
+fn env_vars_are_expanded_at_load() {
+    use notabot::config::SourceConfig;
+
+    // SAFETY: test-only variables with unique names, not read by other tests
+    unsafe {
+        std::env::set_var("NOTABOT_TEST_ENV_DIR", "/tmp/notabot-env");
+        std::env::set_var("NOTABOT_TEST_ENV_PORT", "5123");
+    }
+    let cfg = notabot::config::load_from_str(
+        r#"{
+            "sources": [
+                { "type": "file", "path": "${NOTABOT_TEST_ENV_DIR}/events.json" },
+                { "type": "tcp", "bind": "127.0.0.1:${NOTABOT_TEST_ENV_PORT}" }
+            ],
+            "workflows": {
+                "greet": [{ "type": "type_text", "text": "${NOTABOT_TEST_ENV_GREETING:-Hi} {{name}}" }]
+            }
+        }"#,
+    )
+    .unwrap();
+    assert!(matches!(
+        &cfg.sources[0],
+        SourceConfig::File { path, .. } if path == "/tmp/notabot-env/events.json"
+    ));
+    assert!(matches!(
+        &cfg.sources[1],
+        SourceConfig::Tcp { bind, .. } if bind == "127.0.0.1:5123"
+    ));
+    assert!(matches!(
+        &cfg.workflows["greet"][0],
+        ActionDef::TypeText { text, .. } if text == "Hi {{name}}"
+    ));
+
+    let err = notabot::config::load_from_str(
+        r#"{ "sources": [{ "type": "file", "path": "${NOTABOT_TEST_ENV_UNSET}/x" }] }"#,
+    )
+    .unwrap_err();
+    let msg = format!("{err:#}");
+    assert!(msg.contains("NOTABOT_TEST_ENV_UNSET"), "{msg}");
+    assert!(msg.contains("sources[0].path"), "{msg}");
+}
+
+#[tokio::test]
+async fn tcp_source_reads_length_prefixed_frames() {
+    use notabot::config::TcpFraming;
+    use notabot::sources::{EventSource, TcpSource};
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    // Reserve an ephemeral port for the source
+    let addr = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .to_string();
+    let source = TcpSource::new(addr.clone(), true).with_framing(TcpFraming::LengthPrefixed);
+    let (tx, mut rx) = tokio::sync::mpsc::channel(4);
+    let handle = source.start(tx, tokio_util::sync::CancellationToken::new());
+
+    let mut client = loop {
+        match tokio::net::TcpStream::connect(&addr).await {
+            Ok(c) => break c,
+            Err(_) => tokio::time::sleep(std::time::Duration::from_millis(10)).await,
+        }
+    };
+    let payload = "{\n  \"type\": \"note\",\n  \"text\": \"line one\\nline two\"\n}";
+    let mut frame = (payload.len() as u32).to_be_bytes().to_vec();
+    frame.extend_from_slice(payload.as_bytes());
+    client.write_all(&frame).await.unwrap();
+
+    let mut reply = String::new();
+    BufReader::new(&mut client)
+        .read_line(&mut reply)
+        .await
+        .unwrap();
+    assert_eq!(reply, "OK\n");
+    let event = rx.recv().await.unwrap();
+    assert_eq!(event["type"], "note");
+    assert_eq!(event["text"], "line one\nline two");
+    handle.abort();
+}
+
+#[test]
+fn tcp_tls_settings_are_checked_when_sources_are_built() {
+    let cfg = notabot::config::load_from_str(
+        r#"{ "sources": [{
+            "type": "tcp",
+            "bind": "127.0.0.1:0",
+            "tls": { "cert_path": "missing-cert.pem", "key_path": "missing-key.pem" }
+        }] }"#,
+    )
+    .unwrap();
+    let err = match notabot::sources::build_sources_from_config(&cfg) {
+        Ok(_) => panic!("missing TLS files must fail the build"),
+        Err(err) => format!("{err:#}"),
+    };
+    assert!(err.contains("source #0"), "{err}");
+    if cfg!(feature = "tls") {
+        assert!(err.contains("missing-cert.pem"), "{err}");
+    } else {
+        assert!(err.contains("`tls` feature"), "{err}");
+    }
+}
+
+#[cfg(feature = "tls")]
+#[tokio::test]
+async fn tcp_source_serves_tls() {
+    use notabot::config::TlsConfig;
+    use notabot::sources::{EventSource, TcpSource};
+    use std::sync::Arc;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio_rustls::TlsConnector;
+    use tokio_rustls::rustls::pki_types::pem::PemObject;
+    use tokio_rustls::rustls::pki_types::{CertificateDer, ServerName};
+    use tokio_rustls::rustls::{ClientConfig, RootCertStore, crypto::ring};
+
+    let fixtures = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/tls");
+    let tls = TlsConfig {
+        cert_path: format!("{fixtures}/cert.pem"),
+        key_path: format!("{fixtures}/key.pem"),
+    };
+    let addr = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .to_string();
+    let source = TcpSource::new(addr.clone(), true).with_tls(&tls).unwrap();
+    let (tx, mut rx) = tokio::sync::mpsc::channel(4);
+    let handle = source.start(tx, tokio_util::sync::CancellationToken::new());
+
+    let mut roots = RootCertStore::empty();
+    roots
+        .add(CertificateDer::from_pem_file(&tls.cert_path).unwrap())
+        .unwrap();
+    let client_config = ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    let tcp = loop {
+        match tokio::net::TcpStream::connect(&addr).await {
+            Ok(c) => break c,
+            Err(_) => tokio::time::sleep(std::time::Duration::from_millis(10)).await,
+        }
+    };
+    let mut client = TlsConnector::from(Arc::new(client_config))
+        .connect(ServerName::try_from("localhost").unwrap(), tcp)
+        .await
+        .unwrap();
+    client.write_all(b"{\"type\":\"secure\"}\n").await.unwrap();
+
+    let mut reply = String::new();
+    BufReader::new(&mut client)
+        .read_line(&mut reply)
+        .await
+        .unwrap();
+    assert_eq!(reply, "OK\n");
+    assert_eq!(rx.recv().await.unwrap()["type"], "secure");
+    handle.abort();
+}
+
+/// Spawn a tiny HTTP server answering every GET with `{"status": ...}`: "pending"
+/// for the first `pending_polls` requests, then "done". Serves `total` requests.
+#[cfg(feature = "http-client")]
+fn spawn_status_server(pending_polls: usize, total: usize) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    thread::spawn(move || {
+        for n in 0..total {
+            let Ok((mut stream, _)) = listener.accept() else {
+                return;
+            };
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap_or(0) > 0 && line != "\r\n" {
+                line.clear();
+            }
+            let status = if n < pending_polls { "pending" } else { "done" };
+            let body = format!(r#"{{"job": {{"status": "{status}"}}}}"#);
+            let _ = write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+        }
+    });
+    format!("http://{addr}/status")
+}
+
+#[cfg(feature = "http-client")]
+fn http_poll_runtime(url: &str, timeout_ms: u64) -> Runtime {
+    let mut cfg = Config::default();
+    cfg.workflows.insert(
+        "wait_done".into(),
+        vec![ActionDef::HttpPollUntil {
+            url: url.into(),
+            path: "job.status".into(),
+            equals: "{{want}}".into(),
+            interval_ms: Some(10),
+            timeout_ms: Some(timeout_ms),
+        }],
+    );
+    Runtime::new(cfg, false)
+}
+
+#[cfg(not(feature = "http-client"))]
+#[test]
+fn http_poll_until_is_rejected_without_http_client() {
+    let err = notabot::config::load_from_str(
+        r#"{ "workflows": { "wf": [{ "type": "http_poll_until",
+            "url": "http://127.0.0.1:1/status", "path": "status", "equals": "done" }] } }"#,
+    )
+    .unwrap_err();
+    assert!(
+        format!("{err:#}").contains("`http-client` feature"),
+        "{err:#}"
+    );
+}
+
+#[test]
+fn http_request_methods_are_checked_at_load() {
+    let load = |method: &str| {
+        notabot::config::load_from_str(&format!(
+            r#"{{ "workflows": {{ "wf": [{{ "type": "http_request", "method": "{method}",
+                "url": "http://127.0.0.1:1/" }}] }} }}"#
+        ))
+    };
+    let err = load("PSOT").unwrap_err();
+    assert!(
+        format!("{err:#}").contains("Unsupported HTTP method 'PSOT'"),
+        "{err:#}"
+    );
+    if cfg!(feature = "http-client") {
+        load("patch").unwrap();
+        // Templated methods are only known at run time
+        load("{{verb}}").unwrap();
+    } else {
+        let err = load("GET").unwrap_err();
+        assert!(
+            format!("{err:#}").contains("http_request requires the `http-client` feature"),
+            "{err:#}"
+        );
+    }
+}
+
+#[cfg(feature = "http-client")]
+#[tokio::test]
+async fn http_poll_until_waits_for_field_to_flip() {
+    let url = spawn_status_server(2, 3);
+    let mut rt = http_poll_runtime(&url, 5_000);
+    let vars = HashMap::from([("want".to_string(), "done".to_string())]);
+    rt.run_workflow_by_name("wait_done", vars).await.unwrap();
+}
+
+#[cfg(feature = "http-client")]
+#[tokio::test]
+async fn http_poll_until_times_out() {
+    let url = spawn_status_server(usize::MAX, 1_000);
+    let mut rt = http_poll_runtime(&url, 100);
+    let vars = HashMap::from([("want".to_string(), "done".to_string())]);
+    let err = rt
+        .run_workflow_by_name("wait_done", vars)
+        .await
+        .unwrap_err();
+    assert!(format!("{err:#}").contains("last: 'pending'"), "{err:#}");
+}
+
+/// A received request: "METHOD /path", its `x-token` header, and its body.
+#[cfg(feature = "http-client")]
+type ReceivedRequest = (String, String, String);
+
+/// Serve one canned `(status, body)` response per connection, collecting each request.
+#[cfg(feature = "http-client")]
+fn spawn_http_peer(
+    responses: Vec<(u16, &'static str)>,
+) -> (String, thread::JoinHandle<Vec<ReceivedRequest>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let handle = thread::spawn(move || {
+        let mut requests = Vec::new();
+        for (status, body) in responses {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let (mut token, mut length) = (String::new(), 0);
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 0 && line != "\r\n" {
+                let (name, value) = line.split_once(':').unwrap();
+                match name.to_ascii_lowercase().as_str() {
+                    "x-token" => token = value.trim().to_string(),
+                    "content-length" => length = value.trim().parse().unwrap(),
+                    _ => {}
+                }
+                line.clear();
+            }
+            let mut received = vec![0u8; length];
+            std::io::Read::read_exact(&mut reader, &mut received).unwrap();
+            let target = request_line
+                .split(' ')
+                .take(2)
+                .collect::<Vec<_>>()
+                .join(" ");
+            requests.push((target, token, String::from_utf8(received).unwrap()));
+            write!(
+                stream,
+                "HTTP/1.1 {status} X\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            )
+            .unwrap();
+        }
+        requests
+    });
+    (format!("http://{addr}"), handle)
+}
+
+#[cfg(feature = "http-client")]
+#[tokio::test]
+async fn http_request_sends_interpolated_request_and_stores_response() {
+    let (base, peer) = spawn_http_peer(vec![
+        (201, r#"{"id":7}"#),
+        (200, "ok"),
+        (503, "busy"),
+        (404, "nope"),
+    ]);
+    let cfg = notabot::config::load_from_str(&format!(
+        r#"{{ "workflows": {{
+            "notify": [{{ "type": "http_request", "method": "post", "url": "{base}/jobs/{{{{id}}}}",
+                "headers": {{ "X-Token": "{{{{token}}}}" }}, "body": "{{\"done\":\"{{{{id}}}}\"}}",
+                "into_var": "reply" }},
+                {{ "type": "http_request", "method": "PUT", "url": "{base}/echo", "body": "{{{{reply}}}}" }}],
+            "strict": [{{ "type": "http_request", "method": "GET", "url": "{base}/health" }}],
+            "lenient": [{{ "type": "http_request", "method": "{{{{verb}}}}", "url": "{base}/jobs/1",
+                "into_var": "reply", "allow_error_status": true }}]
+        }} }}"#
+    ))
+    .unwrap();
+    let mut rt = Runtime::new(cfg, false);
+
+    let vars = HashMap::from([
+        ("id".to_string(), "42".to_string()),
+        ("token".to_string(), "s3cret".to_string()),
+    ]);
+    rt.run_workflow_by_name("notify", vars).await.unwrap();
+    let err = rt
+        .run_workflow_by_name("strict", HashMap::new())
+        .await
+        .unwrap_err();
+    assert!(format!("{err:#}").contains("503"), "{err:#}");
+    let vars = HashMap::from([("verb".to_string(), "delete".to_string())]);
+    rt.run_workflow_by_name("lenient", vars).await.unwrap();
+
+    let requests = peer.join().unwrap();
+    assert_eq!(
+        requests[0],
+        (
+            "POST /jobs/42".to_string(),
+            "s3cret".to_string(),
+            r#"{"done":"42"}"#.to_string()
+        )
+    );
+    assert_eq!(requests[1].0, "PUT /echo");
+    assert_eq!(requests[1].2, r#"{"id":7}"#);
+    assert_eq!(requests[2].0, "GET /health");
+    assert_eq!(requests[3].0, "DELETE /jobs/1");
+}